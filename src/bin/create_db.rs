@@ -1,5 +1,46 @@
+use bag_address_lookup::CreateOptions;
+
+const USAGE: &str = "Usage: create-db [--force] [--raw-names]
+
+Builds data/bag.bin from the BAG extract, downloading it to data/bag.zip first
+unless a complete copy is already there.
+
+Options:
+  -f, --force  Rebuild data/bag.bin even when it already exists; without it an
+               existing, non-empty database file is kept as is
+  --raw-names  Keep locality and street names exactly as in the extract,
+               instead of merging names that differ only in spacing or case
+  -h, --help   Print this help";
+
+/// Parse the command line into the options for `create_database_with`, or
+/// `None` when only the help was asked for.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<CreateOptions>, String> {
+    let mut options = CreateOptions::default();
+    for arg in args {
+        match arg.as_str() {
+            "-f" | "--force" => options.force = true,
+            "--raw-names" => options.normalize_names = false,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+    Ok(Some(options))
+}
+
 fn main() {
-    if let Err(e) = bag_address_lookup::create_database() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = bag_address_lookup::create_database_with(options) {
         eprintln!("Error creating database: {}", e);
         std::process::exit(1);
     }
@@ -1,5 +1,8 @@
 #[cfg(feature = "cli")]
-use bag_address_lookup::DatabaseHandle;
+use std::{collections::BTreeMap, iter::Peekable};
+
+#[cfg(feature = "cli")]
+use bag_address_lookup::{DatabaseError, DatabaseHandle, HouseNumberSuffix, RangeInfo};
 
 const VERSION_TEXT: &str = concat!(
     "BAG Address Lookup Service version ",
@@ -10,10 +13,20 @@ fn is_version_flag(arg: &str) -> bool {
     arg == "--version" || arg == "-v"
 }
 
+/// Load the database the service would serve: the file named by
+/// `BAG_ADDRESS_LOOKUP_DATABASE`, or else the embedded one.
 #[cfg(feature = "cli")]
-fn load_database() -> DatabaseHandle {
-    match DatabaseHandle::load() {
+fn load_database() -> DatabaseHandle<'static> {
+    let path = std::env::var_os("BAG_ADDRESS_LOOKUP_DATABASE").filter(|p| !p.is_empty());
+    match DatabaseHandle::open(path.as_deref().map(std::path::Path::new)) {
         Ok(database) => database,
+        Err(DatabaseError::NotEmbedded) => {
+            eprintln!(
+                "Error loading database: no database is embedded in this build; \
+                 set BAG_ADDRESS_LOOKUP_DATABASE to a database file"
+            );
+            std::process::exit(1);
+        }
         Err(err) => {
             eprintln!("Error loading database: {}", err);
             std::process::exit(1);
@@ -33,8 +46,8 @@ fn cmd_lookup(postal_code: &str, house_number_arg: &str) -> i32 {
 
     let database = load_database();
 
-    if let Some((public_space, locality)) = database.lookup(postal_code, house_number) {
-        println!("{public_space}\n{locality}");
+    if let Some(address) = database.lookup_detailed(postal_code, house_number) {
+        println!("{}\n{}", address.public_space, address.locality);
         0
     } else {
         eprintln!("No address found for {postal_code} {house_number}");
@@ -63,6 +76,189 @@ fn cmd_list_municipalities() -> i32 {
     0
 }
 
+/// Check that the database file at `path` loads and that every range
+/// resolves to its names. Prints the counts on success.
+#[cfg(feature = "cli")]
+fn cmd_validate(path: &str) -> i32 {
+    let database = match DatabaseHandle::load_from_path(std::path::Path::new(path)) {
+        Ok(database) => database,
+        Err(err) => {
+            eprintln!("Invalid database {path}: {err}");
+            return 1;
+        }
+    };
+
+    let resolved = database.ranges().count();
+    if resolved != database.range_count() {
+        eprintln!(
+            "Invalid database {path}: {} of {} ranges reference a missing name",
+            database.range_count() - resolved,
+            database.range_count()
+        );
+        return 1;
+    }
+
+    println!("{path} is valid");
+    println!("ranges\t{}", database.range_count());
+    println!("localities\t{}", database.locality_count());
+    println!("public spaces\t{}", database.public_spaces().count());
+    println!("municipalities\t{}", database.municipality_details().len());
+    0
+}
+
+/// Number of differing addresses `diff --verbose` prints.
+#[cfg(feature = "cli")]
+const DIFF_VERBOSE_LIMIT: usize = 20;
+
+/// A house number with its house letter and addition, if any.
+#[cfg(feature = "cli")]
+type AddressKey = (u32, Option<char>, Option<String>);
+
+/// Address changes between two databases.
+#[cfg(feature = "cli")]
+#[derive(Default)]
+struct DiffCounts {
+    added: usize,
+    removed: usize,
+    public_space_changed: usize,
+    locality_changed: usize,
+}
+
+/// Compare the addresses of two database files, postal code by postal code,
+/// and print how many were added, removed or got another name.
+#[cfg(feature = "cli")]
+fn cmd_diff(old_path: &str, new_path: &str, verbose: bool) -> i32 {
+    let load = |path: &str| {
+        DatabaseHandle::load_from_path(std::path::Path::new(path))
+            .map_err(|err| eprintln!("Error loading database {path}: {err}"))
+    };
+    let (Ok(old), Ok(new)) = (load(old_path), load(new_path)) else {
+        return 1;
+    };
+
+    let mut counts = DiffCounts::default();
+    let mut examples = Vec::new();
+    let mut example = |line: String| {
+        if verbose && examples.len() < DIFF_VERBOSE_LIMIT {
+            examples.push(line);
+        }
+    };
+
+    // Both databases are sorted by postal code, so they can be walked in
+    // step without holding either address list in memory.
+    let mut old_ranges = old.ranges().peekable();
+    let mut new_ranges = new.ranges().peekable();
+    let mut old_suffixes = old.house_number_suffixes().peekable();
+    let mut new_suffixes = new.house_number_suffixes().peekable();
+    loop {
+        let postal_code = match (old_ranges.peek(), new_ranges.peek()) {
+            (Some((old_pc, _)), Some((new_pc, _))) => old_pc.min(new_pc).clone(),
+            (Some((pc, _)), None) | (None, Some((pc, _))) => pc.clone(),
+            (None, None) => break,
+        };
+        let old_addresses = take_postal_code(&mut old_ranges, &mut old_suffixes, &postal_code);
+        let new_addresses = take_postal_code(&mut new_ranges, &mut new_suffixes, &postal_code);
+
+        for (address, &(public_space, locality)) in &old_addresses {
+            let house_number = format_house_number(address);
+            let Some(&(new_public_space, new_locality)) = new_addresses.get(address) else {
+                counts.removed += 1;
+                example(format!(
+                    "- {postal_code} {house_number}\t{public_space}, {locality}"
+                ));
+                continue;
+            };
+            if new_public_space != public_space {
+                counts.public_space_changed += 1;
+            }
+            if new_locality != locality {
+                counts.locality_changed += 1;
+            }
+            if (new_public_space, new_locality) != (public_space, locality) {
+                example(format!(
+                    "~ {postal_code} {house_number}\t{public_space}, {locality} -> \
+                     {new_public_space}, {new_locality}"
+                ));
+            }
+        }
+        for (address, (public_space, locality)) in &new_addresses {
+            if !old_addresses.contains_key(address) {
+                let house_number = format_house_number(address);
+                counts.added += 1;
+                example(format!(
+                    "+ {postal_code} {house_number}\t{public_space}, {locality}"
+                ));
+            }
+        }
+    }
+
+    println!("added\t{}", counts.added);
+    println!("removed\t{}", counts.removed);
+    println!("public space changed\t{}", counts.public_space_changed);
+    println!("locality changed\t{}", counts.locality_changed);
+    for line in examples {
+        println!("{line}");
+    }
+    0
+}
+
+/// Take the ranges and house letters / additions of `postal_code` from the
+/// front of `ranges` and `suffixes`, keyed by house number, letter and
+/// addition. Every house number of a range is also listed without a suffix.
+#[cfg(feature = "cli")]
+fn take_postal_code<'a>(
+    ranges: &mut Peekable<impl Iterator<Item = (String, RangeInfo<'a>)>>,
+    suffixes: &mut Peekable<impl Iterator<Item = (String, HouseNumberSuffix)>>,
+    postal_code: &str,
+) -> BTreeMap<AddressKey, (&'a str, &'a str)> {
+    let mut addresses = BTreeMap::new();
+    let mut postal_code_ranges = Vec::new();
+    while let Some((_, range)) = ranges.next_if(|(pc, _)| pc == postal_code) {
+        for house_number in range.house_numbers() {
+            addresses.insert(
+                (house_number, None, None),
+                (range.public_space, range.locality),
+            );
+        }
+        postal_code_ranges.push(range);
+    }
+
+    // Suffixes of postal codes without ranges have no names to compare.
+    while suffixes
+        .next_if(|(pc, _)| pc.as_str() < postal_code)
+        .is_some()
+    {}
+    while let Some((_, suffix)) = suffixes.next_if(|(pc, _)| pc == postal_code) {
+        let Some(range) = postal_code_ranges
+            .iter()
+            .find(|range| range.contains(suffix.house_number))
+        else {
+            continue;
+        };
+        let address = (
+            suffix.house_number,
+            suffix.letter(),
+            suffix.addition().map(str::to_owned),
+        );
+        addresses.insert(address, (range.public_space, range.locality));
+    }
+    addresses
+}
+
+/// Format an address key the way it is written on the door, e.g. `12A-2`.
+#[cfg(feature = "cli")]
+fn format_house_number((house_number, letter, addition): &AddressKey) -> String {
+    let mut formatted = house_number.to_string();
+    if let Some(letter) = letter {
+        formatted.push(*letter);
+    }
+    if let Some(addition) = addition {
+        formatted.push('-');
+        formatted.push_str(addition);
+    }
+    formatted
+}
+
 /// Try to run a CLI command. Returns `Some(exit_code)` if the args matched a
 /// CLI command, `None` otherwise.
 #[cfg(feature = "cli")]
@@ -70,6 +266,11 @@ fn try_run_cli(args: &[String]) -> Option<i32> {
     match args.first().map(String::as_str) {
         Some("list-localities") if args.len() == 1 => Some(cmd_list_localities()),
         Some("list-municipalities") if args.len() == 1 => Some(cmd_list_municipalities()),
+        Some("validate") if args.len() == 2 => Some(cmd_validate(&args[1])),
+        Some("diff") if args.len() == 3 => Some(cmd_diff(&args[1], &args[2], false)),
+        Some("diff") if args.len() == 4 && args[3] == "--verbose" => {
+            Some(cmd_diff(&args[1], &args[2], true))
+        }
         _ if args.len() == 2 => Some(cmd_lookup(&args[0], &args[1])),
         _ => None,
     }
@@ -82,6 +283,43 @@ async fn run_server(args: &[String]) -> i32 {
         .cloned()
         .unwrap_or_else(|| "127.0.0.1:8080".to_string());
 
+    #[cfg(unix)]
+    if let Some(path) = addr.strip_prefix("unix:") {
+        println!("Starting BAG webservice on {}", addr);
+
+        if let Err(e) = bag_address_lookup::serve_unix(path).await {
+            eprintln!("Error running service: {}", e);
+            return 1;
+        }
+        return 0;
+    }
+
+    #[cfg(feature = "tls")]
+    if let (Some(cert), Some(key)) = (
+        std::env::var_os("BAG_ADDRESS_LOOKUP_TLS_CERT"),
+        std::env::var_os("BAG_ADDRESS_LOOKUP_TLS_KEY"),
+    ) {
+        println!("Starting BAG webservice on {} (TLS)", addr);
+
+        if let Err(e) = bag_address_lookup::serve_tls(&addr, cert, key).await {
+            eprintln!("Error running service: {}", e);
+            return 1;
+        }
+        return 0;
+    }
+
+    // Several comma-separated addresses, e.g. `0.0.0.0:8080,[::]:8080`.
+    if addr.contains(',') {
+        let addrs: Vec<&str> = addr.split(',').map(str::trim).collect();
+        println!("Starting BAG webservice on {}", addrs.join(", "));
+
+        if let Err(e) = bag_address_lookup::serve_many(&addrs).await {
+            eprintln!("Error running service: {}", e);
+            return 1;
+        }
+        return 0;
+    }
+
     println!("Starting BAG webservice on {}", addr);
 
     if let Err(e) = bag_address_lookup::serve(&addr).await {
@@ -100,6 +338,8 @@ fn print_usage() {
         eprintln!("  bag-service <postal_code> <house_number>");
         eprintln!("  bag-service list-localities");
         eprintln!("  bag-service list-municipalities");
+        eprintln!("  bag-service validate <database_file>");
+        eprintln!("  bag-service diff <old_database_file> <new_database_file> [--verbose]");
     }
 }
 
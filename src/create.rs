@@ -5,8 +5,13 @@ use std::{
 };
 
 use crate::{
-    Database, log_with_elapsed,
-    parsing::{ParsedData, municipalities, municipalities::Municipality, rvig_municipalities},
+    Database,
+    database::DEFAULT_COMPRESSION_LEVEL,
+    download::download_to_file,
+    log_with_elapsed,
+    parsing::{
+        ParseReport, ParsedData, municipalities, municipalities::Municipality, rvig_municipalities,
+    },
 };
 
 static DOWNLOAD_URL: &str =
@@ -14,12 +19,58 @@ static DOWNLOAD_URL: &str =
 static ZIP_PATH: &str = "data/bag.zip";
 static OUTPUT_PATH: &str = "data/bag.bin";
 
+/// Where [`create_database_with`] gets the BAG extract and writes the
+/// database.
+///
+/// The default downloads the national extract from PDOK to `data/bag.zip`
+/// and writes `data/bag.bin`, skipping the build when that already exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateOptions {
+    /// URL of the BAG extract zip, e.g. of a mirror.
+    pub download_url: String,
+    /// Where the zip is downloaded to. An existing file is used as is.
+    pub zip_path: PathBuf,
+    /// Where the database file is written.
+    pub output_path: PathBuf,
+    /// Keep a non-empty database file at `output_path` rather than
+    /// rebuilding it.
+    pub skip_if_exists: bool,
+    /// Rebuild the database file even when `skip_if_exists` would keep it,
+    /// e.g. after changing the parsing. A complete zip is still reused.
+    pub force: bool,
+    /// Log the progress of the download. Only with the `download` feature;
+    /// `curl` shows its own.
+    pub progress: bool,
+    /// Merge locality and public space names that differ only in spacing or
+    /// case; off keeps the names exactly as in the extract.
+    pub normalize_names: bool,
+}
+
+impl Default for CreateOptions {
+    fn default() -> Self {
+        CreateOptions {
+            download_url: DOWNLOAD_URL.to_string(),
+            zip_path: PathBuf::from(ZIP_PATH),
+            output_path: PathBuf::from(OUTPUT_PATH),
+            skip_if_exists: true,
+            force: false,
+            progress: true,
+            normalize_names: true,
+        }
+    }
+}
+
 /// Build the BAG database file if it does not already exist.
 pub fn create_database() -> Result<(), Box<dyn Error>> {
+    create_database_with(CreateOptions::default())
+}
+
+/// Build the BAG database file as set by `options`.
+pub fn create_database_with(options: CreateOptions) -> Result<(), Box<dyn Error>> {
     let start = Instant::now();
-    let output_path = Path::new(OUTPUT_PATH);
+    let output_path = options.output_path.as_path();
 
-    if output_path.exists() && output_path.metadata()?.len() > 0 {
+    if keeps_existing_output(&options)? {
         log_with_elapsed(start, "BAG database already exists, skipping creation.");
         return Ok(());
     }
@@ -80,61 +131,173 @@ pub fn create_database() -> Result<(), Box<dyn Error>> {
             .collect(),
     };
 
-    let zip_path = ensure_zip_available(start)?;
-    let data = ParsedData::from_bag_zip(&zip_path, start)?;
-    let database = Database::from_parsed_data(data, &reference_municipalities)?;
+    let zip_path = ensure_zip_available(&options, start)?;
+    let data = ParsedData::from_bag_zip(zip_path, start)?;
+    log_parse_report(start, &data.report);
+    let database =
+        Database::from_parsed_data(data, &reference_municipalities, options.normalize_names)?;
 
     log_with_elapsed(
         start,
         &format!(
             "Created database structure: {} localities, {} public spaces, {} address ranges, \
-             {} municipalities, {} provinces.",
+             {} house letters/additions, {} municipalities, {} provinces.",
             database.localities.len(),
             database.public_spaces.len(),
             database.ranges.len(),
+            database.house_number_suffixes.len(),
             database.municipalities.len(),
             database.provinces.len(),
         ),
     );
 
-    database.encode(output_path)?;
+    database.encode_with_level(output_path, compression_level())?;
 
-    log_with_elapsed(start, &format!("Encoded database written to {OUTPUT_PATH}"));
+    log_with_elapsed(
+        start,
+        &format!("Encoded database written to {}", output_path.display()),
+    );
 
     Ok(())
 }
 
-fn ensure_zip_available(start: Instant) -> Result<PathBuf, Box<dyn Error>> {
-    let zip_path = PathBuf::from(ZIP_PATH);
+/// Whether `options` keep the database file already at the output path.
+fn keeps_existing_output(options: &CreateOptions) -> std::io::Result<bool> {
+    let output_path = options.output_path.as_path();
+    Ok(!options.force
+        && options.skip_if_exists
+        && output_path.exists()
+        && output_path.metadata()?.len() > 0)
+}
 
-    if zip_path.exists() {
-        log_with_elapsed(start, "Using existing BAG zip file.");
-        return Ok(zip_path);
+/// Log how many records were skipped as unusable while parsing, by reason.
+fn log_parse_report(start: Instant, report: &ParseReport) {
+    if report.skipped == 0 {
+        return;
     }
+    let mut reasons: Vec<_> = report.reasons.iter().collect();
+    reasons.sort();
+    let reasons = reasons
+        .iter()
+        .map(|(reason, count)| format!("{count} {reason}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    log_with_elapsed(
+        start,
+        &format!("Skipped {} unusable records: {reasons}", report.skipped),
+    );
+}
 
-    log_with_elapsed(start, "Downloading BAG data...");
+/// Read the zstd level for the database file from
+/// `BAG_ADDRESS_LOOKUP_COMPRESSION_LEVEL`.
+fn compression_level() -> i32 {
+    std::env::var("BAG_ADDRESS_LOOKUP_COMPRESSION_LEVEL")
+        .ok()
+        .and_then(|value| value.trim().parse::<i32>().ok())
+        .filter(|level| (1..=22).contains(level))
+        .unwrap_or(DEFAULT_COMPRESSION_LEVEL)
+}
 
-    let status = std::process::Command::new("curl")
-        .arg("-L")
-        .arg("-o")
-        .arg(&zip_path)
-        .arg(DOWNLOAD_URL)
-        .status()?;
+/// Download the BAG zip unless a complete one is already at the zip path.
+///
+/// An existing file whose central directory cannot be read, e.g. left over
+/// from an interrupted download by other means, is downloaded again.
+fn ensure_zip_available(options: &CreateOptions, start: Instant) -> Result<&Path, Box<dyn Error>> {
+    let zip_path = options.zip_path.as_path();
 
-    if !status.success() {
-        return Err(format!("Failed to download file from {DOWNLOAD_URL}").into());
+    if zip_path.exists() {
+        if is_complete_zip(zip_path) {
+            log_with_elapsed(start, "Using existing BAG zip file.");
+            return Ok(zip_path);
+        }
+        log_with_elapsed(
+            start,
+            "Existing BAG zip file is incomplete or corrupt, downloading it again.",
+        );
     }
 
+    log_with_elapsed(start, "Downloading BAG data...");
+    download_to_file(&options.download_url, zip_path, options.progress, start)?;
+    if !is_complete_zip(zip_path) {
+        return Err(format!(
+            "Downloaded file {} is not a valid zip file",
+            zip_path.display()
+        )
+        .into());
+    }
     log_with_elapsed(start, "Download complete.");
 
     Ok(zip_path)
 }
 
+/// Whether the central directory at the end of the zip file at `path` can be
+/// read, which fails for a truncated file.
+fn is_complete_zip(path: &Path) -> bool {
+    std::fs::File::open(path)
+        .map_err(zip::result::ZipError::from)
+        .and_then(zip::ZipArchive::new)
+        .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use std::{path::PathBuf, time::Instant};
 
-    use crate::{Database, parsing::ParsedData};
+    use super::{CreateOptions, create_database_with, is_complete_zip, keeps_existing_output};
+    use crate::{Database, parsing::ParsedData, test_support::TempPath};
+
+    #[test]
+    fn truncated_zips_are_detected() {
+        assert!(is_complete_zip(&PathBuf::from("test/bag.zip")));
+
+        let bytes = std::fs::read("test/bag.zip").unwrap();
+        let truncated = TempPath::new("bag.zip");
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+
+        assert!(!is_complete_zip(&truncated));
+        assert!(!is_complete_zip(&PathBuf::from("does/not/exist.zip")));
+    }
+
+    #[test]
+    fn existing_database_is_kept() {
+        let output_path = TempPath::new("bag.bin");
+        std::fs::write(&output_path, b"not rebuilt").unwrap();
+
+        // Neither the URL nor the zip is touched when the output exists.
+        let result = create_database_with(CreateOptions {
+            download_url: "http://localhost:0/unreachable.zip".to_string(),
+            zip_path: PathBuf::from("does/not/exist.zip"),
+            output_path: output_path.to_path_buf(),
+            skip_if_exists: true,
+            force: false,
+            progress: false,
+            normalize_names: true,
+        });
+        let contents = std::fs::read(&output_path).unwrap();
+
+        result.unwrap();
+        assert_eq!(contents, b"not rebuilt");
+    }
+
+    #[test]
+    fn force_rebuilds_an_existing_database() {
+        let output_path = TempPath::new("bag.bin");
+        std::fs::write(&output_path, b"stale").unwrap();
+        let options = CreateOptions {
+            output_path: output_path.to_path_buf(),
+            ..CreateOptions::default()
+        };
+
+        let kept = keeps_existing_output(&options).unwrap();
+        let forced = keeps_existing_output(&CreateOptions {
+            force: true,
+            ..options
+        })
+        .unwrap();
+
+        assert!(kept);
+        assert!(!forced);
+    }
 
     #[test]
     fn test_create_database() {
@@ -150,7 +313,8 @@ mod tests {
         let data = ParsedData::from_bag_zip(&zip_path, start).unwrap();
 
         // Use empty CBS data for test (test fixture has no GWR data)
-        let database = Database::from_parsed_data(data, &[]).unwrap();
+        let normalize_names = CreateOptions::default().normalize_names;
+        let database = Database::from_parsed_data(data, &[], normalize_names).unwrap();
 
         database.encode(&output_path).unwrap();
     }
@@ -1,20 +1,27 @@
 use crate::{
-    Database, LocalityMap, MunicipalityMap, encode_addresses, index_localities,
-    index_municipalities, index_public_spaces,
+    Database, LocalityMap, MunicipalityMap, PublicSpaceMap, encode_house_number_suffixes,
+    index_localities, index_municipalities, index_public_spaces,
     parsing::{ParsedData, municipalities::Municipality},
+    transform::encode_address_ranges,
 };
 
 impl Database {
     /// Build a database from parsed BAG data and CBS municipality data.
+    ///
+    /// With `normalize_names`, locality and public space names that differ
+    /// only in spacing or case are merged, see [`index_public_spaces`].
     pub fn from_parsed_data(
         data: ParsedData,
         cbs_municipalities: &[Municipality],
+        normalize_names: bool,
     ) -> Result<Database, Box<dyn std::error::Error>> {
         let ParsedData {
             addresses,
             public_spaces,
             localities,
             municipality_relations,
+            address_points,
+            report: _,
         } = data;
 
         let LocalityMap {
@@ -22,7 +29,7 @@ impl Database {
             locality_codes,
             locality_had_suffix,
             locality_map,
-        } = index_localities(localities)?;
+        } = index_localities(localities, normalize_names)?;
 
         let MunicipalityMap {
             municipality_names,
@@ -38,8 +45,12 @@ impl Database {
             locality_names.len(),
         )?;
 
-        let (pc_names, ps_map) = index_public_spaces(public_spaces, locality_map);
-        let ranges = encode_addresses(addresses, &ps_map);
+        let PublicSpaceMap {
+            public_space_names: pc_names,
+            public_spaces_map: ps_map,
+        } = index_public_spaces(public_spaces, locality_map, normalize_names)?;
+        let house_number_suffixes = encode_house_number_suffixes(&addresses, &ps_map);
+        let (ranges, range_points) = encode_address_ranges(addresses, &ps_map, address_points);
 
         Ok(Database {
             localities: locality_names,
@@ -53,6 +64,14 @@ impl Database {
             municipality_province,
             locality_had_suffix,
             municipality_had_suffix,
+            house_number_suffixes,
+            range_points,
+            checksum: None,
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
         })
     }
 }
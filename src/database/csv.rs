@@ -0,0 +1,213 @@
+use std::io::{self, BufWriter, Write};
+
+use crate::DatabaseHandle;
+
+#[cfg(feature = "create")]
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{BufRead, BufReader, Read},
+};
+
+#[cfg(feature = "create")]
+use crate::{Address, Database, encode_addresses, try_encode_pc, util::parse_csv_line};
+
+impl DatabaseHandle<'_> {
+    /// Write every address as a CSV row of
+    /// `postal_code,house_number,public_space,locality`, after a header row.
+    ///
+    /// Rows follow the database order: by postal code, then range. House
+    /// letters and additions are not included. The output can be read back
+    /// with `Database::from_csv`.
+    pub fn export_csv(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "postal_code,house_number,public_space,locality")?;
+        for (postal_code, range) in self.ranges() {
+            let public_space = csv_field(range.public_space);
+            let locality = csv_field(range.locality);
+            for house_number in range.house_numbers() {
+                writeln!(
+                    writer,
+                    "{postal_code},{house_number},{public_space},{locality}"
+                )?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(feature = "create")]
+impl Database {
+    /// Build a database from CSV rows of
+    /// `postal_code,house_number,public_space,locality`.
+    ///
+    /// A header row starting with `postal_code` is skipped, and fields may be
+    /// double-quoted. The result carries no municipalities, locality codes,
+    /// house letters or positions, since the CSV doesn't provide them.
+    pub fn from_csv(reader: impl Read) -> Result<Database, Box<dyn Error>> {
+        let mut rows = Vec::new();
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = parse_csv_line(line.trim_end_matches('\r'));
+            if index == 0 && fields.first().is_some_and(|field| field == "postal_code") {
+                continue;
+            }
+            let [postal_code, house_number, public_space, locality] = &fields[..] else {
+                return Err(format!("line {}: expected 4 fields", index + 1).into());
+            };
+            let postal_code = postal_code.replace(' ', "").to_ascii_uppercase();
+            if try_encode_pc(postal_code.as_bytes()).is_none() {
+                return Err(format!("line {}: invalid postal code", index + 1).into());
+            }
+            let house_number: u32 = house_number
+                .parse()
+                .map_err(|_| format!("line {}: invalid house number", index + 1))?;
+
+            rows.push((
+                postal_code,
+                house_number,
+                public_space.clone(),
+                locality.clone(),
+            ));
+        }
+
+        let mut localities: Vec<String> = rows.iter().map(|row| row.3.clone()).collect();
+        localities.sort();
+        localities.dedup();
+        if localities.len() > u16::MAX as usize {
+            return Err("too many localities for u16 index".into());
+        }
+
+        let mut public_spaces: Vec<String> = rows.iter().map(|row| row.2.clone()).collect();
+        public_spaces.sort();
+        public_spaces.dedup();
+        if public_spaces.len() > u32::MAX as usize {
+            return Err("too many public space names for u32 index".into());
+        }
+
+        let locality_index: HashMap<&str, u16> = localities
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index as u16))
+            .collect();
+        let public_space_index: HashMap<&str, u32> = public_spaces
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index as u32))
+            .collect();
+
+        // Each distinct (public space, locality) pair stands in for a BAG
+        // openbare ruimte, so the BAG range compaction applies unchanged.
+        let mut public_space_ids: HashMap<(u32, u16), u64> = HashMap::new();
+        let mut public_spaces_map: HashMap<u64, (u32, u16)> = HashMap::new();
+        let mut addresses = Vec::with_capacity(rows.len());
+        for (id, (postal_code, house_number, public_space, locality)) in
+            rows.into_iter().enumerate()
+        {
+            // Both lookups succeed: the names were collected from these rows.
+            let (Some(&public_space_index), Some(&locality_index)) = (
+                public_space_index.get(public_space.as_str()),
+                locality_index.get(locality.as_str()),
+            ) else {
+                return Err(
+                    format!("address {postal_code} {house_number} could not be indexed").into(),
+                );
+            };
+            let key = (public_space_index, locality_index);
+            let next_id = public_space_ids.len() as u64;
+            let public_space_id = *public_space_ids.entry(key).or_insert(next_id);
+            public_spaces_map.insert(public_space_id, key);
+
+            addresses.push(Address {
+                id: id as u64,
+                house_number,
+                house_letter: None,
+                house_number_addition: None,
+                postal_code,
+                public_space_id,
+            });
+        }
+
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
+
+        Ok(Database::try_new(localities, public_spaces, ranges)?)
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::csv_field;
+
+    #[cfg(feature = "create")]
+    use crate::{Database, DatabaseHandle};
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn csv_round_trips_through_lookup() {
+        let csv = "postal_code,house_number,public_space,locality\n\
+                   1234AB,10,Stationsstraat,Amsterdam\n\
+                   1234AB,12,Stationsstraat,Amsterdam\n\
+                   1234 ab,14,Stationsstraat,Amsterdam\n\
+                   5678CD,1,\"Kerkstraat, Oost\",Utrecht\n";
+
+        let db = Database::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(db.ranges.len(), 2);
+
+        let db = DatabaseHandle::Decoded(db);
+        assert_eq!(
+            db.lookup("1234AB", 12),
+            Some(("Stationsstraat", "Amsterdam"))
+        );
+        assert_eq!(
+            db.lookup("1234AB", 14),
+            Some(("Stationsstraat", "Amsterdam"))
+        );
+        assert_eq!(db.lookup("1234AB", 11), None);
+        assert_eq!(
+            db.lookup("5678CD", 1),
+            Some(("Kerkstraat, Oost", "Utrecht"))
+        );
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn csv_rejects_malformed_rows() {
+        assert!(Database::from_csv("1234AB,10,Stationsstraat\n".as_bytes()).is_err());
+        assert!(Database::from_csv("1234AB,ten,Stationsstraat,Amsterdam\n".as_bytes()).is_err());
+        assert!(Database::from_csv("12345,10,Stationsstraat,Amsterdam\n".as_bytes()).is_err());
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn export_reproduces_imported_csv() {
+        let csv = "postal_code,house_number,public_space,locality\n\
+                   1234AB,10,Stationsstraat,Amsterdam\n\
+                   1234AB,12,Stationsstraat,Amsterdam\n\
+                   1234AB,13,Stationsstraat,Amsterdam\n\
+                   5678CD,1,\"Kerkstraat, Oost\",Utrecht\n";
+        let db = DatabaseHandle::Decoded(Database::from_csv(csv.as_bytes()).unwrap());
+
+        let mut exported = Vec::new();
+        db.export_csv(&mut exported).unwrap();
+        assert_eq!(String::from_utf8(exported).unwrap(), csv);
+    }
+
+    #[test]
+    fn fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Kerkstraat"), "Kerkstraat");
+        assert_eq!(csv_field("Kerkstraat, Oost"), "\"Kerkstraat, Oost\"");
+        assert_eq!(csv_field("De \"Kaai\""), "\"De \"\"Kaai\"\"\"");
+    }
+}
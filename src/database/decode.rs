@@ -8,9 +8,9 @@ use crate::database::error::DatabaseError;
 
 #[cfg(feature = "compressed_database")]
 use super::{
-    NumberRange,
+    HouseNumberSuffix, NumberRange, RdPoint,
     layout::{Header, validate_offsets_iter},
-    rw::read_u32_reader,
+    rw::{ChecksumReader, read_u32_reader},
 };
 
 #[cfg(feature = "compressed_database")]
@@ -21,6 +21,7 @@ impl Database {
     #[cfg(feature = "compressed_database")]
     pub(crate) fn from_reader<R: Read>(mut reader: R) -> Result<Self, DatabaseError> {
         let header = Header::from_reader(&mut reader)?;
+        let mut reader = ChecksumReader::new(reader);
 
         let locality_offsets = read_offsets(&mut reader, header.locality_count as usize + 1)?;
         let locality_data_len =
@@ -74,6 +75,10 @@ impl Database {
                 step,
             });
         }
+        // Lookups binary search the ranges, so they must be sorted by postal code.
+        if !ranges.is_sorted_by_key(|range| range.postal_code) {
+            return Err(DatabaseError::InvalidLayout);
+        }
 
         // Decode municipality string table
         let municipality_offsets =
@@ -179,6 +184,42 @@ impl Database {
             municipality_had_suffix.push(read_u8_reader(&mut reader)? != 0);
         }
 
+        // Decode house letters / additions
+        let expected_suffixes_offset = header.expected_house_number_suffixes_offset()?;
+        if header.house_number_suffixes_offset != expected_suffixes_offset {
+            return Err(DatabaseError::InvalidLayout);
+        }
+        let mut house_number_suffixes =
+            Vec::with_capacity(header.house_number_suffix_count as usize);
+        for _ in 0..header.house_number_suffix_count {
+            let postal_code = read_u32_reader(&mut reader)?;
+            let house_number = read_u32_reader(&mut reader)?;
+            let letter = read_u8_reader(&mut reader)?;
+            let mut addition = [0u8; 4];
+            reader.read_exact(&mut addition)?;
+
+            house_number_suffixes.push(HouseNumberSuffix {
+                postal_code,
+                house_number,
+                letter,
+                addition,
+            });
+        }
+
+        // Decode range positions
+        let expected_range_points_offset = header.expected_range_points_offset()?;
+        if header.range_points_offset != expected_range_points_offset {
+            return Err(DatabaseError::InvalidLayout);
+        }
+        let mut range_points = Vec::with_capacity(header.range_point_count as usize);
+        for _ in 0..header.range_point_count {
+            let x = read_u32_reader(&mut reader)?;
+            let y = read_u32_reader(&mut reader)?;
+            range_points.push(RdPoint { x, y });
+        }
+
+        reader.verify(header.checksum)?;
+
         Ok(Self {
             localities,
             locality_codes,
@@ -191,6 +232,14 @@ impl Database {
             municipality_province,
             locality_had_suffix,
             municipality_had_suffix,
+            house_number_suffixes,
+            range_points,
+            checksum: Some(header.checksum),
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
         })
     }
 
@@ -252,6 +301,7 @@ impl Database {
             let unique = flags.locality_unique.get(i).copied().unwrap_or(false);
             let had_suffix = self.locality_had_suffix.get(i).copied().unwrap_or(false);
             result.push(super::LocalityDetail {
+                index: i as u16,
                 name: name.as_str(),
                 code: wp_code,
                 municipality: m_name,
@@ -6,11 +6,22 @@ use std::{
 
 use crate::Database;
 
-use super::util::{DATABASE_HEADER_SIZE, DATABASE_MAGIC};
+use super::util::{DATABASE_FORMAT_VERSION, DATABASE_HEADER_SIZE, DATABASE_MAGIC};
+
+/// zstd level used by [`Database::encode`]: the highest, for the smallest file.
+pub(crate) const DEFAULT_COMPRESSION_LEVEL: i32 = 22;
 
 impl Database {
     /// Serialize the database to a binary file (optionally compressed).
     pub fn encode(&self, path: &Path) -> io::Result<()> {
+        self.encode_with_level(path, DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    /// Serialize the database like [`Database::encode`], compressing with the
+    /// given zstd `level` (1 to 22). Lower levels build faster but produce a
+    /// bigger file. The level is ignored without the `compressed_database`
+    /// feature.
+    pub fn encode_with_level(&self, path: &Path, level: i32) -> io::Result<()> {
         let locality_count = u32::try_from(self.localities.len())
             .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "locality count overflow"))?;
         let public_space_count = u32::try_from(self.public_spaces.len()).map_err(|_| {
@@ -23,7 +34,7 @@ impl Database {
 
         #[cfg(feature = "compressed_database")]
         {
-            let mut encoder = zstd::Encoder::new(file, 22)?;
+            let mut encoder = zstd::Encoder::new(file, level)?;
             self.write_database(
                 &mut encoder,
                 locality_count,
@@ -36,6 +47,7 @@ impl Database {
 
         #[cfg(not(feature = "compressed_database"))]
         {
+            let _ = level;
             let mut writer = file;
             self.write_database(&mut writer, locality_count, public_space_count, range_count)
         }
@@ -53,6 +65,20 @@ impl Database {
         })?;
         let province_count = u32::try_from(self.provinces.len())
             .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "province count overflow"))?;
+        let house_number_suffix_count =
+            u32::try_from(self.house_number_suffixes.len()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "house number suffix count overflow",
+                )
+            })?;
+        if !self.range_points.is_empty() && self.range_points.len() != self.ranges.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "range points do not match ranges",
+            ));
+        }
+        let range_point_count = self.range_points.len() as u32;
 
         // Existing section offsets
         let locality_offsets_offset = DATABASE_HEADER_SIZE;
@@ -98,51 +124,60 @@ impl Database {
         let locality_had_suffix_len = locality_count as usize;
 
         let municipality_had_suffix_offset = locality_had_suffix_offset + locality_had_suffix_len;
+        let municipality_had_suffix_len = municipality_count as usize;
+
+        let house_number_suffixes_offset =
+            municipality_had_suffix_offset + municipality_had_suffix_len;
+        let house_number_suffixes_len = house_number_suffix_count as usize * 13;
+
+        let range_points_offset = house_number_suffixes_offset + house_number_suffixes_len;
+
+        // The header carries a checksum of everything after it, so hash the
+        // payload in a first pass that writes nothing.
+        let mut checksum = ChecksumWriter::default();
+        self.write_payload(&mut checksum)?;
+        let checksum = checksum.hasher.finalize();
 
         // Write header
         writer.write_all(&DATABASE_MAGIC)?;
+        writer.write_all(&DATABASE_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
         writer.write_all(&locality_count.to_le_bytes())?;
         writer.write_all(&public_space_count.to_le_bytes())?;
         writer.write_all(&range_count.to_le_bytes())?;
-        writer.write_all(&(locality_offsets_offset as u32).to_le_bytes())?;
-        writer.write_all(&(locality_data_offset as u32).to_le_bytes())?;
-        writer.write_all(&(public_space_offsets_offset as u32).to_le_bytes())?;
-        writer.write_all(&(public_space_data_offset as u32).to_le_bytes())?;
-        writer.write_all(&(ranges_offset as u32).to_le_bytes())?;
+        writer.write_all(&(locality_offsets_offset as u64).to_le_bytes())?;
+        writer.write_all(&(locality_data_offset as u64).to_le_bytes())?;
+        writer.write_all(&(public_space_offsets_offset as u64).to_le_bytes())?;
+        writer.write_all(&(public_space_data_offset as u64).to_le_bytes())?;
+        writer.write_all(&(ranges_offset as u64).to_le_bytes())?;
         writer.write_all(&municipality_count.to_le_bytes())?;
         writer.write_all(&province_count.to_le_bytes())?;
-        writer.write_all(&(municipality_offsets_offset as u32).to_le_bytes())?;
-        writer.write_all(&(municipality_data_offset as u32).to_le_bytes())?;
-        writer.write_all(&(province_offsets_offset as u32).to_le_bytes())?;
-        writer.write_all(&(province_data_offset as u32).to_le_bytes())?;
-        writer.write_all(&(locality_municipality_map_offset as u32).to_le_bytes())?;
-        writer.write_all(&(municipality_province_map_offset as u32).to_le_bytes())?;
-        writer.write_all(&(municipality_codes_offset as u32).to_le_bytes())?;
-        writer.write_all(&(locality_codes_offset as u32).to_le_bytes())?;
-        writer.write_all(&(locality_had_suffix_offset as u32).to_le_bytes())?;
-        writer.write_all(&(municipality_had_suffix_offset as u32).to_le_bytes())?;
+        writer.write_all(&(municipality_offsets_offset as u64).to_le_bytes())?;
+        writer.write_all(&(municipality_data_offset as u64).to_le_bytes())?;
+        writer.write_all(&(province_offsets_offset as u64).to_le_bytes())?;
+        writer.write_all(&(province_data_offset as u64).to_le_bytes())?;
+        writer.write_all(&(locality_municipality_map_offset as u64).to_le_bytes())?;
+        writer.write_all(&(municipality_province_map_offset as u64).to_le_bytes())?;
+        writer.write_all(&(municipality_codes_offset as u64).to_le_bytes())?;
+        writer.write_all(&(locality_codes_offset as u64).to_le_bytes())?;
+        writer.write_all(&(locality_had_suffix_offset as u64).to_le_bytes())?;
+        writer.write_all(&(municipality_had_suffix_offset as u64).to_le_bytes())?;
+        writer.write_all(&house_number_suffix_count.to_le_bytes())?;
+        writer.write_all(&(house_number_suffixes_offset as u64).to_le_bytes())?;
+        writer.write_all(&range_point_count.to_le_bytes())?;
+        writer.write_all(&(range_points_offset as u64).to_le_bytes())?;
+        writer.write_all(&checksum.to_le_bytes())?;
+
+        self.write_payload(writer)
+    }
 
+    /// Write all sections following the header.
+    fn write_payload<W: Write>(&self, writer: &mut W) -> io::Result<()> {
         // Write locality string table
-        let mut offset = 0u32;
-        writer.write_all(&offset.to_le_bytes())?;
-        for name in &self.localities {
-            offset = offset.saturating_add(name.len() as u32);
-            writer.write_all(&offset.to_le_bytes())?;
-        }
-        for name in &self.localities {
-            writer.write_all(name.as_bytes())?;
-        }
+        write_name_table(writer, &self.localities)?;
 
         // Write public space string table
-        offset = 0;
-        writer.write_all(&offset.to_le_bytes())?;
-        for name in &self.public_spaces {
-            offset = offset.saturating_add(name.len() as u32);
-            writer.write_all(&offset.to_le_bytes())?;
-        }
-        for name in &self.public_spaces {
-            writer.write_all(name.as_bytes())?;
-        }
+        write_name_table(writer, &self.public_spaces)?;
 
         // Write ranges
         for range in &self.ranges {
@@ -155,26 +190,10 @@ impl Database {
         }
 
         // Write municipality string table
-        offset = 0;
-        writer.write_all(&offset.to_le_bytes())?;
-        for name in &self.municipalities {
-            offset = offset.saturating_add(name.len() as u32);
-            writer.write_all(&offset.to_le_bytes())?;
-        }
-        for name in &self.municipalities {
-            writer.write_all(name.as_bytes())?;
-        }
+        write_name_table(writer, &self.municipalities)?;
 
         // Write province string table
-        offset = 0;
-        writer.write_all(&offset.to_le_bytes())?;
-        for name in &self.provinces {
-            offset = offset.saturating_add(name.len() as u32);
-            writer.write_all(&offset.to_le_bytes())?;
-        }
-        for name in &self.provinces {
-            writer.write_all(name.as_bytes())?;
-        }
+        write_name_table(writer, &self.provinces)?;
 
         // Write locality -> municipality index map
         for &m_idx in &self.locality_municipality {
@@ -204,6 +223,79 @@ impl Database {
             writer.write_all(&[flag as u8])?;
         }
 
+        // Write house letters / additions (4+4+1+4 bytes each)
+        for suffix in &self.house_number_suffixes {
+            writer.write_all(&suffix.postal_code.to_le_bytes())?;
+            writer.write_all(&suffix.house_number.to_le_bytes())?;
+            writer.write_all(&[suffix.letter])?;
+            writer.write_all(&suffix.addition)?;
+        }
+
+        // Write range positions (4+4 bytes each), parallel to the ranges
+        for point in &self.range_points {
+            writer.write_all(&point.x.to_le_bytes())?;
+            writer.write_all(&point.y.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a string table: `names.len() + 1` cumulative offsets, then the
+/// concatenated names.
+fn write_name_table<W: Write>(writer: &mut W, names: &[String]) -> io::Result<()> {
+    let mut offset = 0u32;
+    writer.write_all(&offset.to_le_bytes())?;
+    for name in names {
+        offset = next_name_offset(offset, name.len())?;
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    for name in names {
+        writer.write_all(name.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// The offset following a name of `len` bytes at `offset`, or an error when
+/// the name data no longer fits the `u32` offsets.
+fn next_name_offset(offset: u32, len: usize) -> io::Result<u32> {
+    u32::try_from(len)
+        .ok()
+        .and_then(|len| offset.checked_add(len))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "name data exceeds the 4 GiB offset range",
+            )
+        })
+}
+
+/// Writer that only computes the CRC32 of the bytes written to it.
+#[derive(Default)]
+struct ChecksumWriter {
+    hasher: crc32fast::Hasher,
+}
+
+impl Write for ChecksumWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::next_name_offset;
+
+    #[test]
+    fn name_offsets_fail_on_overflow() {
+        assert_eq!(next_name_offset(10, 5).unwrap(), 15);
+        assert_eq!(next_name_offset(u32::MAX - 5, 5).unwrap(), u32::MAX);
+        assert!(next_name_offset(u32::MAX - 5, 6).is_err());
+        assert!(next_name_offset(0, u32::MAX as usize + 1).is_err());
+    }
+}
@@ -1,23 +1,98 @@
 #[derive(Debug)]
 pub enum DatabaseError {
     NotFound,
+    /// This build has no embedded database, see [`DatabaseHandle::load`].
+    ///
+    /// [`DatabaseHandle::load`]: crate::DatabaseHandle::load
+    NotEmbedded,
     TooShort,
     InvalidMagic,
     InvalidLayout,
     DecompressionFailed,
+    ChecksumMismatch,
+    /// The range at `index` has a lower postal code than the one before it,
+    /// see [`Database::try_new`].
+    ///
+    /// [`Database::try_new`]: crate::Database::try_new
+    UnsortedRanges {
+        index: usize,
+    },
+    /// The range at `index` refers to a locality or public space that isn't
+    /// there, see [`Database::try_new`].
+    ///
+    /// [`Database::try_new`]: crate::Database::try_new
+    RangeOutOfBounds {
+        index: usize,
+    },
+    /// Name `index` of the `table` names sorts before the one before it, or
+    /// for public spaces repeats it, see [`Database::try_new`].
+    ///
+    /// [`Database::try_new`]: crate::Database::try_new
+    UnsortedNames {
+        table: &'static str,
+        index: usize,
+    },
+    /// The file was written with a layout version this build cannot read.
+    UnsupportedVersion {
+        found: u16,
+        expected: u16,
+    },
+    /// Reading the file or the decompressed stream failed, e.g. because it
+    /// ended early.
+    Io(std::io::Error),
 }
 
 impl std::fmt::Display for DatabaseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
             DatabaseError::NotFound => "database file not found",
+            DatabaseError::NotEmbedded => {
+                "no database is embedded in this build; load a database file with \
+                 DatabaseHandle::load_from_path"
+            }
             DatabaseError::TooShort => "database file too short",
             DatabaseError::InvalidMagic => "database file has invalid magic",
             DatabaseError::InvalidLayout => "database file layout invalid",
             DatabaseError::DecompressionFailed => "database file decompression failed",
+            DatabaseError::ChecksumMismatch => {
+                "database file checksum mismatch; the file may be truncated or corrupt"
+            }
+            DatabaseError::UnsortedRanges { index } => {
+                return write!(f, "range {index} is not sorted by postal code");
+            }
+            DatabaseError::RangeOutOfBounds { index } => {
+                return write!(
+                    f,
+                    "range {index} refers to a missing locality or public space"
+                );
+            }
+            DatabaseError::UnsortedNames { table, index } => {
+                return write!(f, "{table} name {index} is out of order");
+            }
+            DatabaseError::UnsupportedVersion { found, expected } => {
+                return write!(
+                    f,
+                    "database file has format version {found}, expected {expected}; \
+                     rebuild the database file"
+                );
+            }
+            DatabaseError::Io(err) => return write!(f, "database file could not be read: {err}"),
         };
         f.write_str(message)
     }
 }
 
-impl std::error::Error for DatabaseError {}
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DatabaseError {
+    fn from(err: std::io::Error) -> Self {
+        DatabaseError::Io(err)
+    }
+}
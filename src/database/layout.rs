@@ -3,11 +3,16 @@ use std::io::{Cursor, Read};
 use crate::database::error::DatabaseError;
 
 use super::{
-    rw::{read_u32_bytes, read_u32_reader},
-    util::{DATABASE_HEADER_SIZE, DATABASE_MAGIC},
+    rw::{read_u16_bytes, read_u16_reader, read_u32_bytes, read_u32_reader, read_u64_reader},
+    util::{
+        DATABASE_FORMAT_VERSION, DATABASE_FORMAT_VERSION_U32_OFFSETS, DATABASE_HEADER_SIZE,
+        DATABASE_HEADER_SIZE_U32_OFFSETS, DATABASE_MAGIC,
+    },
 };
 
 pub(crate) struct Header {
+    /// Length of the header itself, which depends on the format version.
+    pub(crate) size: usize,
     pub(crate) locality_count: u32,
     pub(crate) public_space_count: u32,
     pub(crate) range_count: u32,
@@ -28,11 +33,21 @@ pub(crate) struct Header {
     pub(crate) locality_codes_offset: usize,
     pub(crate) locality_had_suffix_offset: usize,
     pub(crate) municipality_had_suffix_offset: usize,
+    pub(crate) house_number_suffix_count: u32,
+    pub(crate) house_number_suffixes_offset: usize,
+    /// Either 0 (no geometry) or `range_count`.
+    pub(crate) range_point_count: u32,
+    pub(crate) range_points_offset: usize,
+    /// CRC32 of everything following the header.
+    pub(crate) checksum: u32,
 }
 
 impl Header {
     pub(crate) fn validate_base(&self) -> Result<(), DatabaseError> {
-        if self.locality_offsets_offset != DATABASE_HEADER_SIZE {
+        if self.locality_offsets_offset != self.size {
+            return Err(DatabaseError::InvalidLayout);
+        }
+        if self.range_point_count != 0 && self.range_point_count != self.range_count {
             return Err(DatabaseError::InvalidLayout);
         }
         Ok(())
@@ -168,39 +183,78 @@ impl Header {
             .ok_or(DatabaseError::InvalidLayout)
     }
 
+    pub(crate) fn expected_house_number_suffixes_offset(&self) -> Result<usize, DatabaseError> {
+        self.municipality_had_suffix_offset
+            .checked_add(self.municipality_count as usize)
+            .ok_or(DatabaseError::InvalidLayout)
+    }
+
+    pub(crate) fn expected_range_points_offset(&self) -> Result<usize, DatabaseError> {
+        self.house_number_suffixes_offset
+            .checked_add(
+                (self.house_number_suffix_count as usize)
+                    .checked_mul(13)
+                    .ok_or(DatabaseError::InvalidLayout)?,
+            )
+            .ok_or(DatabaseError::InvalidLayout)
+    }
+
     pub(crate) fn from_reader<R: Read>(reader: &mut R) -> Result<Self, DatabaseError> {
         let mut magic = [0u8; 4];
-        reader
-            .read_exact(&mut magic)
-            .map_err(|_| DatabaseError::DecompressionFailed)?;
+        reader.read_exact(&mut magic)?;
         if magic != DATABASE_MAGIC {
             return Err(DatabaseError::InvalidMagic);
         }
 
+        // Check the version before trusting any other field: their meaning
+        // depends on it.
+        let format_version = read_u16_reader(reader)?;
+        let size = header_size(format_version).ok_or(DatabaseError::UnsupportedVersion {
+            found: format_version,
+            expected: DATABASE_FORMAT_VERSION,
+        })?;
+        // Reserved, keeps the following fields 4-byte aligned.
+        read_u16_reader(reader)?;
+
+        // Section offsets are u64 since version 3.
+        let read_offset = |reader: &mut R| -> Result<usize, DatabaseError> {
+            if format_version == DATABASE_FORMAT_VERSION_U32_OFFSETS {
+                Ok(read_u32_reader(reader)? as usize)
+            } else {
+                usize::try_from(read_u64_reader(reader)?).map_err(|_| DatabaseError::InvalidLayout)
+            }
+        };
+
         let locality_count = read_u32_reader(reader)?;
         let public_space_count = read_u32_reader(reader)?;
         let range_count = read_u32_reader(reader)?;
 
-        let locality_offsets_offset = read_u32_reader(reader)? as usize;
-        let locality_data_offset = read_u32_reader(reader)? as usize;
-        let public_space_offsets_offset = read_u32_reader(reader)? as usize;
-        let public_space_data_offset = read_u32_reader(reader)? as usize;
-        let ranges_offset = read_u32_reader(reader)? as usize;
+        let locality_offsets_offset = read_offset(reader)?;
+        let locality_data_offset = read_offset(reader)?;
+        let public_space_offsets_offset = read_offset(reader)?;
+        let public_space_data_offset = read_offset(reader)?;
+        let ranges_offset = read_offset(reader)?;
 
         let municipality_count = read_u32_reader(reader)?;
         let province_count = read_u32_reader(reader)?;
-        let municipality_offsets_offset = read_u32_reader(reader)? as usize;
-        let municipality_data_offset = read_u32_reader(reader)? as usize;
-        let province_offsets_offset = read_u32_reader(reader)? as usize;
-        let province_data_offset = read_u32_reader(reader)? as usize;
-        let locality_municipality_map_offset = read_u32_reader(reader)? as usize;
-        let municipality_province_map_offset = read_u32_reader(reader)? as usize;
-        let municipality_codes_offset = read_u32_reader(reader)? as usize;
-        let locality_codes_offset = read_u32_reader(reader)? as usize;
-        let locality_had_suffix_offset = read_u32_reader(reader)? as usize;
-        let municipality_had_suffix_offset = read_u32_reader(reader)? as usize;
+        let municipality_offsets_offset = read_offset(reader)?;
+        let municipality_data_offset = read_offset(reader)?;
+        let province_offsets_offset = read_offset(reader)?;
+        let province_data_offset = read_offset(reader)?;
+        let locality_municipality_map_offset = read_offset(reader)?;
+        let municipality_province_map_offset = read_offset(reader)?;
+        let municipality_codes_offset = read_offset(reader)?;
+        let locality_codes_offset = read_offset(reader)?;
+        let locality_had_suffix_offset = read_offset(reader)?;
+        let municipality_had_suffix_offset = read_offset(reader)?;
+        let house_number_suffix_count = read_u32_reader(reader)?;
+        let house_number_suffixes_offset = read_offset(reader)?;
+        let range_point_count = read_u32_reader(reader)?;
+        let range_points_offset = read_offset(reader)?;
+        let checksum = read_u32_reader(reader)?;
 
         let header = Self {
+            size,
             locality_count,
             public_space_count,
             range_count,
@@ -221,6 +275,11 @@ impl Header {
             locality_codes_offset,
             locality_had_suffix_offset,
             municipality_had_suffix_offset,
+            house_number_suffix_count,
+            house_number_suffixes_offset,
+            range_point_count,
+            range_points_offset,
+            checksum,
         };
 
         header.validate_base()?;
@@ -228,7 +287,8 @@ impl Header {
     }
 
     pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Header, DatabaseError> {
-        if bytes.len() < DATABASE_HEADER_SIZE {
+        let size = read_u16_bytes(bytes, 4).and_then(header_size);
+        if bytes.len() < size.unwrap_or(DATABASE_HEADER_SIZE_U32_OFFSETS) {
             return Err(DatabaseError::TooShort);
         }
         let mut cursor = Cursor::new(bytes);
@@ -236,6 +296,16 @@ impl Header {
     }
 }
 
+/// Header length of the given format version, or `None` when this build
+/// cannot read that version.
+fn header_size(format_version: u16) -> Option<usize> {
+    match format_version {
+        DATABASE_FORMAT_VERSION => Some(DATABASE_HEADER_SIZE),
+        DATABASE_FORMAT_VERSION_U32_OFFSETS => Some(DATABASE_HEADER_SIZE_U32_OFFSETS),
+        _ => None,
+    }
+}
+
 pub(crate) fn validate_offsets_iter<I>(iter: I) -> Result<u32, DatabaseError>
 where
     I: IntoIterator<Item = Result<u32, DatabaseError>>,
@@ -1,24 +1,23 @@
 use crate::database::{DatabaseView, util::partition_point_range};
 
 use super::{
-    Database,
-    util::{encode_pc, normalize_postalcode},
+    AddressResult, Database, HouseNumberSuffix, RangeInfo, RdPoint,
+    util::{encode_house_number_suffix, normalize_postalcode, try_encode_pc},
 };
 
-impl DatabaseView {
+impl DatabaseView<'_> {
     pub fn lookup(&self, postalcode: &str, house_number: u32) -> Option<(&str, &str)> {
-        let normalized_postalcode = normalize_postalcode(postalcode)?;
-        let pc_encoded = encode_pc(&normalized_postalcode);
+        self.lookup_detailed(postalcode, house_number)
+            .map(|address| (address.public_space, address.locality))
+    }
 
-        let range_count = self.range_count as usize;
-        let start = partition_point_range(range_count, |idx| {
-            self.range_postal_code(idx)
-                .is_none_or(|code| code < pc_encoded)
-        });
-        let end = partition_point_range(range_count, |idx| {
-            self.range_postal_code(idx)
-                .is_none_or(|code| code <= pc_encoded)
-        });
+    pub(crate) fn lookup_detailed(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+    ) -> Option<AddressResult<'_>> {
+        let pc_encoded = try_encode_pc(&normalize_postalcode(postalcode)?)?;
+        let (start, end) = self.postal_code_bounds(pc_encoded);
 
         for index in start..end {
             let range = self.range_at(index)?;
@@ -30,24 +29,113 @@ impl DatabaseView {
                 && house_number <= range_end
                 && (house_number - range.start).is_multiple_of(step)
             {
-                let public_space = self.public_space_name(range.public_space_index)?;
-                let locality = self.locality_name(range.locality_index)?;
-                return Some((public_space, locality));
+                return self.range_address(index);
             }
         }
 
         None
     }
-}
 
-impl Database {
-    pub(crate) fn lookup(&self, postalcode: &str, house_number: u32) -> Option<(&str, &str)> {
-        let postalcode = normalize_postalcode(postalcode)?;
-        let pc_encoded = encode_pc(&postalcode);
+    /// The names and position of the range at `index`.
+    pub(crate) fn range_address(&self, index: usize) -> Option<AddressResult<'_>> {
+        let range = self.range_at(index)?;
+        Some(AddressResult {
+            public_space: self.public_space_name(range.public_space_index)?,
+            locality: self.locality_name(range.locality_index)?,
+            municipality: self
+                .locality_municipality_index(range.locality_index)
+                .and_then(|index| self.municipality_name(index)),
+            point: self
+                .range_point(index)
+                .filter(|&point| point != RdPoint::MISSING),
+        })
+    }
 
-        let start = self.ranges.partition_point(|r| r.postal_code < pc_encoded);
+    pub fn ranges_for_postal_code(&self, postalcode: &str) -> Vec<RangeInfo<'_>> {
+        let Some(pc_encoded) = normalize_postalcode(postalcode).and_then(|pc| try_encode_pc(&pc))
+        else {
+            return Vec::new();
+        };
+        let (start, end) = self.postal_code_bounds(pc_encoded);
 
-        let end = self.ranges.partition_point(|r| r.postal_code <= pc_encoded);
+        (start..end)
+            .filter_map(|index| self.range_info(index).map(|(_, range)| range))
+            .collect()
+    }
+
+    /// Whether any range is registered under the postal code.
+    pub(crate) fn has_postal_code(&self, postalcode: &str) -> bool {
+        let Some(pc_encoded) = normalize_postalcode(postalcode).and_then(|pc| try_encode_pc(&pc))
+        else {
+            return false;
+        };
+        let (start, end) = self.postal_code_bounds(pc_encoded);
+        start < end
+    }
+
+    /// The encoded postal code and the house numbers and names of the range
+    /// at `index`.
+    pub(crate) fn range_info(&self, index: usize) -> Option<(u32, RangeInfo<'_>)> {
+        let range = self.range_at(index)?;
+        Some((
+            self.range_postal_code(index)?,
+            RangeInfo {
+                start: range.start,
+                length: range.length,
+                step: range.step,
+                public_space: self.public_space_name(range.public_space_index)?,
+                locality: self.locality_name(range.locality_index)?,
+            },
+        ))
+    }
+
+    /// Index bounds of the ranges with the given encoded postal code.
+    fn postal_code_bounds(&self, pc_encoded: u32) -> (usize, usize) {
+        let range_count = self.range_count as usize;
+        let start = partition_point_range(range_count, |idx| {
+            self.range_postal_code(idx)
+                .is_none_or(|code| code < pc_encoded)
+        });
+        let end = partition_point_range(range_count, |idx| {
+            self.range_postal_code(idx)
+                .is_none_or(|code| code <= pc_encoded)
+        });
+        (start, end)
+    }
+
+    pub fn house_number_suffix(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+        letter: Option<&str>,
+        addition: Option<&str>,
+    ) -> Option<HouseNumberSuffix> {
+        let pc_encoded = try_encode_pc(&normalize_postalcode(postalcode)?)?;
+        let (letter, addition) = encode_house_number_suffix(letter, addition)?;
+        let key = (pc_encoded, house_number);
+
+        let count = self.house_number_suffix_count();
+        let record_key = |idx| {
+            self.house_number_suffix_at(idx)
+                .map(|suffix| (suffix.postal_code, suffix.house_number))
+        };
+        let start = partition_point_range(count, |idx| record_key(idx).is_none_or(|k| k < key));
+        let end = partition_point_range(count, |idx| record_key(idx).is_none_or(|k| k <= key));
+
+        (start..end)
+            .filter_map(|idx| self.house_number_suffix_at(idx))
+            .find(|suffix| suffix.matches(letter, &addition))
+    }
+}
+
+impl Database {
+    pub(crate) fn lookup_detailed(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+    ) -> Option<AddressResult<'_>> {
+        let pc_encoded = try_encode_pc(&normalize_postalcode(postalcode)?)?;
+        let (start, end) = self.postal_code_bounds(pc_encoded);
 
         for index in start..end {
             let range = self.ranges.get(index)?;
@@ -63,12 +151,94 @@ impl Database {
                 && house_number <= range_end
                 && (house_number - range.start).is_multiple_of(step)
             {
-                let public_space_name = self.public_space_name(range.public_space_index)?;
-                let locality_name = self.locality_name(range.locality_index)?;
-                return Some((public_space_name, locality_name));
+                return self.range_address(index);
             }
         }
 
         None
     }
+
+    /// The names and position of the range at `index`.
+    pub(crate) fn range_address(&self, index: usize) -> Option<AddressResult<'_>> {
+        let range = self.ranges.get(index)?;
+        Some(AddressResult {
+            public_space: self.public_space_name(range.public_space_index)?,
+            locality: self.locality_name(range.locality_index)?,
+            municipality: self
+                .locality_municipality
+                .get(range.locality_index as usize)
+                .and_then(|&index| self.municipality_name(index)),
+            point: self
+                .range_points
+                .get(index)
+                .copied()
+                .filter(|&point| point != RdPoint::MISSING),
+        })
+    }
+
+    pub(crate) fn ranges_for_postal_code(&self, postalcode: &str) -> Vec<RangeInfo<'_>> {
+        let Some(pc_encoded) = normalize_postalcode(postalcode).and_then(|pc| try_encode_pc(&pc))
+        else {
+            return Vec::new();
+        };
+        let (start, end) = self.postal_code_bounds(pc_encoded);
+
+        (start..end)
+            .filter_map(|index| self.range_info(index).map(|(_, range)| range))
+            .collect()
+    }
+
+    /// Whether any range is registered under the postal code.
+    pub(crate) fn has_postal_code(&self, postalcode: &str) -> bool {
+        let Some(pc_encoded) = normalize_postalcode(postalcode).and_then(|pc| try_encode_pc(&pc))
+        else {
+            return false;
+        };
+        let (start, end) = self.postal_code_bounds(pc_encoded);
+        start < end
+    }
+
+    /// The encoded postal code and the house numbers and names of the range
+    /// at `index`.
+    pub(crate) fn range_info(&self, index: usize) -> Option<(u32, RangeInfo<'_>)> {
+        let range = self.ranges.get(index)?;
+        Some((
+            range.postal_code,
+            RangeInfo {
+                start: range.start,
+                length: range.length,
+                step: range.step,
+                public_space: self.public_space_name(range.public_space_index)?,
+                locality: self.locality_name(range.locality_index)?,
+            },
+        ))
+    }
+
+    /// Index bounds of the ranges with the given encoded postal code.
+    fn postal_code_bounds(&self, pc_encoded: u32) -> (usize, usize) {
+        let start = self.ranges.partition_point(|r| r.postal_code < pc_encoded);
+        let end = self.ranges.partition_point(|r| r.postal_code <= pc_encoded);
+        (start, end)
+    }
+
+    pub(crate) fn house_number_suffix(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+        letter: Option<&str>,
+        addition: Option<&str>,
+    ) -> Option<HouseNumberSuffix> {
+        let pc_encoded = try_encode_pc(&normalize_postalcode(postalcode)?)?;
+        let (letter, addition) = encode_house_number_suffix(letter, addition)?;
+        let key = (pc_encoded, house_number);
+
+        let suffixes = &self.house_number_suffixes;
+        let start = suffixes.partition_point(|s| (s.postal_code, s.house_number) < key);
+        let end = suffixes.partition_point(|s| (s.postal_code, s.house_number) <= key);
+
+        suffixes[start..end]
+            .iter()
+            .find(|suffix| suffix.matches(letter, &addition))
+            .copied()
+    }
 }
@@ -4,17 +4,39 @@ mod create;
 #[cfg(feature = "create")]
 mod encode;
 
+mod csv;
 mod decode;
 mod error;
 mod layout;
 mod lookup;
+mod ndjson;
 mod rw;
 mod util;
 mod view;
 
+use view::ViewBytes;
+
 pub use error::DatabaseError;
-pub use util::encode_pc;
+pub use util::{decode_pc, encode_pc, try_encode_pc};
+
+use util::partition_point_range;
+
+pub(crate) use util::{encode_house_number_suffix, postal_code_prefix_bounds};
+
+#[cfg(feature = "create")]
+pub(crate) use encode::DEFAULT_COMPRESSION_LEVEL;
+
+use std::{path::Path, sync::OnceLock};
+
+use crate::{
+    geo::wgs84_to_rd,
+    postal_codes::LocalityPostalCodes,
+    reverse::ReverseIndex,
+    streets::StreetIndex,
+    suggest::{PlaceFilter, ScoringConfig, SuggestIndex, Suggestion, place_candidates},
+};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NumberRange {
     pub postal_code: u32,
     pub start: u32,
@@ -24,6 +46,79 @@ pub struct NumberRange {
     pub step: u8,
 }
 
+/// A house letter and/or house number addition issued for one address, e.g.
+/// the `A` in `12A` or the `2` in `12-2`.
+///
+/// Addresses without either are not recorded; they are fully described by
+/// the [`NumberRange`] covering their house number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HouseNumberSuffix {
+    pub postal_code: u32,
+    pub house_number: u32,
+    /// Huisletter as an ASCII letter, or 0 when the address has none.
+    pub letter: u8,
+    /// Huisnummertoevoeging as ASCII alphanumerics, zero-padded.
+    pub addition: [u8; 4],
+}
+
+impl HouseNumberSuffix {
+    /// The house letter, if any.
+    pub fn letter(&self) -> Option<char> {
+        (self.letter != 0).then_some(self.letter as char)
+    }
+
+    /// The house number addition, if any.
+    pub fn addition(&self) -> Option<&str> {
+        let len = self
+            .addition
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.addition.len());
+        if len == 0 {
+            return None;
+        }
+        std::str::from_utf8(&self.addition[..len]).ok()
+    }
+
+    /// Whether this record carries the given (encoded) letter and addition,
+    /// ignoring ASCII case.
+    pub(crate) fn matches(&self, letter: u8, addition: &[u8; 4]) -> bool {
+        self.letter.eq_ignore_ascii_case(&letter) && self.addition.eq_ignore_ascii_case(addition)
+    }
+}
+
+/// Rijksdriehoek (EPSG:28992) position, in decimetres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RdPoint {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl RdPoint {
+    /// Marks a range without a known position.
+    pub const MISSING: RdPoint = RdPoint {
+        x: u32::MAX,
+        y: u32::MAX,
+    };
+
+    /// Convert RD coordinates in metres, rounding to decimetres.
+    pub fn from_metres(x: f64, y: f64) -> RdPoint {
+        RdPoint {
+            x: (x * 10.0).round() as u32,
+            y: (y * 10.0).round() as u32,
+        }
+    }
+
+    /// WGS84 `(latitude, longitude)` in degrees.
+    pub fn to_wgs84(self) -> (f64, f64) {
+        crate::geo::rd_to_wgs84(f64::from(self.x) / 10.0, f64::from(self.y) / 10.0)
+    }
+}
+
+/// A BAG database decoded into tables.
+///
+/// Cloning copies the tables and any indexes built so far.
+#[derive(Clone)]
 pub struct Database {
     pub localities: Vec<String>,
     /// BAG woonplaatsidentificatiecode per locality_index.
@@ -42,11 +137,227 @@ pub struct Database {
     pub locality_had_suffix: Vec<bool>,
     /// Parallel to `municipalities`: same semantic as above for CBS entries.
     pub municipality_had_suffix: Vec<bool>,
+    /// House letters and additions, sorted by (postal code, house number,
+    /// letter, addition).
+    pub house_number_suffixes: Vec<HouseNumberSuffix>,
+    /// Parallel to `ranges`: the average position of the addresses in each
+    /// range, or [`RdPoint::MISSING`]. Empty when the database was built
+    /// without geometry.
+    pub range_points: Vec<RdPoint>,
+    /// Checksum of the file the database was decoded from, see
+    /// [`DatabaseHandle::checksum`].
+    pub(crate) checksum: Option<u32>,
+    /// Sorted name index for suggestions, see [`DatabaseHandle::build_suggest_index`].
+    pub(crate) suggest_index: OnceLock<SuggestIndex>,
+    /// Grid over `range_points`, see [`DatabaseHandle::build_reverse_index`].
+    pub(crate) reverse_index: OnceLock<ReverseIndex>,
+    /// Postal codes per locality, see
+    /// [`DatabaseHandle::build_locality_postal_code_index`].
+    pub(crate) locality_postal_codes: OnceLock<LocalityPostalCodes>,
+    /// Ranges per street, see [`DatabaseHandle::build_street_index`].
+    pub(crate) street_index: OnceLock<StreetIndex>,
+    /// Cached [`DatabaseStats::total_addresses`].
+    pub(crate) address_count: OnceLock<u64>,
+}
+
+impl Database {
+    /// Build a database from its name tables and ranges, leaving the other
+    /// tables empty, as `Database::from_csv` does.
+    ///
+    /// Fails with [`DatabaseError::UnsortedRanges`] when the ranges are not
+    /// sorted by postal code, which lookups rely on, and with
+    /// [`DatabaseError::RangeOutOfBounds`] when a range refers to a locality
+    /// or public space past the end of its table. Name searches rely on the
+    /// name tables being sorted too, and fail with
+    /// [`DatabaseError::UnsortedNames`] otherwise. Localities may share a
+    /// name; public space names must be distinct.
+    pub fn try_new(
+        localities: Vec<String>,
+        public_spaces: Vec<String>,
+        ranges: Vec<NumberRange>,
+    ) -> Result<Database, DatabaseError> {
+        if let Some(index) = localities.windows(2).position(|pair| pair[0] > pair[1]) {
+            return Err(DatabaseError::UnsortedNames {
+                table: "locality",
+                index: index + 1,
+            });
+        }
+        if let Some(index) = public_spaces.windows(2).position(|pair| pair[0] >= pair[1]) {
+            return Err(DatabaseError::UnsortedNames {
+                table: "public space",
+                index: index + 1,
+            });
+        }
+        if let Some(index) = ranges
+            .windows(2)
+            .position(|pair| pair[0].postal_code > pair[1].postal_code)
+        {
+            return Err(DatabaseError::UnsortedRanges { index: index + 1 });
+        }
+        if let Some(index) = ranges.iter().position(|range| {
+            range.public_space_index as usize >= public_spaces.len()
+                || range.locality_index as usize >= localities.len()
+        }) {
+            return Err(DatabaseError::RangeOutOfBounds { index });
+        }
+
+        Ok(Database {
+            locality_codes: vec![0; localities.len()],
+            locality_municipality: vec![u16::MAX; localities.len()],
+            locality_had_suffix: vec![false; localities.len()],
+            localities,
+            public_spaces,
+            ranges,
+            municipalities: Vec::new(),
+            provinces: Vec::new(),
+            municipality_codes: Vec::new(),
+            municipality_province: Vec::new(),
+            municipality_had_suffix: Vec::new(),
+            house_number_suffixes: Vec::new(),
+            range_points: Vec::new(),
+            checksum: None,
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
+        })
+    }
+}
+
+/// Databases are equal when their tables are; the checksum of the file they
+/// were read from and the indexes built so far don't count.
+impl PartialEq for Database {
+    fn eq(&self, other: &Self) -> bool {
+        let Database {
+            localities,
+            locality_codes,
+            public_spaces,
+            ranges,
+            municipalities,
+            provinces,
+            municipality_codes,
+            locality_municipality,
+            municipality_province,
+            locality_had_suffix,
+            municipality_had_suffix,
+            house_number_suffixes,
+            range_points,
+            checksum: _,
+            suggest_index: _,
+            reverse_index: _,
+            locality_postal_codes: _,
+            street_index: _,
+            address_count: _,
+        } = self;
+
+        *localities == other.localities
+            && *locality_codes == other.locality_codes
+            && *public_spaces == other.public_spaces
+            && *ranges == other.ranges
+            && *municipalities == other.municipalities
+            && *provinces == other.provinces
+            && *municipality_codes == other.municipality_codes
+            && *locality_municipality == other.locality_municipality
+            && *municipality_province == other.municipality_province
+            && *locality_had_suffix == other.locality_had_suffix
+            && *municipality_had_suffix == other.municipality_had_suffix
+            && *house_number_suffixes == other.house_number_suffixes
+            && *range_points == other.range_points
+    }
+}
+
+impl Eq for Database {}
+
+/// Shows the tables and the checksum, leaving out the indexes.
+impl std::fmt::Debug for Database {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Database")
+            .field("localities", &self.localities)
+            .field("locality_codes", &self.locality_codes)
+            .field("public_spaces", &self.public_spaces)
+            .field("ranges", &self.ranges)
+            .field("municipalities", &self.municipalities)
+            .field("provinces", &self.provinces)
+            .field("municipality_codes", &self.municipality_codes)
+            .field("locality_municipality", &self.locality_municipality)
+            .field("municipality_province", &self.municipality_province)
+            .field("locality_had_suffix", &self.locality_had_suffix)
+            .field("municipality_had_suffix", &self.municipality_had_suffix)
+            .field("house_number_suffixes", &self.house_number_suffixes)
+            .field("range_points", &self.range_points)
+            .field("checksum", &self.checksum)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The names found for an address, as returned by [`DatabaseHandle::lookup_detailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AddressResult<'a> {
+    /// Public space (openbare ruimte) name, usually the street.
+    pub public_space: &'a str,
+    /// Locality (woonplaats) name.
+    pub locality: &'a str,
+    /// Municipality (gemeente) the locality belongs to, when the database
+    /// links it to one.
+    pub municipality: Option<&'a str>,
+    /// Average position of the addresses in the matched range, when the
+    /// database was built with geometry.
+    pub point: Option<RdPoint>,
+}
+
+/// The address closest to a position, as returned by
+/// [`DatabaseHandle::reverse`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ReverseResult<'a> {
+    /// Postal code of the matched range, e.g. `1234AB`.
+    pub postal_code: String,
+    pub address: AddressResult<'a>,
+    /// Distance from the queried position to the range position, in metres.
+    pub distance: f64,
+}
+
+/// One house number range of a postal code, as returned by
+/// [`DatabaseHandle::ranges_for_postal_code`].
+///
+/// The range covers the house numbers `start`, `start + step`, … up to and
+/// including `start + length * step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeInfo<'a> {
+    pub start: u32,
+    pub length: u16,
+    pub step: u8,
+    /// Public space (openbare ruimte) name, usually the street.
+    pub public_space: &'a str,
+    /// Locality (woonplaats) name.
+    pub locality: &'a str,
+}
+
+impl RangeInfo<'_> {
+    /// The house numbers in the range, in increasing order.
+    pub fn house_numbers(&self) -> impl Iterator<Item = u32> + use<> {
+        let (start, step) = (self.start, u32::from(self.step));
+        (0..=u32::from(self.length)).map_while(move |i| start.checked_add(i * step))
+    }
+
+    /// Whether `house_number` is one of the house numbers in the range.
+    pub fn contains(&self, house_number: u32) -> bool {
+        let Some(offset) = house_number.checked_sub(self.start) else {
+            return false;
+        };
+        let step = u32::from(self.step);
+        offset <= u32::from(self.length) * step && offset.is_multiple_of(step)
+    }
 }
 
 /// Details for one locality, as returned by [`DatabaseHandle::locality_details`].
 #[derive(Debug, Clone, Copy)]
 pub struct LocalityDetail<'a> {
+    /// Position of the locality in the database, as returned by
+    /// [`DatabaseHandle::locality_index`].
+    pub index: u16,
     /// Locality (woonplaats) name.
     pub name: &'a str,
     /// BAG woonplaatsidentificatiecode — uniquely identifies the woonplaats
@@ -67,6 +378,21 @@ pub struct LocalityDetail<'a> {
     pub had_suffix: bool,
 }
 
+/// Database counts, as returned by [`DatabaseHandle::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DatabaseStats {
+    /// Number of localities.
+    pub localities: usize,
+    /// Number of distinct public space names.
+    pub public_spaces: usize,
+    /// Number of house number ranges.
+    pub ranges: usize,
+    /// Number of house numbers covered by the ranges. Addresses that differ
+    /// only in house letter or addition count once.
+    pub total_addresses: u64,
+}
+
 /// Details for one municipality, as returned by
 /// [`DatabaseHandle::municipality_details`].
 #[derive(Debug, Clone, Copy)]
@@ -85,8 +411,8 @@ pub struct MunicipalityDetail<'a> {
     pub had_suffix: bool,
 }
 
-pub struct DatabaseView {
-    bytes: &'static [u8],
+pub struct DatabaseView<'a> {
+    bytes: ViewBytes<'a>,
     locality_count: u32,
     public_space_count: u32,
     range_count: u32,
@@ -111,27 +437,48 @@ pub struct DatabaseView {
     locality_codes_offset: usize,
     locality_had_suffix_offset: usize,
     municipality_had_suffix_offset: usize,
+    house_number_suffix_count: u32,
+    house_number_suffixes_offset: usize,
+    range_point_count: u32,
+    range_points_offset: usize,
+    checksum: u32,
+    suggest_index: OnceLock<SuggestIndex>,
+    reverse_index: OnceLock<ReverseIndex>,
+    locality_postal_codes: OnceLock<LocalityPostalCodes>,
+    street_index: OnceLock<StreetIndex>,
+    address_count: OnceLock<u64>,
 }
 
-#[cfg(not(feature = "create"))]
+#[cfg(not(any(feature = "create", feature = "no_embedded_db")))]
 pub(crate) const DATABASE_BYTES: &[u8] =
     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/bag.bin"));
 
-#[cfg(feature = "create")]
+#[cfg(any(feature = "create", feature = "no_embedded_db"))]
 pub(crate) const DATABASE_BYTES: &[u8] = &[];
 
-pub enum DatabaseHandle {
+/// A BAG database, decoded into a [`Database`] or viewed in its encoded
+/// bytes.
+///
+/// Handles don't implement `Clone`: a view borrows or maps the bytes it
+/// reads from, and the decoded tables are large. Share a handle through an
+/// [`Arc`](std::sync::Arc) instead, as the web service does to keep serving
+/// the old database while a reloaded one is swapped in.
+pub enum DatabaseHandle<'a> {
     Decoded(Database),
-    View(DatabaseView),
+    View(DatabaseView<'a>),
 }
 
+/// Iterator over the locality names of a [`DatabaseHandle`].
 pub struct Localities<'a> {
     inner: LocalitiesInner<'a>,
 }
 
 enum LocalitiesInner<'a> {
     Decoded(std::slice::Iter<'a, String>),
-    View { view: &'a DatabaseView, index: u32 },
+    View {
+        view: &'a DatabaseView<'a>,
+        index: u32,
+    },
 }
 
 impl<'a> Iterator for Localities<'a> {
@@ -160,7 +507,40 @@ impl<'a> Iterator for Localities<'a> {
     }
 }
 
-impl DatabaseHandle {
+/// Iterator over the public space (street) names of a [`DatabaseHandle`].
+pub struct PublicSpaces<'a> {
+    inner: PublicSpacesInner<'a>,
+}
+
+enum PublicSpacesInner<'a> {
+    Decoded(std::slice::Iter<'a, String>),
+    View {
+        view: &'a DatabaseView<'a>,
+        index: u32,
+    },
+}
+
+impl<'a> Iterator for PublicSpaces<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            PublicSpacesInner::Decoded(iter) => iter.next().map(String::as_str),
+            PublicSpacesInner::View { view, index } => {
+                while *index < view.public_space_count {
+                    let current = *index;
+                    *index += 1;
+                    if let Some(name) = view.public_space_name(current) {
+                        return Some(name);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<'a> DatabaseHandle<'a> {
     pub fn is_empty(&self) -> bool {
         match self {
             DatabaseHandle::Decoded(db) => db.is_empty(),
@@ -168,6 +548,57 @@ impl DatabaseHandle {
         }
     }
 
+    /// CRC32 checksum of the database file, which changes whenever the
+    /// database is rebuilt with different data.
+    ///
+    /// `None` for a database that wasn't read from a file, e.g. one built in
+    /// memory.
+    pub fn checksum(&self) -> Option<u32> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.checksum,
+            DatabaseHandle::View(view) => Some(view.checksum),
+        }
+    }
+
+    /// Number of address ranges, without iterating them.
+    pub fn range_count(&self) -> usize {
+        match self {
+            DatabaseHandle::Decoded(db) => db.ranges.len(),
+            DatabaseHandle::View(view) => view.range_count as usize,
+        }
+    }
+
+    /// Counts of the localities, public spaces, ranges and addresses.
+    ///
+    /// The address count takes a pass over all ranges the first time; later
+    /// calls reuse it.
+    pub fn stats(&self) -> DatabaseStats {
+        let (public_spaces, address_count) = match self {
+            DatabaseHandle::Decoded(db) => (db.public_spaces.len(), &db.address_count),
+            DatabaseHandle::View(view) => (view.public_space_count as usize, &view.address_count),
+        };
+        let total_addresses = *address_count.get_or_init(|| {
+            (0..self.range_count())
+                .filter_map(|index| self.range_info(index))
+                .map(|(_, range)| u64::from(range.length) + 1)
+                .sum()
+        });
+        DatabaseStats {
+            localities: self.locality_count(),
+            public_spaces,
+            ranges: self.range_count(),
+            total_addresses,
+        }
+    }
+
+    /// Number of localities, without iterating them.
+    pub fn locality_count(&self) -> usize {
+        match self {
+            DatabaseHandle::Decoded(db) => db.localities.len(),
+            DatabaseHandle::View(view) => view.locality_count as usize,
+        }
+    }
+
     pub fn localities(&'_ self) -> Localities<'_> {
         match self {
             DatabaseHandle::Decoded(db) => Localities {
@@ -179,10 +610,383 @@ impl DatabaseHandle {
         }
     }
 
+    /// Index of the locality named exactly `name`, found by binary search
+    /// over the sorted names.
+    ///
+    /// Names shared by several localities resolve to the first of them.
+    pub fn locality_index(&self, name: &str) -> Option<u16> {
+        let count = self.locality_count().min(u16::MAX as usize + 1);
+        // For a view, only the names the search visits are read.
+        let index = partition_point_range(count, |index| {
+            self.locality_name(index as u16)
+                .is_none_or(|locality| locality < name)
+        });
+        let index = u16::try_from(index).ok()?;
+        (self.locality_name(index)? == name).then_some(index)
+    }
+
+    /// First and last index of the localities named exactly `name`; they are
+    /// adjacent in the sorted names.
+    fn locality_indexes(&self, name: &str) -> Option<(u16, u16)> {
+        let first = self.locality_index(name)?;
+        let last = (first..=u16::MAX)
+            .take_while(|&index| self.locality_name(index) == Some(name))
+            .last()
+            .unwrap_or(first);
+        Some((first, last))
+    }
+
+    /// Index of the public space named exactly `name`, found by binary
+    /// search over the sorted, distinct names.
+    fn public_space_index(&self, name: &str) -> Option<u32> {
+        let count = match self {
+            DatabaseHandle::Decoded(db) => db.public_spaces.len(),
+            DatabaseHandle::View(view) => view.public_space_count as usize,
+        };
+        let index = partition_point_range(count, |index| {
+            self.public_space_name(index as u32)
+                .is_none_or(|public_space| public_space < name)
+        });
+        let index = u32::try_from(index).ok()?;
+        (self.public_space_name(index)? == name).then_some(index)
+    }
+
+    /// Every distinct postal code of the localities named exactly `name`,
+    /// sorted. Returns an empty list for unknown names.
+    ///
+    /// Ranges are sorted by postal code, so without
+    /// [`DatabaseHandle::build_locality_postal_code_index`] this scans all of
+    /// them.
+    pub fn postal_codes_for_locality(&self, name: &str) -> Vec<String> {
+        let Some((first, last)) = self.locality_indexes(name) else {
+            return Vec::new();
+        };
+
+        let mut postal_codes: Vec<u32> = match self.locality_postal_codes_cell().get() {
+            Some(index) => index.postal_codes(first, last).collect(),
+            None => (0..self.range_count())
+                .filter_map(|index| self.range_keys(index))
+                .filter(|&(_, _, locality)| (first..=last).contains(&locality))
+                .map(|(postal_code, _, _)| postal_code)
+                .collect(),
+        };
+        postal_codes.sort_unstable();
+        postal_codes.dedup();
+        postal_codes
+            .into_iter()
+            .map(|postal_code| String::from_utf8_lossy(&decode_pc(postal_code)).into_owned())
+            .collect()
+    }
+
+    /// Build the index that answers [`DatabaseHandle::postal_codes_for_locality`]
+    /// without scanning all ranges.
+    ///
+    /// It holds every distinct locality / postal code pair, some 8 bytes
+    /// each; for the full BAG that is a few megabytes, and building it takes
+    /// a pass over all ranges. It is opt-in, and [`DatabaseHandle::load`]
+    /// doesn't build it. Calling this again is a no-op.
+    pub fn build_locality_postal_code_index(&self) {
+        self.locality_postal_codes_cell().get_or_init(|| {
+            LocalityPostalCodes::build(
+                (0..self.range_count())
+                    .filter_map(|index| self.range_keys(index))
+                    .map(|(postal_code, _, locality)| (locality, postal_code)),
+            )
+        });
+    }
+
+    fn locality_postal_codes_cell(&self) -> &OnceLock<LocalityPostalCodes> {
+        match self {
+            DatabaseHandle::Decoded(db) => &db.locality_postal_codes,
+            DatabaseHandle::View(view) => &view.locality_postal_codes,
+        }
+    }
+
+    /// Look up the postal code of an address by its public space and
+    /// locality names, for sources without postal codes.
+    ///
+    /// Both names must match exactly. Returns the postal code and the range
+    /// holding `house_number`, the first in postal code order when there are
+    /// several. Ranges are sorted by postal code, so without
+    /// [`DatabaseHandle::build_street_index`] this scans all of them.
+    pub fn lookup_by_street(
+        &self,
+        public_space: &str,
+        locality: &str,
+        house_number: u32,
+    ) -> Option<(String, RangeInfo<'_>)> {
+        let public_space = self.public_space_index(public_space)?;
+        let (first, last) = self.locality_indexes(locality)?;
+        let contains = |index: &usize| {
+            self.range_info(*index)
+                .is_some_and(|(_, range)| range.contains(house_number))
+        };
+
+        let index = match self.street_index_cell().get() {
+            Some(streets) => streets
+                .ranges(public_space, first, last)
+                .filter(contains)
+                .min(),
+            None => (0..self.range_count())
+                .filter(|&index| {
+                    self.range_keys(index).is_some_and(|(_, ps, locality)| {
+                        ps == public_space && (first..=last).contains(&locality)
+                    })
+                })
+                .find(contains),
+        }?;
+        let (postal_code, range) = self.range_info(index)?;
+        Some((
+            String::from_utf8_lossy(&decode_pc(postal_code)).into_owned(),
+            range,
+        ))
+    }
+
+    /// Build the index that answers [`DatabaseHandle::lookup_by_street`]
+    /// without scanning all ranges.
+    ///
+    /// It holds 12 bytes per range, a few megabytes for the full BAG, and
+    /// building it sorts all ranges. It is opt-in: call it after
+    /// [`DatabaseHandle::load`] when looking up many addresses by street.
+    /// Calling this again is a no-op.
+    pub fn build_street_index(&self) {
+        self.street_index_cell().get_or_init(|| {
+            StreetIndex::build((0..self.range_count()).filter_map(|index| {
+                let (_, public_space, locality) = self.range_keys(index)?;
+                Some((public_space, locality, index))
+            }))
+        });
+    }
+
+    fn street_index_cell(&self) -> &OnceLock<StreetIndex> {
+        match self {
+            DatabaseHandle::Decoded(db) => &db.street_index,
+            DatabaseHandle::View(view) => &view.street_index,
+        }
+    }
+
+    /// Encoded postal code, public space index and locality index of the
+    /// range at `index`.
+    fn range_keys(&self, index: usize) -> Option<(u32, u32, u16)> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.ranges.get(index).map(|range| {
+                (
+                    range.postal_code,
+                    range.public_space_index,
+                    range.locality_index,
+                )
+            }),
+            DatabaseHandle::View(view) => {
+                let range = view.range_at(index)?;
+                Some((
+                    view.range_postal_code(index)?,
+                    range.public_space_index,
+                    range.locality_index,
+                ))
+            }
+        }
+    }
+
+    fn range_info(&self, index: usize) -> Option<(u32, RangeInfo<'_>)> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.range_info(index),
+            DatabaseHandle::View(view) => view.range_info(index),
+        }
+    }
+
+    fn public_space_name(&self, index: u32) -> Option<&str> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.public_space_name(index),
+            DatabaseHandle::View(view) => view.public_space_name(index),
+        }
+    }
+
+    fn locality_name(&self, index: u16) -> Option<&str> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.locality_name(index),
+            DatabaseHandle::View(view) => view.locality_name(index),
+        }
+    }
+
+    /// Build the in-memory index that speeds up [`DatabaseHandle::suggest`]
+    /// and [`DatabaseHandle::suggest_public_spaces`].
+    ///
+    /// Without the index every suggestion folds the diacritics of and scores
+    /// all names; with it, names are folded once and public space prefix
+    /// queries are answered from a sorted list. Suggestions still carry the
+    /// original names, and results are the same either way. Building takes a
+    /// moment and memory for a copy of the names, so it is opt-in;
+    /// [`DatabaseHandle::load`] builds it. Calling this again is a no-op.
+    pub fn build_suggest_index(&self) {
+        self.suggest_index_cell()
+            .get_or_init(|| SuggestIndex::build(self.public_spaces(), place_candidates(self)));
+    }
+
+    /// The suggest index, when it was built.
+    pub(crate) fn suggest_index(&self) -> Option<&SuggestIndex> {
+        self.suggest_index_cell().get()
+    }
+
+    fn suggest_index_cell(&self) -> &OnceLock<SuggestIndex> {
+        match self {
+            DatabaseHandle::Decoded(db) => &db.suggest_index,
+            DatabaseHandle::View(view) => &view.suggest_index,
+        }
+    }
+
+    /// Build the grid index used by [`DatabaseHandle::reverse`].
+    ///
+    /// [`DatabaseHandle::load`] and friends build it; otherwise the first
+    /// reverse lookup does. Calling this again is a no-op.
+    pub fn build_reverse_index(&self) {
+        self.reverse_index();
+    }
+
+    fn reverse_index(&self) -> &ReverseIndex {
+        let cell = match self {
+            DatabaseHandle::Decoded(db) => &db.reverse_index,
+            DatabaseHandle::View(view) => &view.reverse_index,
+        };
+        cell.get_or_init(|| {
+            ReverseIndex::build(
+                (0..self.range_count())
+                    .filter_map(|index| Some((index, self.range_address(index)?.point?))),
+            )
+        })
+    }
+
+    /// Find the address range whose position is closest to a WGS84
+    /// latitude / longitude, as long as it lies within `max_distance` metres.
+    ///
+    /// Returns `None` when nothing is that close, including when the database
+    /// was built without positions.
+    pub fn reverse(&self, lat: f64, lon: f64, max_distance: f64) -> Option<ReverseResult<'_>> {
+        let (x, y) = wgs84_to_rd(lat, lon);
+        let (index, distance) = self.reverse_index().nearest(x, y, max_distance)?;
+        let postal_code = match self {
+            DatabaseHandle::Decoded(db) => db.ranges.get(index)?.postal_code,
+            DatabaseHandle::View(view) => view.range_postal_code(index)?,
+        };
+        Some(ReverseResult {
+            postal_code: String::from_utf8_lossy(&decode_pc(postal_code)).into_owned(),
+            address: self.range_address(index)?,
+            distance,
+        })
+    }
+
+    fn range_address(&self, index: usize) -> Option<AddressResult<'_>> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.range_address(index),
+            DatabaseHandle::View(view) => view.range_address(index),
+        }
+    }
+
+    /// Iterate over all distinct public space (street) names, in sorted order.
+    ///
+    /// A name shared by streets in several localities is yielded once.
+    pub fn public_spaces(&'_ self) -> PublicSpaces<'_> {
+        match self {
+            DatabaseHandle::Decoded(db) => PublicSpaces {
+                inner: PublicSpacesInner::Decoded(db.public_spaces.iter()),
+            },
+            DatabaseHandle::View(view) => PublicSpaces {
+                inner: PublicSpacesInner::View { view, index: 0 },
+            },
+        }
+    }
+
+    /// Look up the public space and locality of an address.
+    ///
+    /// Returns `(public_space, locality)`; prefer [`DatabaseHandle::lookup_detailed`],
+    /// which names the fields.
     pub fn lookup(&self, postalcode: &str, house_number: u32) -> Option<(&str, &str)> {
+        self.lookup_detailed(postalcode, house_number)
+            .map(|address| (address.public_space, address.locality))
+    }
+
+    /// Look up an address by postal code (e.g. `1234AB`, letters in either
+    /// case) and house number.
+    pub fn lookup_detailed(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+    ) -> Option<AddressResult<'_>> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.lookup_detailed(postalcode, house_number),
+            DatabaseHandle::View(view) => view.lookup_detailed(postalcode, house_number),
+        }
+    }
+
+    /// Whether the postal code is known, i.e. has at least one house number
+    /// range. Tells an unknown postal code apart from an unknown house number
+    /// when [`DatabaseHandle::lookup_detailed`] finds nothing.
+    pub fn has_postal_code(&self, postalcode: &str) -> bool {
         match self {
-            DatabaseHandle::Decoded(db) => db.lookup(postalcode, house_number),
-            DatabaseHandle::View(view) => view.lookup(postalcode, house_number),
+            DatabaseHandle::Decoded(db) => db.has_postal_code(postalcode),
+            DatabaseHandle::View(view) => view.has_postal_code(postalcode),
+        }
+    }
+
+    /// List every house number range registered under a postal code, in
+    /// database order. Returns an empty list for unknown or malformed codes.
+    pub fn ranges_for_postal_code(&self, postalcode: &str) -> Vec<RangeInfo<'_>> {
+        match self {
+            DatabaseHandle::Decoded(db) => db.ranges_for_postal_code(postalcode),
+            DatabaseHandle::View(view) => view.ranges_for_postal_code(postalcode),
+        }
+    }
+
+    /// Iterate over every house number range with its postal code, in
+    /// database order (sorted by postal code).
+    pub fn ranges(&self) -> impl Iterator<Item = (String, RangeInfo<'_>)> {
+        (0..self.range_count()).filter_map(|index| {
+            let (postal_code, range) = self.range_info(index)?;
+            Some((
+                String::from_utf8_lossy(&decode_pc(postal_code)).into_owned(),
+                range,
+            ))
+        })
+    }
+
+    /// Iterate over every house letter / addition record with its postal
+    /// code, in database order (sorted by postal code and house number).
+    pub fn house_number_suffixes(&self) -> impl Iterator<Item = (String, HouseNumberSuffix)> {
+        let count = match self {
+            DatabaseHandle::Decoded(db) => db.house_number_suffixes.len(),
+            DatabaseHandle::View(view) => view.house_number_suffix_count(),
+        };
+        (0..count).filter_map(|index| {
+            let suffix = match self {
+                DatabaseHandle::Decoded(db) => db.house_number_suffixes.get(index).copied(),
+                DatabaseHandle::View(view) => view.house_number_suffix_at(index),
+            }?;
+            Some((
+                String::from_utf8_lossy(&decode_pc(suffix.postal_code)).into_owned(),
+                suffix,
+            ))
+        })
+    }
+
+    /// Find the house letter / addition record for an address.
+    ///
+    /// `letter` and `addition` are matched case-insensitively; passing `None`
+    /// for both never matches, since plain house numbers have no record.
+    /// Returns `None` when the address does not exist with exactly this
+    /// letter and addition, or when they are not a valid letter / addition.
+    pub fn house_number_suffix(
+        &self,
+        postalcode: &str,
+        house_number: u32,
+        letter: Option<&str>,
+        addition: Option<&str>,
+    ) -> Option<HouseNumberSuffix> {
+        match self {
+            DatabaseHandle::Decoded(db) => {
+                db.house_number_suffix(postalcode, house_number, letter, addition)
+            }
+            DatabaseHandle::View(view) => {
+                view.house_number_suffix(postalcode, house_number, letter, addition)
+            }
         }
     }
 
@@ -221,54 +1025,379 @@ impl DatabaseHandle {
         include_municipalities: bool,
         include_aliases: bool,
     ) -> Vec<String> {
-        crate::suggest::suggest(
-            self,
+        self.suggest_scored(
             query,
             threshold,
             limit,
             include_municipalities,
             include_aliases,
         )
+        .into_iter()
+        .map(|suggestion| suggestion.name)
+        .collect()
+    }
+
+    /// Like [`DatabaseHandle::suggest`], but also returns the match score of
+    /// every suggestion.
+    pub fn suggest_scored(
+        &self,
+        query: &str,
+        threshold: f32,
+        limit: usize,
+        include_municipalities: bool,
+        include_aliases: bool,
+    ) -> Vec<Suggestion> {
+        self.suggest_scored_with(
+            query,
+            threshold,
+            limit,
+            include_municipalities,
+            include_aliases,
+            &ScoringConfig::default(),
+        )
+    }
+
+    /// Like [`DatabaseHandle::suggest_scored`], scoring names with the
+    /// weights of `scoring`.
+    pub fn suggest_scored_with(
+        &self,
+        query: &str,
+        threshold: f32,
+        limit: usize,
+        include_municipalities: bool,
+        include_aliases: bool,
+        scoring: &ScoringConfig,
+    ) -> Vec<Suggestion> {
+        let filter = PlaceFilter {
+            municipalities: include_municipalities,
+            aliases: include_aliases,
+            localities: None,
+        };
+        crate::suggest::suggest(self, query, threshold, limit, &filter, scoring)
+    }
+
+    /// Like [`DatabaseHandle::suggest_scored_with`], offering only the
+    /// localities with an address whose postal code starts with
+    /// `postal_code_prefix`, e.g. `12` or `1234A`. Municipalities are not
+    /// offered. An invalid prefix matches nothing.
+    ///
+    /// Finding the localities visits every range under the prefix: a few for
+    /// a full four digits, but a tenth of the database for a single digit.
+    pub fn suggest_in_postal_area(
+        &self,
+        query: &str,
+        postal_code_prefix: &str,
+        threshold: f32,
+        limit: usize,
+        include_aliases: bool,
+        scoring: &ScoringConfig,
+    ) -> Vec<Suggestion> {
+        let filter = PlaceFilter {
+            municipalities: false,
+            aliases: include_aliases,
+            localities: Some(
+                self.localities_in_postal_area(postal_code_prefix)
+                    .unwrap_or_default(),
+            ),
+        };
+        crate::suggest::suggest(self, query, threshold, limit, &filter, scoring)
+    }
+
+    /// Indexes of the localities with an address whose postal code starts
+    /// with `prefix`, sorted and distinct. `None` for an invalid prefix.
+    ///
+    /// Ranges are sorted by postal code, so only those under the prefix are
+    /// visited.
+    fn localities_in_postal_area(&self, prefix: &str) -> Option<Vec<u16>> {
+        let (low, high) = postal_code_prefix_bounds(prefix)?;
+        let count = self.range_count();
+        let postal_code = |index| {
+            self.range_keys(index)
+                .map(|(postal_code, _, _)| postal_code)
+        };
+        let start = partition_point_range(count, |index| {
+            postal_code(index).is_none_or(|code| code < low)
+        });
+        let end = partition_point_range(count, |index| {
+            postal_code(index).is_none_or(|code| code <= high)
+        });
+
+        let mut localities: Vec<u16> = (start..end)
+            .filter_map(|index| self.range_keys(index))
+            .map(|(_, _, locality)| locality)
+            .collect();
+        localities.sort_unstable();
+        localities.dedup();
+        Some(localities)
+    }
+
+    /// Fuzzy-search public space (street) names for `query`, returning the
+    /// matching names.
+    ///
+    /// Uses the same scoring as [`DatabaseHandle::suggest`].
+    pub fn suggest_public_spaces(&self, query: &str, threshold: f32, limit: usize) -> Vec<String> {
+        self.suggest_public_spaces_scored(query, threshold, limit)
+            .into_iter()
+            .map(|suggestion| suggestion.name)
+            .collect()
+    }
+
+    /// Like [`DatabaseHandle::suggest_public_spaces`], but also returns the
+    /// match score of every suggestion.
+    pub fn suggest_public_spaces_scored(
+        &self,
+        query: &str,
+        threshold: f32,
+        limit: usize,
+    ) -> Vec<Suggestion> {
+        self.suggest_public_spaces_scored_with(query, threshold, limit, &ScoringConfig::default())
+    }
+
+    /// Like [`DatabaseHandle::suggest_public_spaces_scored`], scoring names
+    /// with the weights of `scoring`.
+    pub fn suggest_public_spaces_scored_with(
+        &self,
+        query: &str,
+        threshold: f32,
+        limit: usize,
+        scoring: &ScoringConfig,
+    ) -> Vec<Suggestion> {
+        crate::suggest::suggest_public_spaces(self, query, threshold, limit, scoring)
     }
 
     /// Load the embedded BAG database.
-    pub fn load() -> Result<DatabaseHandle, DatabaseError> {
-        #[cfg(feature = "compressed_database")]
-        {
-            let mut decoder =
-                zstd::Decoder::new(DATABASE_BYTES).map_err(|_| DatabaseError::InvalidMagic)?;
-            let db = Database::from_reader(&mut decoder)?;
-            Ok(DatabaseHandle::Decoded(db))
+    ///
+    /// Builds with the `create` or `no_embedded_db` feature embed none, and
+    /// return [`DatabaseError::NotEmbedded`]; load a database file with
+    /// [`DatabaseHandle::load_from_path`] instead.
+    pub fn load() -> Result<DatabaseHandle<'static>, DatabaseError> {
+        if DATABASE_BYTES.is_empty() {
+            return Err(DatabaseError::NotEmbedded);
         }
-        #[cfg(not(feature = "compressed_database"))]
-        {
-            let view = DatabaseView::from_bytes(DATABASE_BYTES)?;
-            Ok(DatabaseHandle::View(view))
+        DatabaseHandle::from_bytes(DATABASE_BYTES)
+    }
+
+    /// Open a BAG database held in memory, e.g. a file read at runtime.
+    ///
+    /// A compressed database is decoded; an uncompressed one is viewed in
+    /// place, so the handle borrows `bytes` rather than copying them.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<DatabaseHandle<'a>, DatabaseError> {
+        let handle = Self::open_bytes(bytes)?;
+        handle.build_suggest_index();
+        handle.build_reverse_index();
+        Ok(handle)
+    }
+
+    /// Load a BAG database file at runtime, e.g. a `bag.bin` rebuilt after
+    /// this binary was compiled.
+    ///
+    /// Both zstd-compressed and uncompressed files are accepted; the format
+    /// is detected from the leading magic bytes. Returns
+    /// [`DatabaseError::NotFound`] when the file doesn't exist and
+    /// [`DatabaseError::Io`] when it cannot be read.
+    pub fn load_from_path(path: &Path) -> Result<DatabaseHandle<'static>, DatabaseError> {
+        let bytes = std::fs::read(path).map_err(open_error)?;
+        let handle = if bytes.starts_with(&util::ZSTD_MAGIC) {
+            DatabaseHandle::Decoded(decode_compressed(&bytes)?)
+        } else {
+            #[cfg(feature = "compressed_database")]
+            {
+                DatabaseHandle::Decoded(Database::from_reader(&bytes[..])?)
+            }
+            // Without the decoder a view is the only option.
+            #[cfg(not(feature = "compressed_database"))]
+            {
+                DatabaseHandle::View(DatabaseView::from_owned(bytes)?)
+            }
+        };
+        handle.build_suggest_index();
+        handle.build_reverse_index();
+        Ok(handle)
+    }
+
+    /// Load the embedded BAG database like [`DatabaseHandle::load`], but
+    /// look addresses up in its bytes instead of decoding them into tables.
+    ///
+    /// A compressed database is decompressed once into a buffer the handle
+    /// owns, which takes far less memory than the decoded tables at the cost
+    /// of somewhat slower lookups.
+    pub fn load_view() -> Result<DatabaseHandle<'static>, DatabaseError> {
+        if DATABASE_BYTES.is_empty() {
+            return Err(DatabaseError::NotEmbedded);
+        }
+        let view = if DATABASE_BYTES.starts_with(&util::ZSTD_MAGIC) {
+            decompress_to_view(DATABASE_BYTES)?
+        } else {
+            DatabaseView::from_bytes(DATABASE_BYTES)?
+        };
+        let handle = DatabaseHandle::View(view);
+        handle.build_suggest_index();
+        handle.build_reverse_index();
+        Ok(handle)
+    }
+
+    /// Load a BAG database file like [`DatabaseHandle::load_from_path`], but
+    /// look addresses up in its bytes instead of decoding them into tables,
+    /// as with [`DatabaseHandle::load_view`].
+    pub fn load_view_from_path(path: &Path) -> Result<DatabaseHandle<'static>, DatabaseError> {
+        let bytes = std::fs::read(path).map_err(open_error)?;
+        let view = if bytes.starts_with(&util::ZSTD_MAGIC) {
+            decompress_to_view(&bytes)?
+        } else {
+            DatabaseView::from_owned(bytes)?
+        };
+        let handle = DatabaseHandle::View(view);
+        handle.build_suggest_index();
+        handle.build_reverse_index();
+        Ok(handle)
+    }
+
+    /// Map a BAG database file into memory and look addresses up directly in
+    /// the mapped bytes, so worker processes share one copy through the page
+    /// cache instead of each holding a decoded database.
+    ///
+    /// Compressed files cannot be viewed in place and are decoded as with
+    /// [`DatabaseHandle::load_from_path`]. The file must not be modified or
+    /// truncated while the handle is alive; write a new file and rename it
+    /// over the old one instead.
+    #[cfg(feature = "mmap")]
+    pub fn mmap_from_path(path: &Path) -> Result<DatabaseHandle<'static>, DatabaseError> {
+        let file = std::fs::File::open(path).map_err(open_error)?;
+        // SAFETY: the mapping is only read, and the documented contract is
+        // that the file is not changed underneath it.
+        let map = unsafe { memmap2::Mmap::map(&file) }?;
+        let handle = if map.starts_with(&util::ZSTD_MAGIC) {
+            DatabaseHandle::Decoded(decode_compressed(&map)?)
+        } else {
+            DatabaseHandle::View(DatabaseView::from_mmap(map)?)
+        };
+        handle.build_suggest_index();
+        handle.build_reverse_index();
+        Ok(handle)
+    }
+
+    /// Load the database file at `path`, or the embedded database when there
+    /// is none, the way the enabled features prefer: with the `mmap` feature
+    /// the file is mapped rather than read, and with the `decompress_to_view`
+    /// feature the database is viewed rather than decoded.
+    ///
+    /// This is how the service and the `bag-service` commands load their
+    /// database.
+    pub fn open(path: Option<&Path>) -> Result<DatabaseHandle<'static>, DatabaseError> {
+        match path {
+            #[cfg(feature = "mmap")]
+            Some(path) => DatabaseHandle::mmap_from_path(path),
+            #[cfg(all(not(feature = "mmap"), feature = "decompress_to_view"))]
+            Some(path) => DatabaseHandle::load_view_from_path(path),
+            #[cfg(not(any(feature = "mmap", feature = "decompress_to_view")))]
+            Some(path) => DatabaseHandle::load_from_path(path),
+            #[cfg(feature = "decompress_to_view")]
+            None => DatabaseHandle::load_view(),
+            #[cfg(not(feature = "decompress_to_view"))]
+            None => DatabaseHandle::load(),
+        }
+    }
+
+    /// Open an in-memory database, decoding it when compressed and viewing
+    /// it in place otherwise.
+    fn open_bytes(bytes: &'a [u8]) -> Result<DatabaseHandle<'a>, DatabaseError> {
+        if bytes.starts_with(&util::ZSTD_MAGIC) {
+            Ok(DatabaseHandle::Decoded(decode_compressed(bytes)?))
+        } else {
+            Ok(DatabaseHandle::View(DatabaseView::from_bytes(bytes)?))
         }
     }
 }
 
+/// Error for a database file that could not be opened.
+fn open_error(err: std::io::Error) -> DatabaseError {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        DatabaseError::NotFound
+    } else {
+        DatabaseError::Io(err)
+    }
+}
+
+/// Decode a zstd-compressed database.
+#[cfg(feature = "compressed_database")]
+fn decode_compressed(bytes: &[u8]) -> Result<Database, DatabaseError> {
+    let mut decoder = zstd::Decoder::new(bytes).map_err(|_| DatabaseError::DecompressionFailed)?;
+    Database::from_reader(&mut decoder)
+}
+
+/// Decompress a zstd-compressed database into a view that owns the bytes.
+#[cfg(feature = "compressed_database")]
+fn decompress_to_view(bytes: &[u8]) -> Result<DatabaseView<'static>, DatabaseError> {
+    use std::io::Read;
+
+    let mut decoder = zstd::Decoder::new(bytes).map_err(|_| DatabaseError::DecompressionFailed)?;
+    let mut decompressed = Vec::new();
+    decoder.read_to_end(&mut decompressed)?;
+    DatabaseView::from_owned(decompressed)
+}
+
+/// Compressed databases need the `compressed_database` feature.
+#[cfg(not(feature = "compressed_database"))]
+fn decode_compressed(_bytes: &[u8]) -> Result<Database, DatabaseError> {
+    Err(DatabaseError::DecompressionFailed)
+}
+
+/// Compressed databases need the `compressed_database` feature.
+#[cfg(not(feature = "compressed_database"))]
+fn decompress_to_view(_bytes: &[u8]) -> Result<DatabaseView<'static>, DatabaseError> {
+    Err(DatabaseError::DecompressionFailed)
+}
+
 #[cfg(all(test, feature = "compressed_database"))]
 mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    #[cfg(feature = "create")]
+    use crate::test_support::TempPath;
+    use crate::test_support::{fixture_bytes, fixture_db};
+
+    /// The fixture decoded and viewed in place.
+    fn fixture_handles() -> [DatabaseHandle<'static>; 2] {
+        [
+            DatabaseHandle::Decoded(fixture_db().clone()),
+            DatabaseHandle::View(DatabaseView::from_bytes(fixture_bytes()).unwrap()),
+        ]
+    }
 
     fn verify_test_db(db: &Database) {
         assert_eq!(db.localities.len(), 2);
         assert_eq!(db.public_spaces.len(), 2);
         assert_eq!(db.ranges.len(), 2);
 
-        let lookup_result = db.lookup("1234AB", 56).unwrap();
-        assert_eq!(lookup_result.0, "Abel Eppensstraat");
-        assert_eq!(lookup_result.1, "Hoogerheide");
+        let lookup_result = db.lookup_detailed("1234AB", 56).unwrap();
+        assert_eq!(lookup_result.public_space, "Abel Eppensstraat");
+        assert_eq!(lookup_result.locality, "Hoogerheide");
 
-        let lookup_result = db.lookup("1234AB", 1).unwrap();
-        assert_eq!(lookup_result.0, "Adamistraat");
-        assert_eq!(lookup_result.1, "Huijbergen");
+        let lookup_result = db.lookup_detailed("1234AB", 1).unwrap();
+        assert_eq!(lookup_result.public_space, "Adamistraat");
+        assert_eq!(lookup_result.locality, "Huijbergen");
 
-        let lookup_none = db.lookup("9999ZZ", 1);
+        let lookup_none = db.lookup_detailed("9999ZZ", 1);
         assert!(lookup_none.is_none());
+
+        // Malformed postal codes are rejected instead of panicking.
+        assert!(db.lookup_detailed("123456", 1).is_none());
+        assert!(db.lookup_detailed("1234A", 1).is_none());
+
+        let suffix = db
+            .house_number_suffix("1234AB", 56, Some("a"), None)
+            .unwrap();
+        assert_eq!(suffix.letter(), Some('A'));
+        assert_eq!(suffix.addition(), None);
+        assert!(
+            db.house_number_suffix("1234AB", 56, Some("B"), None)
+                .is_none()
+        );
+        assert!(
+            db.house_number_suffix("1234AB", 56, Some("A"), Some("1"))
+                .is_none()
+        );
     }
 
     #[test]
@@ -281,4 +1410,631 @@ mod tests {
 
         verify_test_db(&db);
     }
+
+    #[test]
+    fn cloned_databases_answer_lookups_independently() {
+        let db = fixture_db();
+        let handle = DatabaseHandle::Decoded(db.clone());
+        handle.build_suggest_index();
+        let DatabaseHandle::Decoded(indexed) = &handle else {
+            unreachable!();
+        };
+
+        let copy = indexed.clone();
+        verify_test_db(db);
+        verify_test_db(&copy);
+        assert!(copy.suggest_index.get().is_some());
+
+        let mut emptied = copy.clone();
+        emptied.ranges.clear();
+        assert!(emptied.lookup_detailed("1234AB", 56).is_none());
+        verify_test_db(&copy);
+    }
+
+    #[test]
+    fn try_new_checks_range_order_and_indexes() {
+        let range = |postal_code, public_space_index, locality_index| NumberRange {
+            postal_code,
+            start: 1,
+            length: 10,
+            public_space_index,
+            locality_index,
+            step: 1,
+        };
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+        let db = Database::try_new(
+            names(&["Amsterdam"]),
+            names(&["Dam", "Rokin"]),
+            vec![
+                range(encode_pc(b"1012JS"), 0, 0),
+                range(encode_pc(b"1012KP"), 1, 0),
+            ],
+        )
+        .unwrap();
+        let address = db.lookup_detailed("1012KP", 5).unwrap();
+        assert_eq!(
+            (address.public_space, address.locality),
+            ("Rokin", "Amsterdam")
+        );
+        assert_eq!(db.locality_municipality, vec![u16::MAX]);
+
+        let unsorted = Database::try_new(
+            names(&["Amsterdam"]),
+            names(&["Dam", "Rokin"]),
+            vec![
+                range(encode_pc(b"1012KP"), 1, 0),
+                range(encode_pc(b"1012JS"), 0, 0),
+            ],
+        );
+        assert!(matches!(
+            unsorted,
+            Err(DatabaseError::UnsortedRanges { index: 1 })
+        ));
+
+        let missing_public_space = Database::try_new(
+            names(&["Amsterdam"]),
+            names(&["Dam"]),
+            vec![
+                range(encode_pc(b"1012JS"), 0, 0),
+                range(encode_pc(b"1012KP"), 1, 0),
+            ],
+        );
+        assert!(matches!(
+            missing_public_space,
+            Err(DatabaseError::RangeOutOfBounds { index: 1 })
+        ));
+
+        let missing_locality = Database::try_new(
+            Vec::new(),
+            names(&["Dam"]),
+            vec![range(encode_pc(b"1012JS"), 0, 0)],
+        );
+        assert!(matches!(
+            missing_locality,
+            Err(DatabaseError::RangeOutOfBounds { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn try_new_checks_name_order() {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+        // Localities may share a name.
+        let shared = Database::try_new(names(&["Hengelo", "Hengelo"]), names(&["Dam"]), vec![]);
+        assert!(shared.is_ok());
+
+        let unsorted_localities =
+            Database::try_new(names(&["Utrecht", "Amsterdam"]), names(&["Dam"]), vec![]);
+        assert!(matches!(
+            unsorted_localities,
+            Err(DatabaseError::UnsortedNames {
+                table: "locality",
+                index: 1
+            })
+        ));
+        let unsorted_public_spaces = Database::try_new(
+            names(&["Amsterdam"]),
+            names(&["Dam", "Rokin", "Damrak"]),
+            vec![],
+        );
+        assert!(matches!(
+            unsorted_public_spaces,
+            Err(DatabaseError::UnsortedNames {
+                table: "public space",
+                index: 2
+            })
+        ));
+        let repeated_public_spaces =
+            Database::try_new(names(&["Amsterdam"]), names(&["Dam", "Dam"]), vec![]);
+        let Err(err) = repeated_public_spaces else {
+            panic!("accepted a repeated public space name");
+        };
+        assert_eq!(err.to_string(), "public space name 1 is out of order");
+    }
+
+    #[test]
+    fn lookup_detailed_names_fields() {
+        let view = DatabaseHandle::View(DatabaseView::from_bytes(fixture_bytes()).unwrap());
+
+        let address = view.lookup_detailed("1234ab", 56).unwrap();
+        assert_eq!(address.public_space, "Abel Eppensstraat");
+        assert_eq!(address.locality, "Hoogerheide");
+        // The fixture is built without municipality data.
+        assert_eq!(address.municipality, None);
+        assert_eq!(
+            view.lookup("1234AB", 56),
+            Some((address.public_space, address.locality))
+        );
+        assert!(view.lookup_detailed("9999ZZ", 1).is_none());
+    }
+
+    #[cfg(any(feature = "create", feature = "no_embedded_db"))]
+    #[test]
+    fn load_without_an_embedded_database_points_to_load_from_path() {
+        let Err(err) = DatabaseHandle::load() else {
+            panic!("loaded an embedded database");
+        };
+        assert!(matches!(err, DatabaseError::NotEmbedded));
+        assert!(err.to_string().contains("load_from_path"), "{err}");
+    }
+
+    #[test]
+    fn open_loads_files_the_way_the_features_prefer() {
+        let handle = DatabaseHandle::open(Some(Path::new("test/bag_uncompressed.bin"))).unwrap();
+        let viewed = cfg!(any(
+            feature = "mmap",
+            feature = "decompress_to_view",
+            not(feature = "compressed_database")
+        ));
+        assert_eq!(matches!(handle, DatabaseHandle::View(_)), viewed);
+        assert_eq!(
+            handle.lookup("1234AB", 56),
+            Some(("Abel Eppensstraat", "Hoogerheide"))
+        );
+    }
+
+    #[test]
+    fn handles_borrow_runtime_buffers() {
+        let bytes = std::fs::read("test/bag_uncompressed.bin").unwrap();
+        let view = DatabaseHandle::from_bytes(&bytes).unwrap();
+        assert!(matches!(view, DatabaseHandle::View(_)));
+
+        let compressed = std::fs::read("test/bag.bin").unwrap();
+        let decoded = DatabaseHandle::from_bytes(&compressed).unwrap();
+        assert!(matches!(decoded, DatabaseHandle::Decoded(_)));
+
+        for handle in [&view, &decoded] {
+            assert_eq!(
+                handle.lookup("1234AB", 56),
+                Some(("Abel Eppensstraat", "Hoogerheide"))
+            );
+            assert!(handle.localities().any(|name| name == "Hoogerheide"));
+            assert!(
+                handle
+                    .public_spaces()
+                    .any(|name| name == "Abel Eppensstraat")
+            );
+        }
+    }
+
+    #[test]
+    fn locality_index_finds_exact_names_in_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        for handle in [&decoded, &view] {
+            for (index, name) in handle.localities().enumerate() {
+                assert_eq!(handle.locality_index(name), Some(index as u16));
+            }
+            assert_eq!(handle.locality_index("Atlantis"), None);
+            assert_eq!(handle.locality_index(""), None);
+        }
+    }
+
+    #[test]
+    fn postal_codes_for_locality_match_with_and_without_index() {
+        let [decoded, view] = fixture_handles();
+
+        let locality = decoded.lookup_detailed("1234AB", 56).unwrap().locality;
+        for handle in [&decoded, &view] {
+            let scanned = handle.postal_codes_for_locality(locality);
+            assert!(scanned.contains(&"1234AB".to_string()));
+            assert!(scanned.is_sorted());
+            assert!(handle.postal_codes_for_locality("Atlantis").is_empty());
+
+            handle.build_locality_postal_code_index();
+            assert_eq!(handle.postal_codes_for_locality(locality), scanned);
+        }
+    }
+
+    #[test]
+    fn lookup_by_street_matches_with_and_without_index() {
+        let [decoded, view] = fixture_handles();
+
+        for handle in [&decoded, &view] {
+            for build_index in [false, true] {
+                if build_index {
+                    handle.build_street_index();
+                }
+                let (postal_code, range) = handle
+                    .lookup_by_street("Abel Eppensstraat", "Hoogerheide", 56)
+                    .unwrap();
+                assert_eq!(postal_code, "1234AB");
+                assert!(range.contains(56));
+                assert_eq!(range.public_space, "Abel Eppensstraat");
+
+                assert!(
+                    handle
+                        .lookup_by_street("Abel Eppensstraat", "Hoogerheide", 100_000)
+                        .is_none()
+                );
+                assert!(
+                    handle
+                        .lookup_by_street("Abel Eppensstraat", "Atlantis", 56)
+                        .is_none()
+                );
+                assert!(
+                    handle
+                        .lookup_by_street("Nergensstraat", "Hoogerheide", 56)
+                        .is_none()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn checksum_is_the_same_for_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        assert!(decoded.checksum().is_some());
+        assert_eq!(decoded.checksum(), view.checksum());
+    }
+
+    #[test]
+    fn stats_count_the_fixture() {
+        let [decoded, view] = fixture_handles();
+
+        let stats = decoded.stats();
+        assert_eq!(
+            (stats.localities, stats.public_spaces, stats.ranges),
+            (2, 2, 2)
+        );
+        // Both ranges hold a single house number.
+        assert_eq!(stats.total_addresses, 2);
+        assert_eq!(view.stats(), stats);
+    }
+
+    #[test]
+    fn public_spaces_iterates_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        let names: Vec<&str> = decoded.public_spaces().collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Abel Eppensstraat"));
+        assert_eq!(view.public_spaces().collect::<Vec<_>>(), names);
+    }
+
+    #[test]
+    fn house_number_suffixes_cover_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        let suffixes: Vec<(String, HouseNumberSuffix)> = decoded.house_number_suffixes().collect();
+        assert!(suffixes.iter().any(|(postal_code, suffix)| {
+            postal_code == "1234AB" && suffix.house_number == 56 && suffix.letter() == Some('A')
+        }));
+        assert_eq!(view.house_number_suffixes().collect::<Vec<_>>(), suffixes);
+    }
+
+    #[test]
+    fn export_csv_covers_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        let mut csv = Vec::new();
+        decoded.export_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "postal_code,house_number,public_space,locality");
+        assert!(rows.contains(&"1234AB,56,Abel Eppensstraat,Hoogerheide"));
+        assert!(rows.contains(&"1234AB,1,Adamistraat,Huijbergen"));
+        let house_numbers: usize = decoded
+            .ranges()
+            .map(|(_, range)| range.house_numbers().count())
+            .sum();
+        assert_eq!(rows.len(), house_numbers + 1);
+
+        let mut view_csv = Vec::new();
+        view.export_csv(&mut view_csv).unwrap();
+        assert_eq!(String::from_utf8(view_csv).unwrap(), csv);
+    }
+
+    #[test]
+    fn export_ndjson_covers_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        let mut ndjson = Vec::new();
+        decoded.export_ndjson(&mut ndjson).unwrap();
+        let ndjson = String::from_utf8(ndjson).unwrap();
+        assert!(
+            ndjson.lines().any(|line| line
+                == r#"{"n":56,"pc":"1234AB","pr":"Abel Eppensstraat","wp":"Hoogerheide"}"#)
+        );
+        let house_numbers: usize = decoded
+            .ranges()
+            .map(|(_, range)| range.house_numbers().count())
+            .sum();
+        assert_eq!(ndjson.lines().count(), house_numbers);
+
+        let mut view_ndjson = Vec::new();
+        view.export_ndjson(&mut view_ndjson).unwrap();
+        assert_eq!(String::from_utf8(view_ndjson).unwrap(), ndjson);
+    }
+
+    #[test]
+    fn load_from_path_accepts_compressed_and_raw_files() {
+        let compressed = DatabaseHandle::load_from_path(Path::new("test/bag.bin")).unwrap();
+        let raw = DatabaseHandle::load_from_path(Path::new("test/bag_uncompressed.bin")).unwrap();
+
+        for database in [&compressed, &raw] {
+            assert!(matches!(database, DatabaseHandle::Decoded(_)));
+            assert_eq!(
+                database.lookup("1234AB", 56),
+                Some(("Abel Eppensstraat", "Hoogerheide"))
+            );
+            assert!(database.suggest_index().is_some());
+        }
+    }
+
+    #[test]
+    fn corrupt_payload_fails_checksum() {
+        let mut bytes = std::fs::read("test/bag_uncompressed.bin").unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(matches!(
+            Database::from_reader(&bytes[..]),
+            Err(DatabaseError::ChecksumMismatch)
+        ));
+        let truncated: &'static [u8] = bytes[..last].to_vec().leak();
+        assert!(matches!(
+            DatabaseView::from_bytes(bytes.leak()),
+            Err(DatabaseError::ChecksumMismatch)
+        ));
+        assert!(matches!(
+            DatabaseView::from_bytes(truncated),
+            Err(DatabaseError::ChecksumMismatch)
+        ));
+    }
+
+    #[test]
+    fn truncated_file_reports_the_io_error() {
+        let bytes = std::fs::read("test/bag_uncompressed.bin").unwrap();
+
+        let Err(error) = Database::from_reader(&bytes[..100]) else {
+            panic!("a truncated file should not decode");
+        };
+        let DatabaseError::Io(io_error) = &error else {
+            panic!("expected an IO error, got {error:?}");
+        };
+        assert_eq!(io_error.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(std::error::Error::source(&error).is_some());
+
+        // Also through `?` in a `Box<dyn Error>` chain.
+        let boxed: Box<dyn std::error::Error> = error.into();
+        assert!(
+            boxed
+                .to_string()
+                .starts_with("database file could not be read")
+        );
+    }
+
+    #[test]
+    fn other_format_version_is_rejected() {
+        let mut bytes = std::fs::read("test/bag_uncompressed.bin").unwrap();
+        bytes[4..6].copy_from_slice(&99u16.to_le_bytes());
+
+        assert!(matches!(
+            Database::from_reader(&bytes[..]),
+            Err(DatabaseError::UnsupportedVersion {
+                found: 99,
+                expected: 3
+            })
+        ));
+        assert!(matches!(
+            DatabaseView::from_bytes(bytes.leak()),
+            Err(DatabaseError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+
+    /// Rewrite a current database file in the version 2 layout, whose
+    /// header stores the section offsets as u32.
+    fn downgrade_to_u32_offsets(bytes: &[u8]) -> Vec<u8> {
+        let shrink = util::DATABASE_HEADER_SIZE - util::DATABASE_HEADER_SIZE_U32_OFFSETS;
+        let mut out = Vec::with_capacity(bytes.len());
+        out.extend_from_slice(&bytes[..4]);
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&bytes[6..8]);
+
+        // Header fields after the reserved u16: true for u64 offsets, false
+        // for u32 counts, ending with the checksum.
+        let fields = [
+            false, false, false, true, true, true, true, true, false, false, true, true, true,
+            true, true, true, true, true, true, true, false, true, false, true, false,
+        ];
+        let mut position = 8;
+        for is_offset in fields {
+            if is_offset {
+                let offset = u64::from_le_bytes(bytes[position..position + 8].try_into().unwrap());
+                out.extend_from_slice(&((offset as usize - shrink) as u32).to_le_bytes());
+                position += 8;
+            } else {
+                out.extend_from_slice(&bytes[position..position + 4]);
+                position += 4;
+            }
+        }
+        assert_eq!(position, util::DATABASE_HEADER_SIZE);
+
+        out.extend_from_slice(&bytes[position..]);
+        out
+    }
+
+    #[test]
+    fn version_2_files_are_still_read() {
+        let bytes = std::fs::read("test/bag_uncompressed.bin").unwrap();
+        let old = downgrade_to_u32_offsets(&bytes);
+
+        let decoded = Database::from_reader(&old[..]).unwrap();
+        verify_test_db(&decoded);
+        let view = DatabaseView::from_bytes(old.leak()).unwrap();
+        assert_eq!(
+            view.lookup("1234AB", 56),
+            Some(("Abel Eppensstraat", "Hoogerheide"))
+        );
+    }
+
+    #[test]
+    fn load_view_from_path_views_compressed_and_raw_files() {
+        for path in ["test/bag.bin", "test/bag_uncompressed.bin"] {
+            let database = DatabaseHandle::load_view_from_path(Path::new(path)).unwrap();
+            assert!(matches!(database, DatabaseHandle::View(_)), "{path}");
+            assert_eq!(
+                database.lookup("1234AB", 56),
+                Some(("Abel Eppensstraat", "Hoogerheide"))
+            );
+            assert!(database.suggest_index().is_some());
+        }
+
+        let missing = DatabaseHandle::load_view_from_path(Path::new("test/does-not-exist.bin"));
+        assert!(matches!(missing, Err(DatabaseError::NotFound)));
+    }
+
+    #[test]
+    fn load_from_path_reports_missing_file() {
+        let result = DatabaseHandle::load_from_path(Path::new("test/does-not-exist.bin"));
+        assert!(matches!(result, Err(DatabaseError::NotFound)));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_from_path_views_uncompressed_file() {
+        let database =
+            DatabaseHandle::mmap_from_path(Path::new("test/bag_uncompressed.bin")).unwrap();
+
+        assert!(matches!(database, DatabaseHandle::View(_)));
+        assert_eq!(
+            database.lookup("1234AB", 56),
+            Some(("Abel Eppensstraat", "Hoogerheide"))
+        );
+        assert_eq!(database.localities().count(), 2);
+    }
+
+    #[test]
+    fn ranges_for_postal_code_matches_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        let ranges = view.ranges_for_postal_code("1234ab");
+        assert!(!ranges.is_empty());
+        assert!(ranges.iter().any(|range| {
+            range.public_space == "Abel Eppensstraat"
+                && range.locality == "Hoogerheide"
+                && (range.start..=range.start + u32::from(range.length) * u32::from(range.step))
+                    .contains(&56)
+        }));
+        assert_eq!(decoded.ranges_for_postal_code("1234AB"), ranges);
+
+        assert!(view.ranges_for_postal_code("9999ZZ").is_empty());
+        assert!(decoded.ranges_for_postal_code("12345A").is_empty());
+    }
+
+    #[test]
+    fn has_postal_code_matches_both_variants() {
+        let [decoded, view] = fixture_handles();
+
+        for database in [&decoded, &view] {
+            assert!(database.has_postal_code("1234ab"));
+            assert!(database.lookup("1234AB", 999).is_none());
+            assert!(!database.has_postal_code("9999ZZ"));
+            assert!(!database.has_postal_code("12345A"));
+        }
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn encode_round_trips_through_zstd() {
+        let db = fixture_db();
+
+        let path = TempPath::new("bag.bin");
+        db.encode(&path).unwrap();
+        let encoded = std::fs::read(&path).unwrap();
+        let loaded = DatabaseHandle::load_from_path(&path);
+
+        assert!(encoded.starts_with(&util::ZSTD_MAGIC));
+        assert!(encoded.len() < fixture_bytes().len());
+        let DatabaseHandle::Decoded(loaded) = loaded.unwrap() else {
+            panic!("compressed files are decoded");
+        };
+        verify_test_db(&loaded);
+        assert_eq!(loaded, *db);
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn compression_level_is_configurable() {
+        let db = fixture_db();
+
+        let path = TempPath::new("bag.bin");
+        db.encode_with_level(&path, 1).unwrap();
+        let fast = std::fs::read(&path).unwrap();
+        let loaded = DatabaseHandle::load_from_path(&path);
+        db.encode(&path).unwrap();
+        let small = std::fs::read(&path).unwrap();
+
+        assert!(fast.len() >= small.len());
+        assert_eq!(
+            loaded.unwrap().lookup("1234AB", 56),
+            Some(("Abel Eppensstraat", "Hoogerheide"))
+        );
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn range_points_round_trip_through_both_variants() {
+        let mut db = fixture_db().clone();
+        // The fixture is built without Verblijfsobject data.
+        assert!(db.range_points.is_empty());
+        assert_eq!(db.lookup_detailed("1234AB", 56).unwrap().point, None);
+
+        let dam = RdPoint {
+            x: 1_213_180,
+            y: 4_873_620,
+        };
+        db.range_points = vec![RdPoint::MISSING, dam];
+
+        let mut bytes = Vec::new();
+        db.write_database(&mut bytes, 2, 2, 2).unwrap();
+        let decoded = Database::from_reader(&bytes[..]).unwrap();
+        let view = DatabaseView::from_bytes(bytes.leak()).unwrap();
+
+        assert_eq!(decoded.range_points, db.range_points);
+        for house_number in [1, 56] {
+            assert_eq!(
+                view.lookup_detailed("1234AB", house_number).unwrap().point,
+                decoded
+                    .lookup_detailed("1234AB", house_number)
+                    .unwrap()
+                    .point
+            );
+        }
+        let points = [1, 56].map(|n| decoded.lookup_detailed("1234AB", n).unwrap().point);
+        assert!(points.contains(&None));
+        assert!(points.contains(&Some(dam)));
+
+        let (lat, lon) = dam.to_wgs84();
+        assert!((lat - 52.3731).abs() < 1e-3 && (lon - 4.8926).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "create")]
+    #[test]
+    fn unsorted_ranges_are_rejected() {
+        let mut db = fixture_db().clone();
+        assert!(db.ranges.len() >= 2);
+        db.ranges[0].postal_code = encode_pc(b"9999ZZ");
+
+        let mut bytes = Vec::new();
+        let (localities, public_spaces, ranges) = (
+            db.localities.len() as u32,
+            db.public_spaces.len() as u32,
+            db.ranges.len() as u32,
+        );
+        db.write_database(&mut bytes, localities, public_spaces, ranges)
+            .unwrap();
+
+        assert!(matches!(
+            Database::from_reader(&bytes[..]),
+            Err(DatabaseError::InvalidLayout)
+        ));
+        assert!(matches!(
+            DatabaseView::from_bytes(bytes.leak()),
+            Err(DatabaseError::InvalidLayout)
+        ));
+    }
 }
@@ -0,0 +1,61 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufWriter, Write},
+};
+
+use crate::DatabaseHandle;
+
+impl DatabaseHandle<'_> {
+    /// Write every address as one JSON object per line, with the `/lookup`
+    /// field names: `{"n":56,"pc":"1234AB","pr":"Street","wp":"Locality"}`.
+    ///
+    /// Addresses are written as the ranges are expanded, so the output is
+    /// never held in memory. House letters and additions are not included.
+    pub fn export_ndjson(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        for (postal_code, range) in self.ranges() {
+            let public_space = json_string(range.public_space);
+            let locality = json_string(range.locality);
+            for house_number in range.house_numbers() {
+                writeln!(
+                    writer,
+                    "{{\"n\":{house_number},\"pc\":\"{postal_code}\",\"pr\":{public_space},\"wp\":{locality}}}"
+                )?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+/// Quote and escape `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(json_string("Kerkstraat"), "\"Kerkstraat\"");
+        assert_eq!(json_string("De \"Kaai\"\\"), "\"De \\\"Kaai\\\"\\\\\"");
+        assert_eq!(json_string("a\u{1}"), "\"a\\u0001\"");
+    }
+}
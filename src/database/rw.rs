@@ -4,12 +4,16 @@ use super::error::DatabaseError;
 
 pub(crate) fn read_u32_reader<R: Read>(reader: &mut R) -> Result<u32, DatabaseError> {
     let mut buf = [0u8; 4];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|_| DatabaseError::DecompressionFailed)?;
+    reader.read_exact(&mut buf)?;
     Ok(u32::from_le_bytes(buf))
 }
 
+pub(crate) fn read_u64_reader<R: Read>(reader: &mut R) -> Result<u64, DatabaseError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
 pub(crate) fn read_u32_bytes(bytes: &[u8], offset: usize) -> Option<u32> {
     let slice = bytes.get(offset..offset + 4)?;
     Some(u32::from_le_bytes(slice.try_into().ok()?))
@@ -24,30 +28,58 @@ pub(crate) fn read_u8_bytes(bytes: &[u8], offset: usize) -> Option<u8> {
     bytes.get(offset).copied()
 }
 
+/// Reader that keeps a CRC32 of all bytes read through it.
+#[cfg(feature = "compressed_database")]
+pub(crate) struct ChecksumReader<R> {
+    inner: R,
+    hasher: crc32fast::Hasher,
+}
+
+#[cfg(feature = "compressed_database")]
+impl<R: Read> ChecksumReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    /// Read the rest of the input and compare its checksum to `expected`.
+    pub(crate) fn verify(mut self, expected: u32) -> Result<(), DatabaseError> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        if self.hasher.finalize() != expected {
+            return Err(DatabaseError::ChecksumMismatch);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(feature = "compressed_database")]
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
 pub(crate) fn read_u16_reader<R: Read>(reader: &mut R) -> Result<u16, DatabaseError> {
     let mut buf = [0u8; 2];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|_| DatabaseError::DecompressionFailed)?;
+    reader.read_exact(&mut buf)?;
     Ok(u16::from_le_bytes(buf))
 }
 
 #[cfg(feature = "compressed_database")]
 pub(crate) fn read_u8_reader<R: Read>(reader: &mut R) -> Result<u8, DatabaseError> {
     let mut buf = [0u8; 1];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|_| DatabaseError::DecompressionFailed)?;
+    reader.read_exact(&mut buf)?;
     Ok(buf[0])
 }
 
 #[cfg(feature = "compressed_database")]
 pub(crate) fn read_bytes<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, DatabaseError> {
     let mut buf = vec![0u8; len];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|_| DatabaseError::DecompressionFailed)?;
+    reader.read_exact(&mut buf)?;
     Ok(buf)
 }
 
@@ -1,7 +1,15 @@
 use std::collections::HashMap;
 
-pub(crate) const DATABASE_MAGIC: [u8; 4] = *b"BAG4";
-pub(crate) const DATABASE_HEADER_SIZE: usize = 84;
+pub(crate) const DATABASE_MAGIC: [u8; 4] = *b"BAG6";
+/// Layout version written after the magic; bump it whenever the layout changes.
+pub(crate) const DATABASE_FORMAT_VERSION: u16 = 3;
+pub(crate) const DATABASE_HEADER_SIZE: usize = 176;
+/// Previous layout version, identical except for `u32` section offsets in
+/// the header. Still read so existing files keep working.
+pub(crate) const DATABASE_FORMAT_VERSION_U32_OFFSETS: u16 = 2;
+pub(crate) const DATABASE_HEADER_SIZE_U32_OFFSETS: usize = 108;
+/// Leading bytes of a zstd frame, used to tell compressed databases apart.
+pub(crate) const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
 
 pub(crate) struct UniqueFlags {
     pub(crate) locality_unique: Vec<bool>,
@@ -90,6 +98,10 @@ pub(crate) fn compute_unique_flags(
 }
 
 /// Encode a 6-char postal code into a compact sortable integer.
+///
+/// `s` must be four ASCII digits followed by two uppercase ASCII letters
+/// (e.g. `b"1234AB"`); other input panics or encodes to garbage. Use
+/// [`try_encode_pc`] for input that has not been validated.
 pub fn encode_pc(s: &[u8]) -> u32 {
     let digits = (s[0] - b'0') as u32 * 1000
         + (s[1] - b'0') as u32 * 100
@@ -102,6 +114,101 @@ pub fn encode_pc(s: &[u8]) -> u32 {
     (digits << 18) | (l0 << 13) | (l1 << 8)
 }
 
+/// Encode a postal code like [`encode_pc`], returning `None` unless `s` is
+/// exactly four ASCII digits followed by two uppercase ASCII letters.
+pub fn try_encode_pc(s: &[u8]) -> Option<u32> {
+    match s {
+        [d0, d1, d2, d3, l0, l1]
+            if [d0, d1, d2, d3].iter().all(|d| d.is_ascii_digit())
+                && l0.is_ascii_uppercase()
+                && l1.is_ascii_uppercase() =>
+        {
+            Some(encode_pc(s))
+        }
+        _ => None,
+    }
+}
+
+/// The lowest and highest encoded postal code starting with `prefix`: up to
+/// four digits, then up to two letters in either case. Returns `None` for an
+/// empty prefix or anything else.
+///
+/// Digits are the most significant part of the encoding, so every postal
+/// code with the prefix lies between the two.
+pub(crate) fn postal_code_prefix_bounds(prefix: &str) -> Option<(u32, u32)> {
+    let prefix = prefix.as_bytes();
+    if prefix.is_empty() || prefix.len() > 6 {
+        return None;
+    }
+
+    let mut low = *b"0000AA";
+    let mut high = *b"9999ZZ";
+    for (position, &byte) in prefix.iter().enumerate() {
+        let byte = byte.to_ascii_uppercase();
+        let valid = if position < 4 {
+            byte.is_ascii_digit()
+        } else {
+            byte.is_ascii_uppercase()
+        };
+        if !valid {
+            return None;
+        }
+        low[position] = byte;
+        high[position] = byte;
+    }
+
+    Some((encode_pc(&low), encode_pc(&high)))
+}
+
+/// Decode a postal code produced by [`encode_pc`] back into its 6 ASCII bytes.
+///
+/// Values that [`encode_pc`] cannot produce decode to unspecified (but ASCII)
+/// bytes rather than panicking.
+pub fn decode_pc(code: u32) -> [u8; 6] {
+    let digits = code >> 18;
+    let l0 = ((code >> 13) & 0x1f) as u8;
+    let l1 = ((code >> 8) & 0x1f) as u8;
+
+    [
+        b'0' + (digits / 1000 % 10) as u8,
+        b'0' + (digits / 100 % 10) as u8,
+        b'0' + (digits / 10 % 10) as u8,
+        b'0' + (digits % 10) as u8,
+        b'A' + l0,
+        b'A' + l1,
+    ]
+}
+
+/// Encode a house letter and house number addition into their fixed-size
+/// [`HouseNumberSuffix`](super::HouseNumberSuffix) form.
+///
+/// The letter must be a single ASCII letter and the addition one to four
+/// ASCII alphanumerics; empty values count as absent. Returns `None` for
+/// anything else.
+pub(crate) fn encode_house_number_suffix(
+    letter: Option<&str>,
+    addition: Option<&str>,
+) -> Option<(u8, [u8; 4])> {
+    let letter = match letter.filter(|value| !value.is_empty()) {
+        None => 0,
+        Some(value) => match value.as_bytes() {
+            [b] if b.is_ascii_alphabetic() => *b,
+            _ => return None,
+        },
+    };
+
+    let mut encoded = [0u8; 4];
+    if let Some(value) = addition.filter(|value| !value.is_empty()) {
+        let bytes = value.as_bytes();
+        if bytes.len() > encoded.len() || !bytes.iter().all(u8::is_ascii_alphanumeric) {
+            return None;
+        }
+        encoded[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    Some((letter, encoded))
+}
+
 pub(crate) fn normalize_postalcode(postalcode: &str) -> Option<[u8; 6]> {
     let bytes = postalcode.as_bytes();
     if bytes.len() != 6 {
@@ -135,7 +242,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::encode_pc;
+    use super::{
+        decode_pc, encode_house_number_suffix, encode_pc, postal_code_prefix_bounds, try_encode_pc,
+    };
 
     #[test]
     fn encode_pc_basic() {
@@ -159,4 +268,67 @@ mod tests {
         let letters = (16u32 << 13) | (23u32 << 8);
         assert_eq!(encoded, digits | letters);
     }
+
+    #[test]
+    fn postal_code_prefix_bounds_span_the_prefix() {
+        let (low, high) = postal_code_prefix_bounds("12").unwrap();
+        assert_eq!(low, encode_pc(b"1200AA"));
+        assert_eq!(high, encode_pc(b"1299ZZ"));
+        assert!((low..=high).contains(&encode_pc(b"1234AB")));
+        assert!(!(low..=high).contains(&encode_pc(b"1300AA")));
+
+        assert_eq!(
+            postal_code_prefix_bounds("1234a"),
+            Some((encode_pc(b"1234AA"), encode_pc(b"1234AZ")))
+        );
+        assert_eq!(
+            postal_code_prefix_bounds("1234AB"),
+            Some((encode_pc(b"1234AB"), encode_pc(b"1234AB")))
+        );
+
+        for invalid in ["", "A", "12A", "123B4", "1234ABC", "1234A1"] {
+            assert_eq!(postal_code_prefix_bounds(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn try_encode_pc_validates_input() {
+        assert_eq!(try_encode_pc(b"1234AB"), Some(encode_pc(b"1234AB")));
+        assert_eq!(try_encode_pc(b"9999ZZ"), Some(encode_pc(b"9999ZZ")));
+        assert_eq!(try_encode_pc(b"1234ab"), None);
+        assert_eq!(try_encode_pc(b"12A4AB"), None);
+        assert_eq!(try_encode_pc(b"1234A1"), None);
+        assert_eq!(try_encode_pc(b"1234A"), None);
+        assert_eq!(try_encode_pc(b"1234ABC"), None);
+        assert_eq!(try_encode_pc(b""), None);
+    }
+
+    #[test]
+    fn decode_pc_round_trips() {
+        for pc in [
+            b"0000AA", b"9999ZZ", b"1234AB", b"9876QX", b"1000ZA", b"0001AZ", b"5050MN",
+        ] {
+            assert_eq!(&decode_pc(encode_pc(pc)), pc);
+        }
+    }
+
+    #[test]
+    fn encode_house_number_suffix_pads_addition() {
+        assert_eq!(
+            encode_house_number_suffix(Some("A"), Some("bis")),
+            Some((b'A', *b"bis\0"))
+        );
+        assert_eq!(
+            encode_house_number_suffix(None, Some("")),
+            Some((0, [0; 4]))
+        );
+    }
+
+    #[test]
+    fn encode_house_number_suffix_rejects_invalid_values() {
+        assert_eq!(encode_house_number_suffix(Some("AB"), None), None);
+        assert_eq!(encode_house_number_suffix(Some("1"), None), None);
+        assert_eq!(encode_house_number_suffix(None, Some("12345")), None);
+        assert_eq!(encode_house_number_suffix(None, Some("1-2")), None);
+    }
 }
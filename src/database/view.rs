@@ -1,12 +1,17 @@
+use std::ops::Deref;
+
 use crate::database::{DatabaseView, layout::Header};
 
 use super::{
+    HouseNumberSuffix, RdPoint,
     error::DatabaseError,
     layout::{OffsetsBytesIter, validate_offsets_iter},
     rw::{read_u8_bytes, read_u16_bytes, read_u32_bytes},
 };
 
 const RANGE_RECORD_SIZE: usize = 17;
+const HOUSE_NUMBER_SUFFIX_RECORD_SIZE: usize = 13;
+const RANGE_POINT_RECORD_SIZE: usize = 8;
 
 pub(crate) struct RangeRef {
     pub(crate) start: u32,
@@ -16,9 +21,52 @@ pub(crate) struct RangeRef {
     pub(crate) step: u8,
 }
 
-impl DatabaseView {
-    pub fn from_bytes(bytes: &'static [u8]) -> Result<Self, DatabaseError> {
+/// The bytes a [`DatabaseView`] reads from.
+pub(crate) enum ViewBytes<'a> {
+    /// Borrowed bytes, e.g. the embedded database or a buffer read at runtime.
+    Borrowed(&'a [u8]),
+    /// Bytes the view owns, e.g. a decompressed or read database file.
+    Owned(Box<[u8]>),
+    /// A database file mapped into memory.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for ViewBytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ViewBytes::Borrowed(bytes) => bytes,
+            ViewBytes::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            ViewBytes::Mapped(map) => map,
+        }
+    }
+}
+
+impl<'a> DatabaseView<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DatabaseError> {
+        Self::from_storage(ViewBytes::Borrowed(bytes))
+    }
+
+    /// Create a view that owns the database bytes.
+    pub(crate) fn from_owned(bytes: Vec<u8>) -> Result<Self, DatabaseError> {
+        Self::from_storage(ViewBytes::Owned(bytes.into_boxed_slice()))
+    }
+
+    /// Create a view over a memory-mapped database file.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn from_mmap(map: memmap2::Mmap) -> Result<Self, DatabaseError> {
+        Self::from_storage(ViewBytes::Mapped(map))
+    }
+
+    fn from_storage(storage: ViewBytes<'a>) -> Result<Self, DatabaseError> {
+        let bytes: &[u8] = &storage;
         let header = Header::from_bytes(bytes)?;
+        if crc32fast::hash(&bytes[header.size..]) != header.checksum {
+            return Err(DatabaseError::ChecksumMismatch);
+        }
 
         let locality_offsets_len = header.locality_offsets_len()?;
         let locality_offsets_end = header
@@ -93,6 +141,14 @@ impl DatabaseView {
         if ranges_end > bytes.len() {
             return Err(DatabaseError::InvalidLayout);
         }
+        // Lookups binary search the ranges, so they must be sorted by postal code.
+        let sorted = bytes[header.ranges_offset..ranges_end]
+            .chunks_exact(RANGE_RECORD_SIZE)
+            .map(|record| read_u32_bytes(record, 0))
+            .is_sorted();
+        if !sorted {
+            return Err(DatabaseError::InvalidLayout);
+        }
 
         // Validate municipality string table
         let municipality_offsets_len = header.municipality_offsets_len()?;
@@ -223,8 +279,42 @@ impl DatabaseView {
             return Err(DatabaseError::InvalidLayout);
         }
 
+        // Validate house letter / addition records
+        let expected_suffixes_offset = header.expected_house_number_suffixes_offset()?;
+        if header.house_number_suffixes_offset != expected_suffixes_offset {
+            return Err(DatabaseError::InvalidLayout);
+        }
+        let suffixes_end = header
+            .house_number_suffixes_offset
+            .checked_add(
+                (header.house_number_suffix_count as usize)
+                    .checked_mul(HOUSE_NUMBER_SUFFIX_RECORD_SIZE)
+                    .ok_or(DatabaseError::InvalidLayout)?,
+            )
+            .ok_or(DatabaseError::InvalidLayout)?;
+        if suffixes_end > bytes.len() {
+            return Err(DatabaseError::InvalidLayout);
+        }
+
+        // Validate range positions
+        let expected_range_points_offset = header.expected_range_points_offset()?;
+        if header.range_points_offset != expected_range_points_offset {
+            return Err(DatabaseError::InvalidLayout);
+        }
+        let range_points_end = header
+            .range_points_offset
+            .checked_add(
+                (header.range_point_count as usize)
+                    .checked_mul(RANGE_POINT_RECORD_SIZE)
+                    .ok_or(DatabaseError::InvalidLayout)?,
+            )
+            .ok_or(DatabaseError::InvalidLayout)?;
+        if range_points_end > bytes.len() {
+            return Err(DatabaseError::InvalidLayout);
+        }
+
         Ok(Self {
-            bytes,
+            bytes: storage,
             locality_count: header.locality_count,
             public_space_count: header.public_space_count,
             range_count: header.range_count,
@@ -249,6 +339,16 @@ impl DatabaseView {
             locality_codes_offset: header.locality_codes_offset,
             locality_had_suffix_offset: header.locality_had_suffix_offset,
             municipality_had_suffix_offset: header.municipality_had_suffix_offset,
+            house_number_suffix_count: header.house_number_suffix_count,
+            house_number_suffixes_offset: header.house_number_suffixes_offset,
+            range_point_count: header.range_point_count,
+            range_points_offset: header.range_points_offset,
+            checksum: header.checksum,
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
         })
     }
 
@@ -258,17 +358,17 @@ impl DatabaseView {
 
     pub(crate) fn range_postal_code(&self, index: usize) -> Option<u32> {
         let base = self.range_offset(index)?;
-        read_u32_bytes(self.bytes, base)
+        read_u32_bytes(&self.bytes, base)
     }
 
     pub(crate) fn range_at(&self, index: usize) -> Option<RangeRef> {
         let base = self.range_offset(index)?;
         Some(RangeRef {
-            start: read_u32_bytes(self.bytes, base + 4)?,
-            length: read_u16_bytes(self.bytes, base + 8)?,
-            public_space_index: read_u32_bytes(self.bytes, base + 10)?,
-            locality_index: read_u16_bytes(self.bytes, base + 14)?,
-            step: read_u8_bytes(self.bytes, base + 16)?,
+            start: read_u32_bytes(&self.bytes, base + 4)?,
+            length: read_u16_bytes(&self.bytes, base + 8)?,
+            public_space_index: read_u32_bytes(&self.bytes, base + 10)?,
+            locality_index: read_u16_bytes(&self.bytes, base + 14)?,
+            step: read_u8_bytes(&self.bytes, base + 16)?,
         })
     }
 
@@ -282,7 +382,40 @@ impl DatabaseView {
         }
     }
 
-    pub(crate) fn locality_name(&self, index: u16) -> Option<&'static str> {
+    /// Position of the range at `index`, if the database has geometry.
+    pub(crate) fn range_point(&self, index: usize) -> Option<RdPoint> {
+        if index >= self.range_point_count as usize {
+            return None;
+        }
+        let base = self
+            .range_points_offset
+            .checked_add(index.checked_mul(RANGE_POINT_RECORD_SIZE)?)?;
+        Some(RdPoint {
+            x: read_u32_bytes(&self.bytes, base)?,
+            y: read_u32_bytes(&self.bytes, base + 4)?,
+        })
+    }
+
+    pub(crate) fn house_number_suffix_count(&self) -> usize {
+        self.house_number_suffix_count as usize
+    }
+
+    pub(crate) fn house_number_suffix_at(&self, index: usize) -> Option<HouseNumberSuffix> {
+        if index >= self.house_number_suffix_count as usize {
+            return None;
+        }
+        let base = self
+            .house_number_suffixes_offset
+            .checked_add(index.checked_mul(HOUSE_NUMBER_SUFFIX_RECORD_SIZE)?)?;
+        Some(HouseNumberSuffix {
+            postal_code: read_u32_bytes(&self.bytes, base)?,
+            house_number: read_u32_bytes(&self.bytes, base + 4)?,
+            letter: read_u8_bytes(&self.bytes, base + 8)?,
+            addition: self.bytes.get(base + 9..base + 13)?.try_into().ok()?,
+        })
+    }
+
+    pub(crate) fn locality_name(&self, index: u16) -> Option<&str> {
         self.name_at(
             self.locality_offsets_offset,
             self.locality_data_offset,
@@ -292,7 +425,7 @@ impl DatabaseView {
         )
     }
 
-    pub(crate) fn public_space_name(&self, index: u32) -> Option<&'static str> {
+    pub(crate) fn public_space_name(&self, index: u32) -> Option<&str> {
         self.name_at(
             self.public_space_offsets_offset,
             self.public_space_data_offset,
@@ -302,7 +435,7 @@ impl DatabaseView {
         )
     }
 
-    pub(crate) fn municipality_name(&self, index: u16) -> Option<&'static str> {
+    pub(crate) fn municipality_name(&self, index: u16) -> Option<&str> {
         self.name_at(
             self.municipality_offsets_offset,
             self.municipality_data_offset,
@@ -312,7 +445,7 @@ impl DatabaseView {
         )
     }
 
-    pub(crate) fn province_name(&self, index: u8) -> Option<&'static str> {
+    pub(crate) fn province_name(&self, index: u8) -> Option<&str> {
         self.name_at(
             self.province_offsets_offset,
             self.province_data_offset,
@@ -327,7 +460,7 @@ impl DatabaseView {
             return None;
         }
         read_u16_bytes(
-            self.bytes,
+            &self.bytes,
             self.locality_municipality_map_offset + locality_index as usize * 2,
         )
     }
@@ -337,7 +470,7 @@ impl DatabaseView {
             return None;
         }
         read_u8_bytes(
-            self.bytes,
+            &self.bytes,
             self.municipality_province_map_offset + municipality_index as usize,
         )
     }
@@ -347,7 +480,7 @@ impl DatabaseView {
             return None;
         }
         read_u16_bytes(
-            self.bytes,
+            &self.bytes,
             self.municipality_codes_offset + municipality_index as usize * 2,
         )
     }
@@ -357,7 +490,7 @@ impl DatabaseView {
             return None;
         }
         read_u16_bytes(
-            self.bytes,
+            &self.bytes,
             self.locality_codes_offset + locality_index as usize * 2,
         )
     }
@@ -389,7 +522,7 @@ impl DatabaseView {
     }
 
     /// Collect locality names and their parent municipality indexes (u16::MAX = unknown).
-    fn collect_locality_names_and_parents(&self) -> (Vec<&'static str>, Vec<u16>) {
+    fn collect_locality_names_and_parents(&self) -> (Vec<&str>, Vec<u16>) {
         let mut names = Vec::with_capacity(self.locality_count as usize);
         let mut parents = Vec::with_capacity(self.locality_count as usize);
         for i in 0..self.locality_count {
@@ -404,7 +537,7 @@ impl DatabaseView {
         (names, parents)
     }
 
-    fn collect_municipality_names(&self) -> Vec<&'static str> {
+    fn collect_municipality_names(&self) -> Vec<&str> {
         let mut names = Vec::with_capacity(self.municipality_count as usize);
         for i in 0..self.municipality_count {
             names.push(self.municipality_name(i as u16).unwrap_or(""));
@@ -412,7 +545,7 @@ impl DatabaseView {
         names
     }
 
-    pub(crate) fn locality_details(&self) -> Vec<super::LocalityDetail<'static>> {
+    pub(crate) fn locality_details(&self) -> Vec<super::LocalityDetail<'_>> {
         let (locality_names, parents) = self.collect_locality_names_and_parents();
         let muni_names = self.collect_municipality_names();
         let loc_had_suffix = self.collect_locality_had_suffix();
@@ -449,6 +582,7 @@ impl DatabaseView {
                 .unwrap_or(false);
             let had_suffix = loc_had_suffix.get(i as usize).copied().unwrap_or(false);
             result.push(super::LocalityDetail {
+                index: loc_idx,
                 name,
                 code: wp_code,
                 municipality: m_name,
@@ -461,7 +595,7 @@ impl DatabaseView {
         result
     }
 
-    pub(crate) fn municipality_details(&self) -> Vec<super::MunicipalityDetail<'static>> {
+    pub(crate) fn municipality_details(&self) -> Vec<super::MunicipalityDetail<'_>> {
         let (locality_names, parents) = self.collect_locality_names_and_parents();
         let muni_names = self.collect_municipality_names();
         let loc_had_suffix = self.collect_locality_had_suffix();
@@ -507,13 +641,13 @@ impl DatabaseView {
         data_end: usize,
         index: u32,
         count: u32,
-    ) -> Option<&'static str> {
+    ) -> Option<&str> {
         if index >= count {
             return None;
         }
 
-        let start = read_u32_bytes(self.bytes, offsets_offset + index as usize * 4)? as usize;
-        let end = read_u32_bytes(self.bytes, offsets_offset + (index as usize + 1) * 4)? as usize;
+        let start = read_u32_bytes(&self.bytes, offsets_offset + index as usize * 4)? as usize;
+        let end = read_u32_bytes(&self.bytes, offsets_offset + (index as usize + 1) * 4)? as usize;
         if start > end {
             return None;
         }
@@ -0,0 +1,345 @@
+//! Fetching the source data over HTTP.
+//!
+//! With the `download` feature requests are made in process, and large
+//! downloads are retried and report their progress. Without it `curl` must be
+//! on the `PATH`.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+#[cfg(feature = "download")]
+use std::{
+    fs::File,
+    io::{BufWriter, ErrorKind, Read, Write},
+    time::Duration,
+};
+
+#[cfg(feature = "download")]
+use crate::log_with_elapsed;
+
+/// Attempts at a download before giving up.
+#[cfg(feature = "download")]
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, growing with every further attempt.
+#[cfg(feature = "download")]
+const RETRY_DELAY: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+
+/// Time allowed for connecting to a server.
+#[cfg(feature = "download")]
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time allowed for the response headers to arrive.
+#[cfg(feature = "download")]
+const RESPONSE_TIMEOUT: Duration = if cfg!(test) {
+    Duration::from_millis(200)
+} else {
+    Duration::from_secs(60)
+};
+
+/// Time allowed for receiving a whole response body. The BAG extract is
+/// several gigabytes, so this only stops downloads that stall.
+#[cfg(feature = "download")]
+const BODY_TIMEOUT: Duration = if cfg!(test) {
+    Duration::from_millis(200)
+} else {
+    Duration::from_secs(4 * 60 * 60)
+};
+
+/// Largest response [`fetch`] accepts; the reference data is far smaller.
+#[cfg(feature = "download")]
+const MAX_FETCH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Bytes between progress messages when the download size is unknown.
+#[cfg(feature = "download")]
+const PROGRESS_STEP_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Fetch the body of `url`, failing on an error status.
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    #[cfg(feature = "download")]
+    {
+        let body = agent()
+            .get(url)
+            .call()
+            .map_err(timeout_error)?
+            .body_mut()
+            .with_config()
+            .limit(MAX_FETCH_BYTES)
+            .read_to_vec()
+            .map_err(timeout_error)?;
+        Ok(body)
+    }
+    #[cfg(not(feature = "download"))]
+    {
+        let output = std::process::Command::new("curl")
+            .arg("-sLf")
+            .arg(url)
+            .output()?;
+        if !output.status.success() {
+            return Err(format!("curl exited with {}", output.status).into());
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Download `url` to `path`, logging the progress when `progress` is set.
+///
+/// The data is written next to `path` first and only moved there once the
+/// download is complete, so an interrupted download never leaves a partial
+/// file at `path`. A failed download is retried from the start a few times.
+pub(crate) fn download_to_file(
+    url: &str,
+    path: &Path,
+    progress: bool,
+    start: Instant,
+) -> Result<(), Box<dyn Error>> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    download_to(url, &partial, progress, start)?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+fn download_to(
+    url: &str,
+    path: &Path,
+    progress: bool,
+    start: Instant,
+) -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "download")]
+    {
+        let mut attempt = 1;
+        loop {
+            match download_attempt(url, path, progress, start) {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < DOWNLOAD_ATTEMPTS => {
+                    log_with_elapsed(
+                        start,
+                        &format!(
+                            "Download failed ({err}), retrying ({attempt}/{DOWNLOAD_ATTEMPTS})..."
+                        ),
+                    );
+                    std::thread::sleep(RETRY_DELAY * attempt);
+                    attempt += 1;
+                }
+                Err(err) => return Err(format!("Failed to download file from {url}: {err}").into()),
+            }
+        }
+    }
+    #[cfg(not(feature = "download"))]
+    {
+        // curl shows its own progress.
+        let _ = (progress, start);
+        let status = std::process::Command::new("curl")
+            .arg("-Lf")
+            .arg("-o")
+            .arg(path)
+            .arg(url)
+            .status()?;
+
+        if !status.success() {
+            return Err(format!("Failed to download file from {url}").into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "download")]
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+        .timeout_recv_body(Some(BODY_TIMEOUT))
+        .build()
+        .into()
+}
+
+/// `err`, with timeouts while receiving the response named as such.
+#[cfg(feature = "download")]
+fn timeout_error(err: ureq::Error) -> Box<dyn Error> {
+    match err {
+        ureq::Error::Timeout(ureq::Timeout::RecvResponse) => format!(
+            "timed out waiting {}s for the response",
+            RESPONSE_TIMEOUT.as_secs_f32()
+        )
+        .into(),
+        ureq::Error::Timeout(ureq::Timeout::RecvBody) => format!(
+            "timed out receiving the response body for {}s",
+            BODY_TIMEOUT.as_secs_f32()
+        )
+        .into(),
+        err => err.into(),
+    }
+}
+
+/// Stream `url` to `path` once.
+#[cfg(feature = "download")]
+fn download_attempt(
+    url: &str,
+    path: &Path,
+    progress: bool,
+    start: Instant,
+) -> Result<(), Box<dyn Error>> {
+    let mut response = agent().get(url).call().map_err(timeout_error)?;
+    let total = response.body().content_length();
+    let step = total.map_or(PROGRESS_STEP_BYTES, |total| (total / 10).max(1));
+    let mut reader = response.body_mut().as_reader();
+    let mut file = BufWriter::new(File::create(path)?);
+
+    let mut buffer = vec![0; 64 * 1024];
+    let mut written = 0u64;
+    let mut next_report = step;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(timeout_error(err.into())),
+        };
+        file.write_all(&buffer[..read])?;
+        written += read as u64;
+
+        if progress && written >= next_report {
+            log_with_elapsed(start, &progress_message(written, total));
+            next_report = written + step;
+        }
+    }
+    file.flush()?;
+
+    // A connection closed early can look like the end of the body.
+    if let Some(total) = total
+        && written != total
+    {
+        return Err(format!("received {written} of {total} bytes").into());
+    }
+
+    Ok(())
+}
+
+/// Progress of a download of `total` bytes, when known.
+#[cfg(feature = "download")]
+fn progress_message(written: u64, total: Option<u64>) -> String {
+    const MB: u64 = 1024 * 1024;
+    match total {
+        Some(total) if total > 0 => format!(
+            "Downloaded {} of {} MB ({}%)",
+            written / MB,
+            total / MB,
+            written * 100 / total
+        ),
+        _ => format!("Downloaded {} MB", written / MB),
+    }
+}
+
+#[cfg(all(test, feature = "download"))]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        time::Instant,
+    };
+
+    use super::{download_to_file, progress_message};
+    use crate::test_support::TempPath;
+
+    #[test]
+    fn failed_downloads_are_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/bag.zip", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            // The first connection is dropped before answering.
+            drop(listener.accept().unwrap());
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nzipdata",
+                )
+                .unwrap();
+        });
+
+        let path = TempPath::new("bag.zip");
+        download_to_file(&url, &path, true, Instant::now()).unwrap();
+        server.join().unwrap();
+
+        let contents = std::fs::read(&path).unwrap();
+        assert_eq!(contents, b"zipdata");
+        assert!(!path.with_extension("zip.part").exists());
+    }
+
+    #[test]
+    fn short_downloads_are_not_kept() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/bag.zip", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            // Every attempt gets fewer bytes than announced.
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\nzip",
+                    )
+                    .unwrap();
+            }
+        });
+
+        let path = TempPath::new("bag.zip");
+        let result = download_to_file(&url, &path, false, Instant::now());
+        server.join().unwrap();
+
+        assert!(result.is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn stalled_downloads_time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/bag.zip", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            // Every attempt gets the headers and then nothing, until the
+            // client gives up and closes the connection.
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nzip")
+                    .unwrap();
+                let _ = stream.read(&mut request);
+            }
+        });
+
+        let path = TempPath::new("bag.zip");
+        let result = download_to_file(&url, &path, false, Instant::now());
+        server.join().unwrap();
+
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("timed out receiving the response body"),
+            "{err}"
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn progress_is_reported_in_megabytes() {
+        let mb = 1024 * 1024;
+        assert_eq!(
+            progress_message(300 * mb, Some(1200 * mb)),
+            "Downloaded 300 of 1200 MB (25%)"
+        );
+        assert_eq!(progress_message(300 * mb, None), "Downloaded 300 MB");
+    }
+}
@@ -0,0 +1,135 @@
+//! Conversion between Rijksdriehoek (RD, EPSG:28992) and WGS84 coordinates.
+//!
+//! Uses the polynomial approximation by Schreutelkamp and Strang van Hees,
+//! which is accurate to about a metre within the Netherlands. That is plenty
+//! for positions that are averaged over a range of house numbers anyway.
+
+/// RD coordinates of the Amersfoort reference point.
+const REF_X: f64 = 155_000.0;
+const REF_Y: f64 = 463_000.0;
+
+/// WGS84 latitude and longitude of the Amersfoort reference point.
+const REF_LAT: f64 = 52.15517440;
+const REF_LON: f64 = 5.38720621;
+
+/// `(p, q, K)` terms of the latitude polynomial, in arc seconds.
+const LAT_TERMS: [(i32, i32, f64); 11] = [
+    (0, 1, 3_235.653_89),
+    (2, 0, -32.582_97),
+    (0, 2, -0.247_50),
+    (2, 1, -0.849_78),
+    (0, 3, -0.065_50),
+    (2, 2, -0.017_09),
+    (1, 0, -0.007_38),
+    (4, 0, 0.005_30),
+    (2, 3, -0.000_39),
+    (4, 1, 0.000_33),
+    (1, 1, -0.000_12),
+];
+
+/// `(p, q, L)` terms of the longitude polynomial, in arc seconds.
+const LON_TERMS: [(i32, i32, f64); 12] = [
+    (1, 0, 5_260.529_16),
+    (1, 1, 105.946_84),
+    (1, 2, 2.456_56),
+    (3, 0, -0.818_85),
+    (1, 3, 0.055_94),
+    (3, 1, -0.056_07),
+    (0, 1, 0.011_99),
+    (3, 2, -0.002_56),
+    (1, 4, 0.001_28),
+    (0, 2, 0.000_22),
+    (2, 0, -0.000_22),
+    (5, 0, 0.000_26),
+];
+
+/// `(p, q, R)` terms of the x polynomial, in metres.
+const X_TERMS: [(i32, i32, f64); 9] = [
+    (0, 1, 190_094.945),
+    (1, 1, -11_832.228),
+    (2, 1, -114.221),
+    (0, 3, -32.391),
+    (1, 0, -0.705),
+    (3, 1, -2.340),
+    (1, 3, -0.608),
+    (0, 2, -0.008),
+    (2, 3, 0.148),
+];
+
+/// `(p, q, S)` terms of the y polynomial, in metres.
+const Y_TERMS: [(i32, i32, f64); 10] = [
+    (1, 0, 309_056.544),
+    (0, 2, 3_638.893),
+    (2, 0, 73.077),
+    (1, 2, -157.984),
+    (3, 0, 59.788),
+    (0, 1, 0.433),
+    (2, 2, -6.439),
+    (1, 1, -0.032),
+    (0, 4, 0.092),
+    (1, 4, -0.054),
+];
+
+/// Convert RD coordinates in metres to WGS84 `(latitude, longitude)` in
+/// degrees.
+pub fn rd_to_wgs84(x: f64, y: f64) -> (f64, f64) {
+    let dx = (x - REF_X) * 1e-5;
+    let dy = (y - REF_Y) * 1e-5;
+
+    let sum = |terms: &[(i32, i32, f64)]| -> f64 {
+        terms
+            .iter()
+            .map(|&(p, q, k)| k * dx.powi(p) * dy.powi(q))
+            .sum()
+    };
+
+    (
+        REF_LAT + sum(&LAT_TERMS) / 3600.0,
+        REF_LON + sum(&LON_TERMS) / 3600.0,
+    )
+}
+
+/// Convert WGS84 latitude and longitude in degrees to RD `(x, y)` in
+/// metres.
+pub fn wgs84_to_rd(lat: f64, lon: f64) -> (f64, f64) {
+    // Offsets from the reference point in units of 10 000 arc seconds.
+    let dlat = 0.36 * (lat - REF_LAT);
+    let dlon = 0.36 * (lon - REF_LON);
+
+    let sum = |terms: &[(i32, i32, f64)]| -> f64 {
+        terms
+            .iter()
+            .map(|&(p, q, r)| r * dlat.powi(p) * dlon.powi(q))
+            .sum()
+    };
+
+    (REF_X + sum(&X_TERMS), REF_Y + sum(&Y_TERMS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rd_to_wgs84, wgs84_to_rd};
+
+    #[test]
+    fn reference_point_maps_to_amersfoort() {
+        let (lat, lon) = rd_to_wgs84(155_000.0, 463_000.0);
+        assert!((lat - 52.15517440).abs() < 1e-9);
+        assert!((lon - 5.38720621).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dam_square_is_converted() {
+        // Dam, Amsterdam: RD (121 318, 487 362) is about 52.3731 N, 4.8926 E.
+        let (lat, lon) = rd_to_wgs84(121_318.0, 487_362.0);
+        assert!((lat - 52.3731).abs() < 1e-4, "lat {lat}");
+        assert!((lon - 4.8926).abs() < 1e-4, "lon {lon}");
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        let (lat, lon) = rd_to_wgs84(121_318.0, 487_362.0);
+        let (x, y) = wgs84_to_rd(lat, lon);
+        assert!((x - 121_318.0).abs() < 1.0, "x {x}");
+        assert!((y - 487_362.0).abs() < 1.0, "y {y}");
+    }
+}
@@ -1,5 +1,9 @@
 mod database;
 mod fryslan_aliases;
+mod geo;
+mod postal_codes;
+mod reverse;
+mod streets;
 mod suggest;
 
 #[cfg(feature = "webservice")]
@@ -14,32 +18,57 @@ mod transform;
 #[cfg(feature = "create")]
 mod create;
 
+#[cfg(feature = "create")]
+mod download;
+
 #[cfg(feature = "create")]
 mod parsing;
 
+#[cfg(feature = "create")]
+mod util;
+
+#[cfg(test)]
+mod test_support;
+
 pub use database::{
-    Database, DatabaseError, DatabaseHandle, LocalityDetail, MunicipalityDetail, NumberRange,
-    encode_pc,
+    AddressResult, Database, DatabaseError, DatabaseHandle, DatabaseStats, HouseNumberSuffix,
+    Localities, LocalityDetail, MunicipalityDetail, NumberRange, PublicSpaces, RangeInfo, RdPoint,
+    ReverseResult, decode_pc, encode_pc, try_encode_pc,
+};
+pub use geo::{rd_to_wgs84, wgs84_to_rd};
+pub use suggest::{
+    DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD, ScoringConfig, SuggestAlgorithm, Suggestion,
 };
-pub use suggest::{DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD};
 
 #[cfg(feature = "webservice")]
-pub use service::{serve, serve_with_shutdown};
+pub use service::{
+    Server, ServerBuilder, ServerConfig, serve, serve_many, serve_many_with_shutdown,
+    serve_with_config, serve_with_shutdown,
+};
+
+#[cfg(all(feature = "webservice", unix))]
+pub use service::{serve_unix, serve_unix_with_shutdown};
+
+#[cfg(feature = "tls")]
+pub use service::{serve_tls, serve_tls_with_shutdown};
 
 #[cfg(feature = "create")]
 pub use logging::log_with_elapsed;
 
 #[cfg(feature = "create")]
-pub use create::create_database;
+pub use create::{CreateOptions, create_database, create_database_with};
 
 #[cfg(feature = "create")]
-pub use parsing::{Address, Locality, PublicSpace};
+pub use parsing::{Address, AddressPoint, Locality, PublicSpace};
 
 #[cfg(feature = "create")]
-pub use parsing::MunicipalityRelation;
+pub use parsing::{
+    MunicipalityRelation, ParseOptions, ParseReport, ParsedData, ProgressEvent, SkipReason,
+};
 
 #[cfg(feature = "create")]
 pub use transform::{
-    LocalityMap, MunicipalityMap, encode_addresses, index_localities, index_municipalities,
-    index_public_spaces,
+    LocalityMap, MunicipalityMap, PublicSpaceMap, encode_addresses, encode_house_number_suffixes,
+    encode_range_points, index_localities, index_municipalities, index_public_spaces,
+    normalize_name,
 };
@@ -2,35 +2,46 @@
 // BAG catalog §7.4: https://www.kadaster.nl/zakelijk/registraties/basisregistraties/bag/catalogus-bag
 //
 // A Nummeraanduiding assigns a house number and postal code to an addressable
-// object via an OpenbareRuimte. Only currently valid records with status
-// "Naamgeving uitgegeven" are included.
+// object via an OpenbareRuimte. Only currently valid records whose latest
+// voorkomen has an accepted naming status (see `ParseOptions::naming_statuses`)
+// are included.
 
 use std::{collections::HashMap, io::BufRead};
 
 use quick_xml::{Reader, events::Event};
 
-use super::xml_utils::{
-    BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
-    VOORKOMEN_ID_TAG, VoorkomenState, read_simple_tag,
+use super::{
+    ParseOptions, ParseReport, SkipReason,
+    report::Outcome,
+    xml_utils::{
+        BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
+        VOORKOMEN_ID_TAG, Voorkomen, VoorkomenState, read_simple_tag,
+    },
 };
 
-const NUM_TAG: &[u8] = b"Objecten:Nummeraanduiding";
+pub(super) const NUM_TAG: &[u8] = b"Nummeraanduiding";
 // §7.4.1 identificatie - 16-digit national identifier
-const ID_TAG: &[u8] = b"Objecten:identificatie";
+const ID_TAG: &[u8] = b"identificatie";
 // §7.4.2 huisnummer - house number (1-99999)
-const HOUSE_NUMBER_TAG: &[u8] = b"Objecten:huisnummer";
+const HOUSE_NUMBER_TAG: &[u8] = b"huisnummer";
+// §7.4.3 huisletter - single letter following the house number (e.g. 12A)
+const HOUSE_LETTER_TAG: &[u8] = b"huisletter";
+// §7.4.4 huisnummertoevoeging - up to 4 alphanumerics after number and letter
+const HOUSE_NUMBER_ADDITION_TAG: &[u8] = b"huisnummertoevoeging";
 // §7.4.5 postcode - 6-character Dutch postal code (e.g. "1234AB")
-const POSTAL_CODE_TAG: &[u8] = b"Objecten:postcode";
+const POSTAL_CODE_TAG: &[u8] = b"postcode";
 // §7.4.8 ligtAan - reference to the OpenbareRuimte this address belongs to
-const PUBLIC_SPACE_REF_TAG: &[u8] = b"Objecten-ref:OpenbareRuimteRef";
+const PUBLIC_SPACE_REF_TAG: &[u8] = b"OpenbareRuimteRef";
 // §7.4.7 status - lifecycle status of the address designation
-const STATUS_TAG: &[u8] = b"Objecten:status";
-// Only include addresses where a name/number has been officially issued
-const ISSUED_STATUS: &str = "Naamgeving uitgegeven";
+const STATUS_TAG: &[u8] = b"status";
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Address {
+    /// Nummeraanduiding identificatie.
+    pub id: u64,
     pub house_number: u32,
+    pub house_letter: Option<String>,
+    pub house_number_addition: Option<String>,
     pub postal_code: String,
     pub public_space_id: u64,
 }
@@ -38,29 +49,37 @@ pub struct Address {
 /// Parse BAG address XML data into structured address records.
 ///
 /// `reference_date` is the extract's standtechnische datum (YYYY-MM-DD);
-/// voorkomens with a future `beginGeldigheid` are excluded.
+/// voorkomens with a future `beginGeldigheid` are excluded. Unusable records
+/// are counted in the returned report.
 pub fn parse_addresses<R: BufRead>(
     source: R,
     reference_date: &str,
-) -> Result<Vec<Address>, quick_xml::Error> {
+    options: &ParseOptions,
+) -> Result<(Vec<Address>, ParseReport), quick_xml::Error> {
     let mut reader = Reader::from_reader(source);
     reader.config_mut().trim_text(true);
 
     let mut buf = Vec::new();
-    let mut by_id: HashMap<u64, (u32, Address)> = HashMap::new();
+    let mut report = ParseReport::default();
+    // Latest voorkomen per id; `Ok(None)` when it isn't an active address.
+    let mut by_id: HashMap<u64, (u32, Outcome<Address>)> = HashMap::new();
 
     loop {
         buf.clear();
         match reader.read_event_into(&mut buf)? {
-            Event::Start(e) if e.name().as_ref() == NUM_TAG => {
-                if let Some((id, voorkomen_id, address)) =
-                    parse_address(&mut reader, &mut buf, reference_date)?
+            Event::Start(e) if e.local_name().as_ref() == NUM_TAG => {
+                if let Some(Voorkomen {
+                    id,
+                    voorkomen_id,
+                    inactive: false,
+                    outcome,
+                }) = parse_address(&mut reader, &mut buf, reference_date, options, &mut report)?
                 {
                     match by_id.get_mut(&id) {
-                        Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, address),
+                        Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, outcome),
                         Some(_) => {}
                         None => {
-                            by_id.insert(id, (voorkomen_id, address));
+                            by_id.insert(id, (voorkomen_id, outcome));
                         }
                     }
                 }
@@ -70,31 +89,51 @@ pub fn parse_addresses<R: BufRead>(
         }
     }
 
-    Ok(by_id.into_values().map(|(_, a)| a).collect())
+    let addresses = by_id
+        .into_values()
+        .filter_map(|(_, address)| {
+            address
+                .inspect_err(|reason| report.record(*reason))
+                .ok()
+                .flatten()
+        })
+        .collect();
+    Ok((addresses, report))
 }
 
-fn parse_address<B: BufRead>(
+/// Parse one Nummeraanduiding voorkomen. Its outcome is `Ok(None)` when the
+/// voorkomen is inactive or withdrawn and the skip reason when it is
+/// incomplete. Active voorkomens without a valid id are counted in `report`.
+pub(super) fn parse_address<B: BufRead>(
     reader: &mut Reader<B>,
     buf: &mut Vec<u8>,
     reference_date: &str,
-) -> Result<Option<(u64, u32, Address)>, quick_xml::Error> {
+    options: &ParseOptions,
+    report: &mut ParseReport,
+) -> Result<Option<Voorkomen<Address>>, quick_xml::Error> {
     let mut id = None;
     let mut house_number = None;
+    let mut house_letter = None;
+    let mut house_number_addition = None;
     let mut postal_code = None;
     let mut public_space_id = None;
-    let mut issued = false;
+    let mut status = None;
     let mut invalid = None;
+    let mut invalid_id = None;
     let mut state = VoorkomenState::default();
 
     loop {
         buf.clear();
         match reader.read_event_into(buf)? {
-            Event::Start(e) if e.name().as_ref() == ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, ID_TAG, buf)? {
-                    id = Some(value.parse::<u64>().expect("Failed to parse address id"));
+                    match value.parse::<u64>() {
+                        Ok(value) => id = Some(value),
+                        Err(_) => invalid_id = Some(value),
+                    }
                 }
             }
-            Event::Start(e) if e.name().as_ref() == HOUSE_NUMBER_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == HOUSE_NUMBER_TAG => {
                 if let Some(value) = read_simple_tag(reader, HOUSE_NUMBER_TAG, buf)? {
                     if let Ok(num) = value.parse::<u32>() {
                         house_number = Some(num);
@@ -103,71 +142,264 @@ fn parse_address<B: BufRead>(
                     }
                 }
             }
-            Event::Start(e) if e.name().as_ref() == POSTAL_CODE_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == HOUSE_LETTER_TAG => {
+                house_letter = read_simple_tag(reader, HOUSE_LETTER_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == HOUSE_NUMBER_ADDITION_TAG => {
+                house_number_addition = read_simple_tag(reader, HOUSE_NUMBER_ADDITION_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == POSTAL_CODE_TAG => {
                 if let Some(value) = read_simple_tag(reader, POSTAL_CODE_TAG, buf)? {
                     postal_code = Some(value);
                 }
             }
-            Event::Start(e) if e.name().as_ref() == PUBLIC_SPACE_REF_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == PUBLIC_SPACE_REF_TAG => {
                 if let Some(value) = read_simple_tag(reader, PUBLIC_SPACE_REF_TAG, buf)? {
                     public_space_id = value.parse::<u64>().ok();
                 }
             }
-            Event::Start(e) if e.name().as_ref() == STATUS_TAG => {
-                if let Some(value) = read_simple_tag(reader, STATUS_TAG, buf)?
-                    && value == ISSUED_STATUS
-                {
-                    issued = true;
-                }
+            Event::Start(e) if e.local_name().as_ref() == STATUS_TAG => {
+                status = read_simple_tag(reader, STATUS_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == END_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == END_VALIDITY_TAG => {
                 state.eind_geldigheid = true;
                 let _ = read_simple_tag(reader, END_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == BEGIN_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == BEGIN_VALIDITY_TAG => {
                 state.begin_geldigheid = read_simple_tag(reader, BEGIN_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
                 state.tijdstip_inactief = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_INACTIEF_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
                 state.tijdstip_nietbag = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_NIETBAG_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == VOORKOMEN_ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == VOORKOMEN_ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, VOORKOMEN_ID_TAG, buf)? {
                     state.voorkomen_id = value.parse().ok();
                 }
             }
-            Event::End(e) if e.name().as_ref() == NUM_TAG => break,
+            Event::End(e) if e.local_name().as_ref() == NUM_TAG => break,
             Event::Eof => break,
             _ => {}
         }
     }
 
-    if !issued || state.is_inactive(reference_date) {
+    let inactive = state.is_inactive(reference_date);
+    if invalid_id.is_some() {
+        if !inactive {
+            report.record(SkipReason::InvalidAddressId);
+        }
         return Ok(None);
     }
-
-    if let Some(invalid_value) = invalid {
-        eprintln!(
-            "Warning: Skipping address with invalid house number '{}'",
-            invalid_value
-        );
+    let Some(id) = id else {
         return Ok(None);
-    }
+    };
 
-    match (id, house_number, postal_code, public_space_id) {
-        (Some(id), Some(house_number), Some(postal_code), Some(public_space_id)) => Ok(Some((
+    let outcome = match (invalid, house_number, postal_code, public_space_id) {
+        _ if inactive || !options.accepts_naming_status(status.as_deref()) => Ok(None),
+        (Some(_), ..) => Err(SkipReason::InvalidHouseNumber),
+        (None, None, ..) => Err(SkipReason::MissingHouseNumber),
+        (None, _, None, _) => Err(SkipReason::MissingPostalCode),
+        (None, _, _, None) => Err(SkipReason::MissingPublicSpaceRef),
+        (None, Some(house_number), Some(postal_code), Some(public_space_id)) => Ok(Some(Address {
             id,
-            state.voorkomen_id.unwrap_or(0),
-            Address {
-                house_number,
-                postal_code,
-                public_space_id,
-            },
-        ))),
-        _ => Ok(None),
+            house_number,
+            house_letter,
+            house_number_addition,
+            postal_code,
+            public_space_id,
+        })),
+    };
+    Ok(Some(Voorkomen {
+        id,
+        voorkomen_id: state.voorkomen_id.unwrap_or(0),
+        inactive,
+        outcome,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseOptions, SkipReason, parse_addresses};
+
+    #[test]
+    fn invalid_address_id_skips_the_record() {
+        let xml = r#"<sl:bagObject>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">00032000001339XX</Objecten:identificatie>
+  <Objecten:huisnummer>1</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+</Objecten:Nummeraanduiding>
+</sl:bagObject>"#;
+
+        let (addresses, report) =
+            parse_addresses(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].id, 3200000133985);
+        assert_eq!(addresses[0].house_number, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.count(SkipReason::InvalidAddressId), 1);
+    }
+
+    #[test]
+    fn incomplete_latest_voorkomens_are_reported() {
+        let xml = r#"<sl:bagObject>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2a</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>1</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>2</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133986</Objecten:identificatie>
+  <Objecten:huisnummer>4</Objecten:huisnummer>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133987</Objecten:identificatie>
+  <Objecten:huisnummer>6x</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+</Objecten:Nummeraanduiding>
+</sl:bagObject>"#;
+
+        let (addresses, report) =
+            parse_addresses(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+
+        // The invalid first voorkomen of 2 is superseded, so it isn't a skip.
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].house_number, 2);
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.count(SkipReason::MissingPostalCode), 1);
+        assert_eq!(report.count(SkipReason::InvalidHouseNumber), 1);
+    }
+
+    #[test]
+    fn withdrawn_latest_voorkomen_drops_the_address() {
+        let xml = r#"<sl:bagObject>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>1</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving ingetrokken</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>2</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133986</Objecten:identificatie>
+  <Objecten:huisnummer>4</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving ingetrokken</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>1</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133986</Objecten:identificatie>
+  <Objecten:huisnummer>4</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>2</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:Nummeraanduiding>
+</sl:bagObject>"#;
+
+        let (addresses, _) =
+            parse_addresses(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+
+        // Only the address whose latest voorkomen is issued remains.
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].house_number, 4);
+    }
+
+    #[test]
+    fn options_can_accept_more_naming_statuses() {
+        let xml = r#"<sl:bagObject>
+<Objecten:Nummeraanduiding>
+  <Objecten:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten:identificatie>
+  <Objecten:huisnummer>2</Objecten:huisnummer>
+  <Objecten:postcode>1234AB</Objecten:postcode>
+  <Objecten:status>Naamgeving ingetrokken</Objecten:status>
+  <Objecten:ligtAan><Objecten-ref:OpenbareRuimteRef>0003300000116985</Objecten-ref:OpenbareRuimteRef></Objecten:ligtAan>
+</Objecten:Nummeraanduiding>
+</sl:bagObject>"#;
+
+        let (addresses, _) =
+            parse_addresses(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+        assert!(addresses.is_empty());
+
+        let mut options = ParseOptions::default();
+        options
+            .naming_statuses
+            .push("Naamgeving ingetrokken".to_string());
+        let (addresses, _) = parse_addresses(xml.as_bytes(), "2025-12-08", &options).unwrap();
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].house_number, 2);
+    }
+
+    #[test]
+    fn tags_match_regardless_of_namespace_prefix() {
+        let xml = r#"<sl:bagObject>
+<Obj:Nummeraanduiding>
+  <Obj:identificatie domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Obj:identificatie>
+  <Obj:huisnummer>2</Obj:huisnummer>
+  <Obj:huisletter>A</Obj:huisletter>
+  <Obj:postcode>1234AB</Obj:postcode>
+  <Obj:status>Naamgeving uitgegeven</Obj:status>
+  <Obj:ligtAan><ObjRef:OpenbareRuimteRef>0003300000116985</ObjRef:OpenbareRuimteRef></Obj:ligtAan>
+  <Obj:voorkomen><Hist:Voorkomen><Hist:voorkomenidentificatie>1</Hist:voorkomenidentificatie></Hist:Voorkomen></Obj:voorkomen>
+</Obj:Nummeraanduiding>
+<Nummeraanduiding>
+  <identificatie>0003200000133986</identificatie>
+  <huisnummer>4</huisnummer>
+  <postcode>1234AB</postcode>
+  <status>Naamgeving uitgegeven</status>
+  <ligtAan><OpenbareRuimteRef>0003300000116985</OpenbareRuimteRef></ligtAan>
+  <voorkomen><Voorkomen><eindGeldigheid>2020-01-01</eindGeldigheid></Voorkomen></voorkomen>
+</Nummeraanduiding>
+</sl:bagObject>"#;
+
+        let (addresses, _) =
+            parse_addresses(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+
+        // The unprefixed record is parsed too: its end validity drops it.
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].id, 3200000133985);
+        assert_eq!(addresses[0].house_number, 2);
+        assert_eq!(addresses[0].house_letter.as_deref(), Some("A"));
+        assert_eq!(addresses[0].postal_code, "1234AB");
+        assert_eq!(addresses[0].public_space_id, 3300000116985);
     }
 }
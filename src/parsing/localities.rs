@@ -3,29 +3,30 @@
 //
 // A Woonplaats is a formally designated area within a municipality. Only
 // voorkomens that are part of the active lifecycle on the extract's
-// standtechnische datum are kept: not superseded, not retracted, not inactive,
-// not flagged NIET BAG, and with a beginGeldigheid that has already passed.
+// standtechnische datum are kept: not superseded, not inactive, not flagged
+// NIET BAG, with a beginGeldigheid that has already passed, and with an
+// accepted status (see `ParseOptions::locality_statuses`).
 
 use std::{collections::HashMap, io::BufRead};
 
 use quick_xml::{events::Event, reader::Reader};
 
 use super::{
+    ParseOptions, ParseReport, SkipReason,
     municipalities::strip_province_suffix,
     xml_utils::{
         BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
-        VOORKOMEN_ID_TAG, VoorkomenState, read_simple_tag,
+        VOORKOMEN_ID_TAG, Voorkomen, VoorkomenState, read_simple_tag,
     },
 };
 
-const WP_TAG: &[u8] = b"Objecten:Woonplaats";
+pub(super) const WP_TAG: &[u8] = b"Woonplaats";
 // §7.2.1 identificatie - unique four-digit national identifier
-const ID_TAG: &[u8] = b"Objecten:identificatie";
+const ID_TAG: &[u8] = b"identificatie";
 // §7.2.2 naam - official locality name
-const NAME_TAG: &[u8] = b"Objecten:naam";
+const NAME_TAG: &[u8] = b"naam";
 // §7.2.3 status - "Woonplaats aangewezen" (active) or "Woonplaats ingetrokken" (retracted)
-const STATUS_TAG: &[u8] = b"Objecten:status";
-const STATUS_RETRACTED: &str = "Woonplaats ingetrokken";
+const STATUS_TAG: &[u8] = b"status";
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Locality {
@@ -40,15 +41,18 @@ pub struct Locality {
 /// Parse BAG locality XML data into structured locality records.
 ///
 /// `reference_date` is the extract's standtechnische datum (YYYY-MM-DD);
-/// voorkomens with a future `beginGeldigheid` are excluded.
+/// voorkomens with a future `beginGeldigheid` are excluded. Unusable records
+/// are counted in the returned report.
 pub fn parse_localities<R: BufRead>(
     reader: R,
     reference_date: &str,
-) -> Result<Vec<Locality>, quick_xml::Error> {
+    options: &ParseOptions,
+) -> Result<(Vec<Locality>, ParseReport), quick_xml::Error> {
     let mut reader = Reader::from_reader(reader);
     reader.config_mut().trim_text(true);
 
     let mut buf = Vec::new();
+    let mut report = ParseReport::default();
     // Dedup by identificatiecode, keeping the voorkomen with the highest
     // voorkomenidentificatie (the latest materially-valid version).
     let mut by_id: HashMap<u16, (u32, Locality)> = HashMap::new();
@@ -56,17 +60,25 @@ pub fn parse_localities<R: BufRead>(
     loop {
         buf.clear();
         match reader.read_event_into(&mut buf)? {
-            Event::Start(e) if e.name().as_ref() == WP_TAG => {
-                if let Some((voorkomen_id, locality)) =
-                    parse_woonplaats(&mut reader, &mut buf, reference_date)?
-                {
-                    match by_id.get_mut(&locality.id) {
+            Event::Start(e) if e.local_name().as_ref() == WP_TAG => {
+                let Some(Voorkomen {
+                    voorkomen_id,
+                    outcome,
+                    ..
+                }) = parse_woonplaats(&mut reader, &mut buf, reference_date, options, &mut report)?
+                else {
+                    continue;
+                };
+                match outcome {
+                    Ok(Some(locality)) => match by_id.get_mut(&locality.id) {
                         Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, locality),
                         Some(_) => {}
                         None => {
                             by_id.insert(locality.id, (voorkomen_id, locality));
                         }
-                    }
+                    },
+                    Ok(None) => {}
+                    Err(reason) => report.record(reason),
                 }
             }
             Event::Eof => break,
@@ -76,71 +88,86 @@ pub fn parse_localities<R: BufRead>(
 
     let mut out: Vec<Locality> = by_id.into_values().map(|(_, loc)| loc).collect();
     out.sort_by_key(|l| l.id);
-    Ok(out)
+    Ok((out, report))
 }
 
-fn parse_woonplaats<B: BufRead>(
+/// Parse one Woonplaats voorkomen. Its outcome is `Ok(None)` when the
+/// voorkomen is inactive or its status isn't accepted. Active voorkomens
+/// without a valid id are counted in `report`.
+pub(super) fn parse_woonplaats<B: BufRead>(
     reader: &mut Reader<B>,
     buf: &mut Vec<u8>,
     reference_date: &str,
-) -> Result<Option<(u32, Locality)>, quick_xml::Error> {
+    options: &ParseOptions,
+    report: &mut ParseReport,
+) -> Result<Option<Voorkomen<Locality>>, quick_xml::Error> {
     let mut id = None;
     let mut name = None;
-    let mut retracted = false;
+    let mut status = None;
+    let mut invalid = None;
     let mut state = VoorkomenState::default();
 
     loop {
         buf.clear();
         match reader.read_event_into(buf)? {
-            Event::Start(e) if e.name().as_ref() == ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, ID_TAG, buf)? {
-                    id = Some(value.parse().expect("Failed to parse locality id"));
+                    match value.parse() {
+                        Ok(value) => id = Some(value),
+                        Err(_) => invalid = Some(value),
+                    }
                 }
             }
-            Event::Start(e) if e.name().as_ref() == NAME_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == NAME_TAG => {
                 if let Some(value) = read_simple_tag(reader, NAME_TAG, buf)? {
                     name = Some(value);
                 }
             }
-            Event::Start(e) if e.name().as_ref() == STATUS_TAG => {
-                if let Some(value) = read_simple_tag(reader, STATUS_TAG, buf)?
-                    && value == STATUS_RETRACTED
-                {
-                    retracted = true;
-                }
+            Event::Start(e) if e.local_name().as_ref() == STATUS_TAG => {
+                status = read_simple_tag(reader, STATUS_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == END_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == END_VALIDITY_TAG => {
                 state.eind_geldigheid = true;
                 let _ = read_simple_tag(reader, END_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == BEGIN_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == BEGIN_VALIDITY_TAG => {
                 state.begin_geldigheid = read_simple_tag(reader, BEGIN_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
                 state.tijdstip_inactief = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_INACTIEF_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
                 state.tijdstip_nietbag = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_NIETBAG_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == VOORKOMEN_ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == VOORKOMEN_ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, VOORKOMEN_ID_TAG, buf)? {
                     state.voorkomen_id = value.parse().ok();
                 }
             }
-            Event::End(e) if e.name().as_ref() == WP_TAG => break,
+            Event::End(e) if e.local_name().as_ref() == WP_TAG => break,
             Event::Eof => break,
             _ => {}
         }
     }
 
-    if retracted || state.is_inactive(reference_date) {
+    let inactive = state.is_inactive(reference_date);
+    let dropped = inactive || !options.accepts_locality_status(status.as_deref());
+    if invalid.is_some() {
+        if !dropped {
+            report.record(SkipReason::InvalidLocalityId);
+        }
         return Ok(None);
     }
+    let Some(id) = id else {
+        return Ok(None);
+    };
 
-    match (id, name) {
-        (Some(id), Some(mut name)) => {
+    let outcome = match name {
+        _ if dropped => Ok(None),
+        None => Err(SkipReason::MissingLocalityName),
+        Some(mut name) => {
             let stripped = strip_province_suffix(&name);
             let had_suffix = stripped.len() != name.len();
             if had_suffix {
@@ -150,15 +177,46 @@ fn parse_woonplaats<B: BufRead>(
                 );
                 name = new_name;
             }
-            Ok(Some((
-                state.voorkomen_id.unwrap_or(0),
-                Locality {
-                    id,
-                    name,
-                    had_suffix,
-                },
-            )))
+            Ok(Some(Locality {
+                id,
+                name,
+                had_suffix,
+            }))
         }
-        _ => Ok(None),
+    };
+    Ok(Some(Voorkomen {
+        id: u64::from(id),
+        voorkomen_id: state.voorkomen_id.unwrap_or(0),
+        inactive,
+        outcome,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseOptions, SkipReason, parse_localities};
+
+    #[test]
+    fn invalid_locality_id_skips_the_record() {
+        let xml = r#"<sl:bagObject>
+<Objecten:Woonplaats>
+  <Objecten:identificatie domein="NL.IMBAG.Woonplaats">not-a-number</Objecten:identificatie>
+  <Objecten:naam>Nergens</Objecten:naam>
+  <Objecten:status>Woonplaats aangewezen</Objecten:status>
+</Objecten:Woonplaats>
+<Objecten:Woonplaats>
+  <Objecten:identificatie domein="NL.IMBAG.Woonplaats">3594</Objecten:identificatie>
+  <Objecten:naam>Amsterdam</Objecten:naam>
+  <Objecten:status>Woonplaats aangewezen</Objecten:status>
+</Objecten:Woonplaats>
+</sl:bagObject>"#;
+
+        let (localities, report) =
+            parse_localities(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
+
+        assert_eq!(localities.len(), 1);
+        assert_eq!(localities[0].id, 3594);
+        assert_eq!(localities[0].name, "Amsterdam");
+        assert_eq!(report.count(SkipReason::InvalidLocalityId), 1);
     }
 }
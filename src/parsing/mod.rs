@@ -2,39 +2,126 @@ mod addresses;
 mod localities;
 pub mod municipalities;
 mod municipality_relations;
+mod mutations;
 mod public_spaces;
+mod report;
 pub mod rvig_municipalities;
+mod verblijfsobjecten;
 mod xml_utils;
 
 use std::{
+    collections::HashSet,
     error::Error,
     fs::File,
-    io::{BufReader, Cursor, Read},
-    path::Path,
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
     time::Instant,
 };
 
 use rayon::prelude::*;
+use tempfile::NamedTempFile;
 
 pub use addresses::{Address, parse_addresses};
 pub use localities::{Locality, parse_localities};
 pub use municipality_relations::{MunicipalityRelation, parse_municipality_relations};
 pub use public_spaces::{PublicSpace, parse_public_spaces};
+pub use report::{ParseReport, SkipReason};
+pub use verblijfsobjecten::{AddressPoint, parse_verblijfsobjecten};
 use zip::ZipArchive;
 
 use crate::log_with_elapsed;
 
+/// Progress reported by [`ParsedData::from_bag_zip_with_progress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// Parsing of the section `label` (e.g. `"addresses"`) started.
+    StartedSection { label: &'static str },
+    /// The section `label` was parsed into `count` records.
+    ParsedItems { label: &'static str, count: usize },
+    /// All sections have been parsed.
+    Finished,
+}
+
+/// Which BAG statuses keep a record while parsing.
+///
+/// The default keeps what a production lookup should contain. A looser set,
+/// e.g. one that also accepts "Naamgeving ingetrokken", builds a database for
+/// testing against records that are no longer or not yet in use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Naming statuses of OpenbareRuimte (§7.3.4) and Nummeraanduiding
+    /// (§7.4.7) objects that are kept. The status of an object's latest
+    /// voorkomen counts: any status not listed here drops the object, even
+    /// when an earlier voorkomen had a listed one. Defaults to
+    /// "Naamgeving uitgegeven".
+    pub naming_statuses: Vec<String>,
+    /// Statuses of Woonplaats (§7.2.3) voorkomens that are kept. Defaults to
+    /// "Woonplaats aangewezen".
+    pub locality_statuses: Vec<String>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            naming_statuses: vec!["Naamgeving uitgegeven".to_string()],
+            locality_statuses: vec!["Woonplaats aangewezen".to_string()],
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Whether a public space or address voorkomen with naming `status` keeps
+    /// its object.
+    pub(crate) fn accepts_naming_status(&self, status: Option<&str>) -> bool {
+        status.is_some_and(|status| self.naming_statuses.iter().any(|s| s == status))
+    }
+
+    /// Whether a locality voorkomen with `status` is kept.
+    pub(crate) fn accepts_locality_status(&self, status: Option<&str>) -> bool {
+        status.is_some_and(|status| self.locality_statuses.iter().any(|s| s == status))
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct ParsedData {
     pub addresses: Vec<addresses::Address>,
     pub public_spaces: Vec<public_spaces::PublicSpace>,
     pub localities: Vec<localities::Locality>,
     pub municipality_relations: Vec<municipality_relations::MunicipalityRelation>,
+    pub address_points: Vec<verblijfsobjecten::AddressPoint>,
+    /// The records that were skipped as unusable, over all sections.
+    pub report: ParseReport,
 }
 
 impl ParsedData {
     /// Load and parse BAG data from a zip archive into structured records.
+    ///
+    /// The XML files of each nested zip are parsed on the global rayon pool,
+    /// so `RAYON_NUM_THREADS` bounds the parallelism. The records are the
+    /// same for any thread count.
     pub fn from_bag_zip(zip_path: &Path, start: Instant) -> Result<ParsedData, Box<dyn Error>> {
+        ParsedData::from_bag_zip_with_progress(zip_path, start, |_| {})
+    }
+
+    /// Like [`ParsedData::from_bag_zip`], reporting progress to `progress` as
+    /// each nested zip is parsed.
+    pub fn from_bag_zip_with_progress(
+        zip_path: &Path,
+        start: Instant,
+        progress: impl FnMut(ProgressEvent),
+    ) -> Result<ParsedData, Box<dyn Error>> {
+        ParsedData::from_bag_zip_with_options(zip_path, start, &ParseOptions::default(), progress)
+    }
+
+    /// Like [`ParsedData::from_bag_zip_with_progress`], keeping the records
+    /// whose statuses `options` accepts.
+    pub fn from_bag_zip_with_options(
+        zip_path: &Path,
+        start: Instant,
+        options: &ParseOptions,
+        mut progress: impl FnMut(ProgressEvent),
+    ) -> Result<ParsedData, Box<dyn Error>> {
         let f = File::open(zip_path)?;
         let mut zip = ZipArchive::new(f)?;
         let mut data = ParsedData::default();
@@ -61,8 +148,10 @@ impl ParsedData {
                 data.municipality_relations = ParsedData::parse_nested_xml_zip(
                     start,
                     &mut entry,
+                    &mut progress,
+                    &mut data.report,
                     "municipality relations",
-                    |reader| parse_municipality_relations(reader, &reference_date),
+                    |reader| parse_municipality_relations(reader, &reference_date).map(unreported),
                 )?;
             } else {
                 match &name[..7] {
@@ -71,8 +160,10 @@ impl ParsedData {
                         data.localities = ParsedData::parse_nested_xml_zip(
                             start,
                             &mut entry,
+                            &mut progress,
+                            &mut data.report,
                             "localities",
-                            |reader| parse_localities(reader, &reference_date),
+                            |reader| parse_localities(reader, &reference_date, options),
                         )?;
                     }
                     // OpenbareRuimte (public space) - BAG catalog §7.3
@@ -80,8 +171,10 @@ impl ParsedData {
                         data.public_spaces = ParsedData::parse_nested_xml_zip(
                             start,
                             &mut entry,
+                            &mut progress,
+                            &mut data.report,
                             "public spaces",
-                            |reader| parse_public_spaces(reader, &reference_date),
+                            |reader| parse_public_spaces(reader, &reference_date, options),
                         )?;
                     }
                     // Nummeraanduiding (address designation) - BAG catalog §7.4
@@ -89,8 +182,23 @@ impl ParsedData {
                         data.addresses = ParsedData::parse_nested_xml_zip(
                             start,
                             &mut entry,
+                            &mut progress,
+                            &mut data.report,
                             "addresses",
-                            |reader| parse_addresses(reader, &reference_date),
+                            |reader| parse_addresses(reader, &reference_date, options),
+                        )?;
+                    }
+                    // Verblijfsobject (addressable object) - BAG catalog §7.6
+                    "9999VBO" => {
+                        data.address_points = ParsedData::parse_nested_xml_zip(
+                            start,
+                            &mut entry,
+                            &mut progress,
+                            &mut data.report,
+                            "address points",
+                            |reader| {
+                                parse_verblijfsobjecten(reader, &reference_date).map(unreported)
+                            },
                         )?;
                     }
                     _ => {
@@ -100,55 +208,292 @@ impl ParsedData {
             }
         }
 
+        data.report_dangling_refs();
+        progress(ProgressEvent::Finished);
+
         Ok(data)
     }
 
     fn parse_nested_xml_zip<T, F>(
         start: Instant,
         entry: &mut zip::read::ZipFile<'_, File>,
-        label: &str,
+        progress: &mut impl FnMut(ProgressEvent),
+        report: &mut ParseReport,
+        label: &'static str,
         parse_fn: F,
     ) -> Result<Vec<T>, Box<dyn Error>>
     where
         T: Send,
-        F: Fn(&mut dyn std::io::BufRead) -> Result<Vec<T>, quick_xml::Error> + Sync,
+        F: Fn(&mut dyn std::io::BufRead) -> Result<(Vec<T>, ParseReport), quick_xml::Error> + Sync,
     {
+        progress(ProgressEvent::StartedSection { label });
+
         let name = entry.name().to_string();
-        let mut buf = Vec::new();
-        entry.read_to_end(&mut buf)?;
+        let mut spool = spool_file(&name)?;
+        let mut writer = BufWriter::new(spool.as_file_mut());
+        let bytes = io::copy(entry, &mut writer)?;
+        writer.into_inner().map_err(|e| e.into_error())?;
+
+        log_with_elapsed(
+            start,
+            &format!(
+                "Spooled {bytes} bytes from {name} to {}",
+                spool.path().display()
+            ),
+        );
+
+        // The nested zip is spooled to disk rather than held in memory, as
+        // it can be gigabytes large.
+        let (items, section_report) = parse_xml_zip(spool.path(), &parse_fn)?;
+        report.merge(section_report);
+
+        log_with_elapsed(start, &format!("Parsed {} {label}", items.len()));
+        progress(ProgressEvent::ParsedItems {
+            label,
+            count: items.len(),
+        });
+
+        Ok(items)
+    }
+
+    /// Load and parse BAG data from a directory holding an extracted BAG
+    /// extract.
+    ///
+    /// The directory is searched recursively for XML files and nested zips
+    /// whose name contains the object type (`WPL`, `OPR`, `NUM`, `VBO` or
+    /// `GEM-WPL`), so both the nested zips of the extract and their unpacked
+    /// contents are accepted. The records are the same as those of
+    /// [`ParsedData::from_bag_zip`] on the equivalent zip.
+    pub fn from_directory(dir: &Path, start: Instant) -> Result<ParsedData, Box<dyn Error>> {
+        ParsedData::from_directory_with_options(dir, start, &ParseOptions::default())
+    }
+
+    /// Like [`ParsedData::from_directory`], keeping the records whose statuses
+    /// `options` accepts.
+    pub fn from_directory_with_options(
+        dir: &Path,
+        start: Instant,
+        options: &ParseOptions,
+    ) -> Result<ParsedData, Box<dyn Error>> {
+        let mut paths = Vec::new();
+        collect_paths(dir, &mut paths)?;
+        paths.sort();
+
+        let reference_date = extract_date_from_names(
+            paths
+                .iter()
+                .filter_map(|path| path.file_name().and_then(|name| name.to_str())),
+        )
+        .ok_or("Could not determine standtechnische datum from BAG extract filenames")?;
+        log_with_elapsed(
+            start,
+            &format!("Using extract reference date {reference_date}"),
+        );
+
+        let mut report = ParseReport::default();
+        let mut data = ParsedData {
+            municipality_relations: parse_directory_section(
+                start,
+                &paths,
+                &mut report,
+                "GEM-WPL",
+                "municipality relations",
+                |reader| parse_municipality_relations(reader, &reference_date).map(unreported),
+            )?,
+            localities: parse_directory_section(
+                start,
+                &paths,
+                &mut report,
+                "WPL",
+                "localities",
+                |reader| parse_localities(reader, &reference_date, options),
+            )?,
+            public_spaces: parse_directory_section(
+                start,
+                &paths,
+                &mut report,
+                "OPR",
+                "public spaces",
+                |reader| parse_public_spaces(reader, &reference_date, options),
+            )?,
+            addresses: parse_directory_section(
+                start,
+                &paths,
+                &mut report,
+                "NUM",
+                "addresses",
+                |reader| parse_addresses(reader, &reference_date, options),
+            )?,
+            address_points: parse_directory_section(
+                start,
+                &paths,
+                &mut report,
+                "VBO",
+                "address points",
+                |reader| parse_verblijfsobjecten(reader, &reference_date).map(unreported),
+            )?,
+            report,
+        };
+        data.report_dangling_refs();
+
+        Ok(data)
+    }
 
-        log_with_elapsed(start, &format!("Read {} bytes from {name}", buf.len()));
+    /// Count the addresses referring to a public space that wasn't parsed;
+    /// they are left out of the database. Earlier counts are replaced.
+    fn report_dangling_refs(&mut self) {
+        if let Some(count) = self
+            .report
+            .reasons
+            .remove(&SkipReason::DanglingPublicSpaceRef)
+        {
+            self.report.skipped -= count;
+        }
+        let public_space_ids: HashSet<u64> = self.public_spaces.iter().map(|ps| ps.id).collect();
+        for address in &self.addresses {
+            if !public_space_ids.contains(&address.public_space_id) {
+                self.report.record(SkipReason::DanglingPublicSpaceRef);
+            }
+        }
+    }
+}
 
-        // Inner ZIP entries are parsed in parallel. Each worker opens its own
-        // ZipArchive over the shared buffer; ZipArchive::by_index needs &mut,
-        // so sharing a single archive across threads isn't possible, but
-        // re-opening is cheap since the central directory is already in memory.
-        let n = ZipArchive::new(Cursor::new(&buf[..]))?.len();
+/// Pair the records of a parser that skips nothing with an empty report.
+fn unreported<T>(items: Vec<T>) -> (Vec<T>, ParseReport) {
+    (items, ParseReport::default())
+}
 
-        let per_file: Vec<Vec<T>> = (0..n)
-            .into_par_iter()
-            .map(|i| -> Result<Vec<T>, Box<dyn Error + Send + Sync>> {
-                let mut inner_zip = ZipArchive::new(Cursor::new(&buf[..]))?;
+/// Parse the XML files of the zip at `path` in parallel.
+fn parse_xml_zip<T, F>(path: &Path, parse_fn: &F) -> Result<(Vec<T>, ParseReport), Box<dyn Error>>
+where
+    T: Send,
+    F: Fn(&mut dyn std::io::BufRead) -> Result<(Vec<T>, ParseReport), quick_xml::Error> + Sync,
+{
+    // ZipArchive::by_index needs &mut, so sharing a single archive across
+    // threads isn't possible; each worker opens its own archive instead.
+    let n = ZipArchive::new(File::open(path)?)?.len();
+
+    let per_file: Vec<(Vec<T>, ParseReport)> = (0..n)
+        .into_par_iter()
+        .map_init(
+            || ZipArchive::new(File::open(path)?),
+            |inner_zip, i| -> Result<(Vec<T>, ParseReport), Box<dyn Error + Send + Sync>> {
+                let inner_zip = inner_zip.as_mut().map_err(|e| e.to_string())?;
                 let inner_entry = inner_zip.by_index(i)?;
                 if !inner_entry.name().ends_with(".xml") {
-                    return Ok(Vec::new());
+                    return Ok(unreported(Vec::new()));
                 }
                 let mut reader = BufReader::new(inner_entry);
                 Ok(parse_fn(&mut reader)?)
-            })
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| -> Box<dyn Error> { e })?;
-
-        let total: usize = per_file.iter().map(Vec::len).sum();
-        let mut items = Vec::with_capacity(total);
-        for chunk in per_file {
-            items.extend(chunk);
-        }
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| -> Box<dyn Error> { e })?;
 
-        log_with_elapsed(start, &format!("Parsed {} {label}", items.len()));
+    Ok(merge_files(per_file))
+}
 
-        Ok(items)
+/// Concatenate the records and merge the reports of parsed files.
+fn merge_files<T>(per_file: Vec<(Vec<T>, ParseReport)>) -> (Vec<T>, ParseReport) {
+    let mut items = Vec::new();
+    let mut report = ParseReport::default();
+    for (file_items, file_report) in per_file {
+        items.extend(file_items);
+        report.merge(file_report);
+    }
+    (items, report)
+}
+
+/// Parse the XML files and nested zips among `paths` that hold the object
+/// type `code`.
+fn parse_directory_section<T, F>(
+    start: Instant,
+    paths: &[PathBuf],
+    report: &mut ParseReport,
+    code: &str,
+    label: &str,
+    parse_fn: F,
+) -> Result<Vec<T>, Box<dyn Error>>
+where
+    T: Send,
+    F: Fn(&mut dyn std::io::BufRead) -> Result<(Vec<T>, ParseReport), quick_xml::Error> + Sync,
+{
+    let paths: Vec<&PathBuf> = paths
+        .iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(object_type_code)
+                == Some(code)
+        })
+        .collect();
+
+    let per_file: Vec<(Vec<T>, ParseReport)> = paths
+        .par_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+        .map(
+            |path| -> Result<(Vec<T>, ParseReport), Box<dyn Error + Send + Sync>> {
+                let mut reader = BufReader::new(File::open(path)?);
+                Ok(parse_fn(&mut reader)?)
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| -> Box<dyn Error> { e })?;
+    let (mut items, section_report) = merge_files(per_file);
+    report.merge(section_report);
+
+    for path in paths
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+    {
+        let (zip_items, zip_report) = parse_xml_zip(path, &parse_fn)?;
+        items.extend(zip_items);
+        report.merge(zip_report);
+    }
+
+    log_with_elapsed(start, &format!("Parsed {} {label}", items.len()));
+
+    Ok(items)
+}
+
+/// The BAG object type a file belongs to, judged by its name.
+fn object_type_code(name: &str) -> Option<&'static str> {
+    if name.contains("GEM-WPL") {
+        return Some("GEM-WPL");
+    }
+    ["WPL", "OPR", "NUM", "VBO"]
+        .into_iter()
+        .find(|code| name.contains(code))
+}
+
+/// Recursively collect the files and directories below `dir`, skipping
+/// hidden ones such as macOS resource forks.
+fn collect_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_paths(&path, paths)?;
+        }
+        paths.push(path);
     }
+    Ok(())
+}
+
+/// Create a temporary file for a copy of the nested zip `name`, removed
+/// again when dropped.
+///
+/// The file gets a random name and is created exclusively, so another user
+/// of the temporary directory can't redirect the copy through a symlink.
+fn spool_file(name: &str) -> io::Result<NamedTempFile> {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    tempfile::Builder::new()
+        .prefix("bag-address-lookup-")
+        .suffix(&format!("-{file_name}"))
+        .tempfile()
 }
 
 /// Extract the standtechnische datum from the BAG extract's filenames.
@@ -157,9 +502,12 @@ impl ParsedData {
 /// or `GEM-WPL-RELATIE-08122025.zip`). We scan entries for a trailing 8-digit
 /// run and reformat it as ISO-8601 so later string comparisons sort correctly.
 fn extract_date_from_zip(zip: &mut ZipArchive<File>) -> Option<String> {
-    for index in 0..zip.len() {
-        let entry = zip.by_index(index).ok()?;
-        let name = entry.name();
+    extract_date_from_names(zip.file_names())
+}
+
+/// Find the DDMMYYYY date in the first of `names` that ends in one.
+fn extract_date_from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    for name in names {
         let stem = name
             .rsplit('/')
             .next()
@@ -188,6 +536,7 @@ fn extract_date_from_zip(zip: &mut ZipArchive<File>) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::TempPath;
     use std::path::PathBuf;
 
     #[test]
@@ -230,6 +579,113 @@ mod tests {
         assert_eq!(locality_names, vec!["Hoogerheide", "Huijbergen"]);
     }
 
+    /// Debug output of each record, sorted, so comparisons ignore order.
+    fn sorted<T: std::fmt::Debug>(items: &[T]) -> Vec<String> {
+        let mut items: Vec<String> = items.iter().map(|item| format!("{item:?}")).collect();
+        items.sort();
+        items
+    }
+
+    fn assert_same_records(left: &ParsedData, right: &ParsedData) {
+        assert_eq!(sorted(&left.addresses), sorted(&right.addresses));
+        assert_eq!(sorted(&left.public_spaces), sorted(&right.public_spaces));
+        assert_eq!(sorted(&left.localities), sorted(&right.localities));
+        assert_eq!(
+            sorted(&left.municipality_relations),
+            sorted(&right.municipality_relations)
+        );
+        assert_eq!(sorted(&left.address_points), sorted(&right.address_points));
+        assert_eq!(left.report, right.report);
+    }
+
+    #[test]
+    fn parallel_parse_matches_sequential_parse() {
+        let parse_with_threads = |threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    ParsedData::from_bag_zip(&PathBuf::from("test/bag.zip"), Instant::now())
+                        .map_err(|err| err.to_string())
+                })
+                .unwrap()
+        };
+        let sequential = parse_with_threads(1);
+        let parallel = parse_with_threads(4);
+
+        assert_same_records(&parallel, &sequential);
+    }
+
+    #[test]
+    fn spool_files_are_unique_and_removed_on_drop() {
+        let first = spool_file("nested/9999NUM08122025.zip").unwrap();
+        let second = spool_file("nested/9999NUM08122025.zip").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(
+            first
+                .path()
+                .to_string_lossy()
+                .ends_with("-9999NUM08122025.zip")
+        );
+
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn directory_parse_matches_zip_parse() {
+        let zip_path = PathBuf::from("test/bag.zip");
+        let expected = ParsedData::from_bag_zip(&zip_path, Instant::now()).unwrap();
+        assert!(!expected.addresses.is_empty());
+
+        let dir = TempPath::new("extract");
+        ZipArchive::new(File::open(&zip_path).unwrap())
+            .unwrap()
+            .extract(&dir)
+            .unwrap();
+
+        // The nested zips as they come out of the extract
+        let parsed = ParsedData::from_directory(&dir, Instant::now()).unwrap();
+        assert_same_records(&parsed, &expected);
+
+        // And fully unpacked into XML files
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "zip") {
+                ZipArchive::new(File::open(&path).unwrap())
+                    .unwrap()
+                    .extract(path.with_extension(""))
+                    .unwrap();
+                std::fs::remove_file(&path).unwrap();
+            }
+        }
+        let parsed = ParsedData::from_directory(&dir, Instant::now()).unwrap();
+        assert_same_records(&parsed, &expected);
+    }
+
+    #[test]
+    fn progress_reports_each_section() {
+        let mut events = Vec::new();
+        let data = ParsedData::from_bag_zip_with_progress(
+            &PathBuf::from("test/bag.zip"),
+            Instant::now(),
+            |event| events.push(event),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            events.first(),
+            Some(ProgressEvent::StartedSection { .. })
+        ));
+        assert!(events.contains(&ProgressEvent::ParsedItems {
+            label: "addresses",
+            count: data.addresses.len(),
+        }));
+        assert_eq!(events.last(), Some(&ProgressEvent::Finished));
+    }
+
     #[test]
     fn extract_date_parses_ddmmyyyy_filename() {
         // The function expects a real ZIP archive; just verify the algorithm
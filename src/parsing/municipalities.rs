@@ -6,7 +6,7 @@
 
 use std::{error::Error, time::Instant};
 
-use crate::log_with_elapsed;
+use crate::{download::fetch, log_with_elapsed};
 
 static CBS_TABLE_ID_FALLBACK: &str = "86247NED";
 static CBS_FALLBACK_PATH: &str = "fallback/municipalities.json";
@@ -170,16 +170,9 @@ fn detect_latest_table_id(start: Instant) -> Result<String, Box<dyn Error>> {
         $filter=substringof(%27Gebieden%20in%20Nederland%27,%20Title)\
         &$select=Identifier&$format=json&$orderby=Title%20desc&$top=1";
 
-    let output = std::process::Command::new("curl")
-        .arg("-sL")
-        .arg(catalog_url)
-        .output()?;
+    let body = fetch(catalog_url).map_err(|e| format!("CBS catalog request failed: {e}"))?;
 
-    if !output.status.success() {
-        return Err("CBS catalog request failed".into());
-    }
-
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let json: serde_json::Value = serde_json::from_slice(&body)?;
     let id = json["value"][0]["Identifier"]
         .as_str()
         .ok_or("CBS catalog: missing Identifier")?
@@ -208,17 +201,10 @@ fn fetch_cbs_live(start: Instant) -> Result<Vec<u8>, Box<dyn Error>> {
 
     log_with_elapsed(start, "Downloading CBS municipality data...");
 
-    let output = std::process::Command::new("curl")
-        .arg("-sLf")
-        .arg(&url)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(format!("Failed to download CBS data from {url}").into());
-    }
+    let body = fetch(&url).map_err(|e| format!("Failed to download CBS data from {url}: {e}"))?;
 
     log_with_elapsed(start, "CBS download complete.");
-    Ok(output.stdout)
+    Ok(body)
 }
 
 fn parse_cbs_json_text(data: &str) -> Result<Vec<Municipality>, Box<dyn Error>> {
@@ -11,12 +11,12 @@ use quick_xml::{events::Event, reader::Reader};
 
 use super::xml_utils::read_simple_tag;
 
-const GWR_TAG: &[u8] = b"gwr-product:GemeenteWoonplaatsRelatie";
-const RELATED_WP_TAG: &[u8] = b"gwr-product:gerelateerdeWoonplaats";
-const RELATED_GM_TAG: &[u8] = b"gwr-product:gerelateerdeGemeente";
-const ID_TAG: &[u8] = b"gwr-product:identificatie";
-const BEGIN_VALIDITY_TAG: &[u8] = b"bagtypes:begindatumTijdvakGeldigheid";
-const END_VALIDITY_TAG: &[u8] = b"bagtypes:einddatumTijdvakGeldigheid";
+const GWR_TAG: &[u8] = b"GemeenteWoonplaatsRelatie";
+const RELATED_WP_TAG: &[u8] = b"gerelateerdeWoonplaats";
+const RELATED_GM_TAG: &[u8] = b"gerelateerdeGemeente";
+const ID_TAG: &[u8] = b"identificatie";
+const BEGIN_VALIDITY_TAG: &[u8] = b"begindatumTijdvakGeldigheid";
+const END_VALIDITY_TAG: &[u8] = b"einddatumTijdvakGeldigheid";
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct MunicipalityRelation {
@@ -43,7 +43,7 @@ pub fn parse_municipality_relations<R: BufRead>(
     loop {
         buf.clear();
         match reader.read_event_into(&mut buf)? {
-            Event::Start(e) if e.name().as_ref() == GWR_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == GWR_TAG => {
                 if let Some(relation) = parse_relation(&mut reader, &mut buf, reference_date)? {
                     by_locality.insert(relation.locality_id, relation.municipality_code);
                 }
@@ -77,20 +77,20 @@ fn parse_relation<B: BufRead>(
     loop {
         buf.clear();
         match reader.read_event_into(buf)? {
-            Event::Start(e) if e.name().as_ref() == RELATED_WP_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == RELATED_WP_TAG => {
                 locality_id = parse_nested_id(reader, RELATED_WP_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == RELATED_GM_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == RELATED_GM_TAG => {
                 municipality_code = parse_nested_id(reader, RELATED_GM_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == BEGIN_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == BEGIN_VALIDITY_TAG => {
                 begin_geldigheid = read_simple_tag(reader, BEGIN_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == END_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == END_VALIDITY_TAG => {
                 expired = true;
                 let _ = read_simple_tag(reader, END_VALIDITY_TAG, buf)?;
             }
-            Event::End(e) if e.name().as_ref() == GWR_TAG => break,
+            Event::End(e) if e.local_name().as_ref() == GWR_TAG => break,
             Event::Eof => break,
             _ => {}
         }
@@ -125,12 +125,12 @@ fn parse_nested_id<B: BufRead>(
     loop {
         buf.clear();
         match reader.read_event_into(buf)? {
-            Event::Start(e) if e.name().as_ref() == ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, ID_TAG, buf)? {
                     id = Some(value.parse().expect("Failed to parse GWR identificatie"));
                 }
             }
-            Event::End(e) if e.name().as_ref() == parent_end => break,
+            Event::End(e) if e.local_name().as_ref() == parent_end => break,
             Event::Eof => break,
             _ => {}
         }
@@ -0,0 +1,260 @@
+// Applies BAG mutation (delta) extracts to parsed data.
+// See "BAG Extract 2.0 Mutaties" in the catalog:
+// https://www.kadaster.nl/zakelijk/registraties/basisregistraties/bag/catalogus-bag
+//
+// Each mutation holds an object's voorkomen before (`was`) and after (`wordt`)
+// the change; additions only have a `wordt`. Only `wordt` voorkomens are read.
+// The latest one per object replaces the parsed record, or removes it when it
+// is ended, inactive, withdrawn or unusable.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter},
+    path::Path,
+};
+
+use quick_xml::{events::Event, reader::Reader};
+use zip::ZipArchive;
+
+use super::{
+    Address, Locality, ParseOptions, ParseReport, ParsedData, PublicSpace,
+    addresses::{NUM_TAG, parse_address},
+    extract_date_from_names,
+    localities::{WP_TAG, parse_woonplaats},
+    public_spaces::{OPR_TAG, parse_openbare_ruimte},
+    report::Outcome,
+    spool_file,
+    xml_utils::Voorkomen,
+};
+
+/// State before the mutation, which is already part of the parsed data.
+const WAS_TAG: &[u8] = b"was";
+
+/// Latest mutated voorkomen per object id.
+type Slots<T> = HashMap<u64, (u32, Outcome<T>)>;
+
+#[derive(Default)]
+struct Mutations {
+    localities: Slots<Locality>,
+    public_spaces: Slots<PublicSpace>,
+    addresses: Slots<Address>,
+    report: ParseReport,
+}
+
+impl ParsedData {
+    /// Apply a BAG mutation extract (e.g. a daily `9999MUT` delivery) to data
+    /// parsed from a full extract, so it needn't be parsed again.
+    ///
+    /// Localities, public spaces and addresses are added, replaced or removed
+    /// according to their latest voorkomen in the mutations. Address points
+    /// and municipality relations are left as they are.
+    pub fn apply_mutations(&mut self, mutation_zip: &Path) -> Result<(), Box<dyn Error>> {
+        self.apply_mutations_with_options(mutation_zip, &ParseOptions::default())
+    }
+
+    /// Like [`ParsedData::apply_mutations`], keeping the records whose
+    /// statuses `options` accepts.
+    pub fn apply_mutations_with_options(
+        &mut self,
+        mutation_zip: &Path,
+        options: &ParseOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut zip = ZipArchive::new(File::open(mutation_zip)?)?;
+        let mut names: Vec<String> = zip.file_names().map(str::to_string).collect();
+        // Later deliveries come last, so they win over earlier ones.
+        names.sort();
+
+        // The end of the mutation period; with several deliveries, the last.
+        let reference_date = names
+            .iter()
+            .filter_map(|name| extract_date_from_names([name.as_str()]))
+            .max()
+            .ok_or("Could not determine the mutation date from the extract filenames")?;
+
+        let mut mutations = Mutations::default();
+        for name in &names {
+            let mut entry = zip.by_name(name)?;
+            if name.ends_with(".xml") {
+                mutations.parse(BufReader::new(entry), &reference_date, options)?;
+            } else if name.ends_with(".zip") {
+                let mut spool = spool_file(name)?;
+                let mut writer = BufWriter::new(spool.as_file_mut());
+                io::copy(&mut entry, &mut writer)?;
+                writer.into_inner().map_err(|e| e.into_error())?;
+
+                let mut inner_zip = ZipArchive::new(File::open(spool.path())?)?;
+                let mut inner_names: Vec<String> =
+                    inner_zip.file_names().map(str::to_string).collect();
+                inner_names.sort();
+                for inner_name in inner_names.iter().filter(|name| name.ends_with(".xml")) {
+                    let inner_entry = inner_zip.by_name(inner_name)?;
+                    mutations.parse(BufReader::new(inner_entry), &reference_date, options)?;
+                }
+            }
+        }
+
+        mutations.apply_to(self);
+        Ok(())
+    }
+}
+
+impl Mutations {
+    /// Collect the `wordt` voorkomens of one mutation XML file.
+    fn parse<R: BufRead>(
+        &mut self,
+        source: R,
+        reference_date: &str,
+        options: &ParseOptions,
+    ) -> Result<(), quick_xml::Error> {
+        let mut reader = Reader::from_reader(source);
+        reader.config_mut().trim_text(true);
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) if e.local_name().as_ref() == WAS_TAG => {
+                    let end = e.to_end().into_owned();
+                    reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                }
+                Event::Start(e) if e.local_name().as_ref() == WP_TAG => {
+                    let voorkomen = parse_woonplaats(
+                        &mut reader,
+                        &mut buf,
+                        reference_date,
+                        options,
+                        &mut self.report,
+                    )?;
+                    record(&mut self.localities, voorkomen);
+                }
+                Event::Start(e) if e.local_name().as_ref() == OPR_TAG => {
+                    let voorkomen =
+                        parse_openbare_ruimte(&mut reader, &mut buf, reference_date, options)?;
+                    record(&mut self.public_spaces, voorkomen);
+                }
+                Event::Start(e) if e.local_name().as_ref() == NUM_TAG => {
+                    let voorkomen = parse_address(
+                        &mut reader,
+                        &mut buf,
+                        reference_date,
+                        options,
+                        &mut self.report,
+                    )?;
+                    record(&mut self.addresses, voorkomen);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_to(self, data: &mut ParsedData) {
+        let mut report = self.report;
+        replace(
+            &mut data.localities,
+            |locality| u64::from(locality.id),
+            self.localities,
+            &mut report,
+        );
+        data.localities.sort_by_key(|locality| locality.id);
+        replace(
+            &mut data.public_spaces,
+            |public_space| public_space.id,
+            self.public_spaces,
+            &mut report,
+        );
+        replace(
+            &mut data.addresses,
+            |address| address.id,
+            self.addresses,
+            &mut report,
+        );
+
+        data.report.merge(report);
+        data.report_dangling_refs();
+    }
+}
+
+/// Keep `voorkomen` when it is the latest of its object so far. At an equal
+/// voorkomen id the later mutation wins.
+fn record<T>(slots: &mut Slots<T>, voorkomen: Option<Voorkomen<T>>) {
+    let Some(Voorkomen {
+        id,
+        voorkomen_id,
+        outcome,
+        ..
+    }) = voorkomen
+    else {
+        return;
+    };
+    match slots.get_mut(&id) {
+        Some(slot) if voorkomen_id < slot.0 => {}
+        _ => {
+            slots.insert(id, (voorkomen_id, outcome));
+        }
+    }
+}
+
+/// Replace the `records` that were mutated by their new version, if any.
+fn replace<T>(
+    records: &mut Vec<T>,
+    id: impl Fn(&T) -> u64,
+    slots: Slots<T>,
+    report: &mut ParseReport,
+) {
+    records.retain(|record| !slots.contains_key(&id(record)));
+    for (_, outcome) in slots.into_values() {
+        match outcome {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => {}
+            Err(reason) => report.record(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Instant};
+
+    use crate::ParsedData;
+
+    #[test]
+    fn mutations_update_parsed_data() {
+        let mut data =
+            ParsedData::from_bag_zip(&PathBuf::from("test/bag.zip"), Instant::now()).unwrap();
+        data.apply_mutations(&PathBuf::from("test/mutations.zip"))
+            .unwrap();
+
+        let mut addresses: Vec<(u64, u32, &str)> = data
+            .addresses
+            .iter()
+            .map(|a| (a.id, a.house_number, a.postal_code.as_str()))
+            .collect();
+        addresses.sort();
+        // 56A is renumbered to 58, 1 is withdrawn and 3 is added.
+        assert_eq!(
+            addresses,
+            vec![(200000057534, 58, "1234AB"), (200000057536, 3, "1234AC")]
+        );
+        let renumbered = data.addresses.iter().find(|a| a.house_number == 58);
+        assert_eq!(renumbered.unwrap().house_letter, None);
+
+        let mut public_spaces: Vec<&str> = data
+            .public_spaces
+            .iter()
+            .map(|ps| ps.name.as_str())
+            .collect();
+        public_spaces.sort();
+        assert_eq!(
+            public_spaces,
+            vec!["Abel Eppensstraat", "Adamistraat", "Bergstraat"]
+        );
+
+        assert_eq!(data.localities.len(), 2);
+        assert_eq!(data.report.skipped, 0);
+    }
+}
@@ -2,28 +2,31 @@
 // BAG catalog §7.3: https://www.kadaster.nl/zakelijk/registraties/basisregistraties/bag/catalogus-bag
 //
 // An OpenbareRuimte is a public space (usually a street) within a Woonplaats.
-// Only currently valid records with status "Naamgeving uitgegeven" are included.
+// Only currently valid records whose latest voorkomen has an active naming
+// status (see `ParseOptions::naming_statuses`) are included.
 
 use std::{collections::HashMap, io::BufRead};
 
 use quick_xml::{events::Event, reader::Reader};
 
-use super::xml_utils::{
-    BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
-    VOORKOMEN_ID_TAG, VoorkomenState, read_simple_tag,
+use super::{
+    ParseOptions, ParseReport, SkipReason,
+    report::Outcome,
+    xml_utils::{
+        BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
+        VOORKOMEN_ID_TAG, Voorkomen, VoorkomenState, read_simple_tag,
+    },
 };
 
-const OPR_TAG: &[u8] = b"Objecten:OpenbareRuimte";
+pub(super) const OPR_TAG: &[u8] = b"OpenbareRuimte";
 // §7.3.1 identificatie - 16-digit national identifier
-const ID_TAG: &[u8] = b"Objecten:identificatie";
+const ID_TAG: &[u8] = b"identificatie";
 // §7.3.2 naam - official public space name (max 80 characters)
-const NAME_TAG: &[u8] = b"Objecten:naam";
+const NAME_TAG: &[u8] = b"naam";
 // §7.3.6 ligtIn - reference to the Woonplaats this public space belongs to
-const LOCALITY_REF_TAG: &[u8] = b"Objecten-ref:WoonplaatsRef";
+const LOCALITY_REF_TAG: &[u8] = b"WoonplaatsRef";
 // §7.3.4 status - lifecycle status of the public space
-const STATUS_TAG: &[u8] = b"Objecten:status";
-// Only include public spaces where a name has been officially issued
-const ISSUED_STATUS: &str = "Naamgeving uitgegeven";
+const STATUS_TAG: &[u8] = b"status";
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct PublicSpace {
@@ -35,29 +38,36 @@ pub struct PublicSpace {
 /// Parse BAG public space XML data into structured public space records.
 ///
 /// `reference_date` is the extract's standtechnische datum (YYYY-MM-DD);
-/// voorkomens with a future `beginGeldigheid` are excluded.
+/// voorkomens with a future `beginGeldigheid` are excluded. Unusable records
+/// are counted in the returned report.
 pub fn parse_public_spaces<R: BufRead>(
     source: R,
     reference_date: &str,
-) -> Result<Vec<PublicSpace>, quick_xml::Error> {
+    options: &ParseOptions,
+) -> Result<(Vec<PublicSpace>, ParseReport), quick_xml::Error> {
     let mut reader = Reader::from_reader(source);
     reader.config_mut().trim_text(true);
 
     let mut buf = Vec::new();
-    let mut by_id: HashMap<u64, (u32, PublicSpace)> = HashMap::new();
+    // Latest voorkomen per id; `Ok(None)` when it isn't an active public space.
+    let mut by_id: HashMap<u64, (u32, Outcome<PublicSpace>)> = HashMap::new();
 
     loop {
         buf.clear();
         match reader.read_event_into(&mut buf)? {
-            Event::Start(e) if e.name().as_ref() == OPR_TAG => {
-                if let Some((voorkomen_id, public_space)) =
-                    parse_openbare_ruimte(&mut reader, &mut buf, reference_date)?
+            Event::Start(e) if e.local_name().as_ref() == OPR_TAG => {
+                if let Some(Voorkomen {
+                    id,
+                    voorkomen_id,
+                    inactive: false,
+                    outcome,
+                }) = parse_openbare_ruimte(&mut reader, &mut buf, reference_date, options)?
                 {
-                    match by_id.get_mut(&public_space.id) {
-                        Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, public_space),
+                    match by_id.get_mut(&id) {
+                        Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, outcome),
                         Some(_) => {}
                         None => {
-                            by_id.insert(public_space.id, (voorkomen_id, public_space));
+                            by_id.insert(id, (voorkomen_id, outcome));
                         }
                     }
                 }
@@ -67,84 +77,141 @@ pub fn parse_public_spaces<R: BufRead>(
         }
     }
 
-    Ok(by_id.into_values().map(|(_, ps)| ps).collect())
+    let mut report = ParseReport::default();
+    let public_spaces = by_id
+        .into_values()
+        .filter_map(|(_, ps)| {
+            ps.inspect_err(|reason| report.record(*reason))
+                .ok()
+                .flatten()
+        })
+        .collect();
+    Ok((public_spaces, report))
 }
 
-fn parse_openbare_ruimte<B: BufRead>(
+/// Parse one OpenbareRuimte voorkomen. Its outcome is `Ok(None)` when the
+/// voorkomen is inactive or withdrawn and the skip reason when it is
+/// incomplete.
+pub(super) fn parse_openbare_ruimte<B: BufRead>(
     reader: &mut Reader<B>,
     buf: &mut Vec<u8>,
     reference_date: &str,
-) -> Result<Option<(u32, PublicSpace)>, quick_xml::Error> {
+    options: &ParseOptions,
+) -> Result<Option<Voorkomen<PublicSpace>>, quick_xml::Error> {
     let mut id = None;
     let mut name = None;
     let mut locality_id = None;
-    let mut issued = false;
+    let mut status = None;
+    let mut invalid = None;
     let mut state = VoorkomenState::default();
 
     loop {
         buf.clear();
         match reader.read_event_into(buf)? {
-            Event::Start(e) if e.name().as_ref() == ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, ID_TAG, buf)? {
                     id = value.parse::<u64>().ok();
                 }
             }
-            Event::Start(e) if e.name().as_ref() == NAME_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == NAME_TAG => {
                 if let Some(value) = read_simple_tag(reader, NAME_TAG, buf)? {
                     name = Some(value);
                 }
             }
-            Event::Start(e) if e.name().as_ref() == LOCALITY_REF_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == LOCALITY_REF_TAG => {
                 if let Some(value) = read_simple_tag(reader, LOCALITY_REF_TAG, buf)? {
-                    locality_id = Some(value.parse().expect("Failed to parse locality id"));
+                    match value.parse() {
+                        Ok(value) => locality_id = Some(value),
+                        Err(_) => invalid = Some(value),
+                    }
                 }
             }
-            Event::Start(e) if e.name().as_ref() == STATUS_TAG => {
-                if let Some(value) = read_simple_tag(reader, STATUS_TAG, buf)?
-                    && value == ISSUED_STATUS
-                {
-                    issued = true;
-                }
+            Event::Start(e) if e.local_name().as_ref() == STATUS_TAG => {
+                status = read_simple_tag(reader, STATUS_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == END_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == END_VALIDITY_TAG => {
                 state.eind_geldigheid = true;
                 let _ = read_simple_tag(reader, END_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == BEGIN_VALIDITY_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == BEGIN_VALIDITY_TAG => {
                 state.begin_geldigheid = read_simple_tag(reader, BEGIN_VALIDITY_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
                 state.tijdstip_inactief = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_INACTIEF_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
                 state.tijdstip_nietbag = true;
                 let _ = read_simple_tag(reader, TIJDSTIP_NIETBAG_TAG, buf)?;
             }
-            Event::Start(e) if e.name().as_ref() == VOORKOMEN_ID_TAG => {
+            Event::Start(e) if e.local_name().as_ref() == VOORKOMEN_ID_TAG => {
                 if let Some(value) = read_simple_tag(reader, VOORKOMEN_ID_TAG, buf)? {
                     state.voorkomen_id = value.parse().ok();
                 }
             }
-            Event::End(e) if e.name().as_ref() == OPR_TAG => break,
+            Event::End(e) if e.local_name().as_ref() == OPR_TAG => break,
             Event::Eof => break,
             _ => {}
         }
     }
 
-    if !issued || state.is_inactive(reference_date) {
+    let Some(id) = id else {
         return Ok(None);
-    }
+    };
+    let inactive = state.is_inactive(reference_date);
+
+    let outcome = match (invalid, name, locality_id) {
+        _ if inactive || !options.accepts_naming_status(status.as_deref()) => Ok(None),
+        (Some(_), ..) => Err(SkipReason::InvalidLocalityRef),
+        (None, None, _) => Err(SkipReason::MissingPublicSpaceName),
+        (None, _, None) => Err(SkipReason::MissingLocalityRef),
+        (None, Some(name), Some(locality_id)) => Ok(Some(PublicSpace {
+            id,
+            name,
+            locality_id,
+        })),
+    };
+    Ok(Some(Voorkomen {
+        id,
+        voorkomen_id: state.voorkomen_id.unwrap_or(0),
+        inactive,
+        outcome,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseOptions, parse_public_spaces};
+
+    #[test]
+    fn withdrawn_latest_voorkomen_drops_the_public_space() {
+        let xml = r#"<sl:bagObject>
+<Objecten:OpenbareRuimte>
+  <Objecten:identificatie domein="NL.IMBAG.Openbareruimte">0003300000116985</Objecten:identificatie>
+  <Objecten:naam>Kerkstraat</Objecten:naam>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtIn><Objecten-ref:WoonplaatsRef>3594</Objecten-ref:WoonplaatsRef></Objecten:ligtIn>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>1</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:OpenbareRuimte>
+<Objecten:OpenbareRuimte>
+  <Objecten:identificatie domein="NL.IMBAG.Openbareruimte">0003300000116985</Objecten:identificatie>
+  <Objecten:naam>Kerkstraat</Objecten:naam>
+  <Objecten:status>Naamgeving ingetrokken</Objecten:status>
+  <Objecten:ligtIn><Objecten-ref:WoonplaatsRef>3594</Objecten-ref:WoonplaatsRef></Objecten:ligtIn>
+  <Objecten:voorkomen><Historie:Voorkomen><Historie:voorkomenidentificatie>2</Historie:voorkomenidentificatie></Historie:Voorkomen></Objecten:voorkomen>
+</Objecten:OpenbareRuimte>
+<Objecten:OpenbareRuimte>
+  <Objecten:identificatie domein="NL.IMBAG.Openbareruimte">0003300000116986</Objecten:identificatie>
+  <Objecten:naam>Markt</Objecten:naam>
+  <Objecten:status>Naamgeving uitgegeven</Objecten:status>
+  <Objecten:ligtIn><Objecten-ref:WoonplaatsRef>3594</Objecten-ref:WoonplaatsRef></Objecten:ligtIn>
+</Objecten:OpenbareRuimte>
+</sl:bagObject>"#;
+
+        let (public_spaces, _) =
+            parse_public_spaces(xml.as_bytes(), "2025-12-08", &ParseOptions::default()).unwrap();
 
-    match (id, name, locality_id) {
-        (Some(id), Some(name), Some(locality_id)) => Ok(Some((
-            state.voorkomen_id.unwrap_or(0),
-            PublicSpace {
-                id,
-                name,
-                locality_id,
-            },
-        ))),
-        _ => Ok(None),
+        assert_eq!(public_spaces.len(), 1);
+        assert_eq!(public_spaces[0].name, "Markt");
     }
 }
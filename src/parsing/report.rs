@@ -0,0 +1,85 @@
+use std::{collections::HashMap, fmt};
+
+/// Why a BAG record was left out of [`ParsedData`](super::ParsedData).
+///
+/// Records dropped by design (superseded, inactive or with a status that
+/// [`ParseOptions`](super::ParseOptions) doesn't accept) are not skips; these
+/// are records that should have been kept but were unusable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// A Woonplaats identificatie that isn't a four-digit number.
+    InvalidLocalityId,
+    /// A Woonplaats without a naam.
+    MissingLocalityName,
+    /// An OpenbareRuimte without a naam.
+    MissingPublicSpaceName,
+    /// An OpenbareRuimte without a WoonplaatsRef.
+    MissingLocalityRef,
+    /// An OpenbareRuimte whose WoonplaatsRef isn't a four-digit number.
+    InvalidLocalityRef,
+    /// A Nummeraanduiding identificatie that isn't a number.
+    InvalidAddressId,
+    /// A Nummeraanduiding without a huisnummer.
+    MissingHouseNumber,
+    /// A Nummeraanduiding whose huisnummer isn't a number.
+    InvalidHouseNumber,
+    /// A Nummeraanduiding without a postcode.
+    MissingPostalCode,
+    /// A Nummeraanduiding without an OpenbareRuimteRef.
+    MissingPublicSpaceRef,
+    /// A Nummeraanduiding referring to an OpenbareRuimte that isn't in the
+    /// parsed data. The address is still parsed, but left out of the
+    /// database.
+    DanglingPublicSpaceRef,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::InvalidLocalityId => "invalid locality id",
+            SkipReason::MissingLocalityName => "locality without a name",
+            SkipReason::MissingPublicSpaceName => "public space without a name",
+            SkipReason::MissingLocalityRef => "public space without a locality",
+            SkipReason::InvalidLocalityRef => "public space with an invalid locality id",
+            SkipReason::InvalidAddressId => "invalid address id",
+            SkipReason::MissingHouseNumber => "address without a house number",
+            SkipReason::InvalidHouseNumber => "address with an invalid house number",
+            SkipReason::MissingPostalCode => "address without a postal code",
+            SkipReason::MissingPublicSpaceRef => "address without a public space",
+            SkipReason::DanglingPublicSpaceRef => "address referring to an unknown public space",
+        })
+    }
+}
+
+/// What a voorkomen of a BAG object amounts to: the record, `None` when its
+/// status drops the object, or the reason it is skipped.
+pub(crate) type Outcome<T> = Result<Option<T>, SkipReason>;
+
+/// Counts of the records skipped while parsing, by reason.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Total number of skipped records.
+    pub skipped: usize,
+    /// Number of skipped records per reason.
+    pub reasons: HashMap<SkipReason, usize>,
+}
+
+impl ParseReport {
+    /// Number of records skipped for `reason`.
+    pub fn count(&self, reason: SkipReason) -> usize {
+        self.reasons.get(&reason).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn record(&mut self, reason: SkipReason) {
+        self.skipped += 1;
+        *self.reasons.entry(reason).or_default() += 1;
+    }
+
+    pub(crate) fn merge(&mut self, other: ParseReport) {
+        self.skipped += other.skipped;
+        for (reason, count) in other.reasons {
+            *self.reasons.entry(reason).or_default() += count;
+        }
+    }
+}
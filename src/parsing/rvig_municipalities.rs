@@ -17,8 +17,10 @@
 use std::{collections::HashMap, error::Error, time::Instant};
 
 use crate::{
+    download::fetch,
     log_with_elapsed,
     parsing::municipalities::{Municipality, strip_province_suffix},
+    util::parse_csv_line,
 };
 
 static RVIG_URL: &str = "https://publicaties.rvig.nl/media/13307/download";
@@ -87,17 +89,11 @@ pub fn load_rvig_municipalities(start: Instant) -> Result<Vec<RvigMunicipality>,
 fn fetch_rvig_live(start: Instant) -> Result<Vec<u8>, Box<dyn Error>> {
     log_with_elapsed(start, "Downloading RVIG Tabel 33...");
 
-    let output = std::process::Command::new("curl")
-        .arg("-sLf")
-        .arg(RVIG_URL)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(format!("Failed to download RVIG Tabel 33 from {RVIG_URL}").into());
-    }
+    let body = fetch(RVIG_URL)
+        .map_err(|e| format!("Failed to download RVIG Tabel 33 from {RVIG_URL}: {e}"))?;
 
     log_with_elapsed(start, "RVIG download complete.");
-    Ok(output.stdout)
+    Ok(body)
 }
 
 fn rvig_match(a: &[RvigMunicipality], b: &[RvigMunicipality]) -> bool {
@@ -188,29 +184,6 @@ fn is_non_gemeente(code: &str, name: &str) -> bool {
     matches!(code, "0000" | "0997" | "0998" | "0999" | "1999") || name.contains("(Niet GBA)")
 }
 
-/// Minimal CSV line parser: double-quoted fields, `""` escapes a literal
-/// quote, comma separator outside quotes. RVIG Tabel 33 follows this shape.
-fn parse_csv_line(line: &str) -> Vec<String> {
-    let mut out = Vec::new();
-    let mut current = String::new();
-    let mut in_quotes = false;
-    let mut chars = line.chars().peekable();
-    while let Some(c) = chars.next() {
-        match (c, in_quotes) {
-            ('"', true) if chars.peek() == Some(&'"') => {
-                chars.next();
-                current.push('"');
-            }
-            ('"', true) => in_quotes = false,
-            ('"', false) => in_quotes = true,
-            (',', false) => out.push(std::mem::take(&mut current)),
-            (ch, _) => current.push(ch),
-        }
-    }
-    out.push(current);
-    out
-}
-
 /// Log important differences between CBS (primary) and RVIG (secondary) so
 /// any drift is visible when (re)building the database.
 pub fn report_differences_vs_cbs(rvig: &[RvigMunicipality], cbs: &[Municipality], start: Instant) {
@@ -277,18 +250,6 @@ mod tests {
         assert_eq!(decode_utf16_le(&bytes).unwrap(), "abc");
     }
 
-    #[test]
-    fn parse_csv_line_handles_quoted_fields() {
-        let fields = parse_csv_line(r#""0014","Groningen","","",""#);
-        assert_eq!(fields, vec!["0014", "Groningen", "", "", ""]);
-    }
-
-    #[test]
-    fn parse_csv_line_unescapes_doubled_quotes() {
-        let fields = parse_csv_line(r#""a","b""c","d""#);
-        assert_eq!(fields, vec!["a", r#"b"c"#, "d"]);
-    }
-
     #[test]
     fn parser_skips_header_historical_and_non_gemeente() {
         let csv = "\"92.10 Gemeentecode\",\"92.11 Gemeentenaam\",\"92.12 Nieuwe gemeentecode\",\"99.98 Datum ingang tabelregel\",\"99.99 Datum beëindiging tabelregel\"\n\
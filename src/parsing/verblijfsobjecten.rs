@@ -0,0 +1,226 @@
+// Parses Verblijfsobject (addressable object) geometry from the BAG extract.
+// BAG catalog: https://www.kadaster.nl/zakelijk/registraties/basisregistraties/bag/catalogus-bag
+//
+// A Verblijfsobject is the unit an address designates (a home, an office, …).
+// It carries a point geometry in Rijksdriehoek coordinates (EPSG:28992) and
+// references its Nummeraanduidingen, which is how addresses get a position.
+
+use std::{collections::HashMap, io::BufRead};
+
+use quick_xml::{events::Event, reader::Reader};
+
+use super::xml_utils::{
+    BEGIN_VALIDITY_TAG, END_VALIDITY_TAG, TIJDSTIP_INACTIEF_TAG, TIJDSTIP_NIETBAG_TAG,
+    VOORKOMEN_ID_TAG, VoorkomenState, read_simple_tag,
+};
+
+const VBO_TAG: &[u8] = b"Verblijfsobject";
+// identificatie - 16-digit national identifier
+const ID_TAG: &[u8] = b"identificatie";
+// geometrie - a point, or a surface for objects without one
+const POINT_TAG: &[u8] = b"punt";
+const POS_TAG: &[u8] = b"pos";
+// heeftAlsHoofdadres / heeftAlsNevenadres - references to the object's addresses
+const ADDRESS_REF_TAG: &[u8] = b"NummeraanduidingRef";
+
+/// The position of one address, taken from the Verblijfsobject it designates.
+#[derive(Debug, PartialEq)]
+pub struct AddressPoint {
+    /// Nummeraanduiding identificatie, matching [`Address::id`](super::Address::id).
+    pub address_id: u64,
+    /// Rijksdriehoek x coordinate in metres.
+    pub x: f64,
+    /// Rijksdriehoek y coordinate in metres.
+    pub y: f64,
+}
+
+/// Parse BAG Verblijfsobject XML data into address positions.
+///
+/// Every address of an object gets the object's point; objects with only a
+/// surface geometry are skipped. `reference_date` is the extract's
+/// standtechnische datum (YYYY-MM-DD); voorkomens with a future
+/// `beginGeldigheid` are excluded.
+pub fn parse_verblijfsobjecten<R: BufRead>(
+    source: R,
+    reference_date: &str,
+) -> Result<Vec<AddressPoint>, quick_xml::Error> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut by_id: HashMap<u64, (u32, Vec<AddressPoint>)> = HashMap::new();
+
+    loop {
+        buf.clear();
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.local_name().as_ref() == VBO_TAG => {
+                if let Some((id, voorkomen_id, points)) =
+                    parse_verblijfsobject(&mut reader, &mut buf, reference_date)?
+                {
+                    match by_id.get_mut(&id) {
+                        Some(slot) if voorkomen_id > slot.0 => *slot = (voorkomen_id, points),
+                        Some(_) => {}
+                        None => {
+                            by_id.insert(id, (voorkomen_id, points));
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(by_id.into_values().flat_map(|(_, points)| points).collect())
+}
+
+fn parse_verblijfsobject<B: BufRead>(
+    reader: &mut Reader<B>,
+    buf: &mut Vec<u8>,
+    reference_date: &str,
+) -> Result<Option<(u64, u32, Vec<AddressPoint>)>, quick_xml::Error> {
+    let mut id = None;
+    let mut address_ids = Vec::new();
+    let mut in_point = false;
+    let mut position = None;
+    let mut state = VoorkomenState::default();
+
+    loop {
+        buf.clear();
+        match reader.read_event_into(buf)? {
+            Event::Start(e) if e.local_name().as_ref() == ID_TAG => {
+                if let Some(value) = read_simple_tag(reader, ID_TAG, buf)? {
+                    id = value.parse::<u64>().ok();
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == ADDRESS_REF_TAG => {
+                if let Some(value) = read_simple_tag(reader, ADDRESS_REF_TAG, buf)?
+                    && let Ok(address_id) = value.parse::<u64>()
+                {
+                    address_ids.push(address_id);
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == POINT_TAG => in_point = true,
+            Event::End(e) if e.local_name().as_ref() == POINT_TAG => in_point = false,
+            Event::Start(e) if in_point && e.local_name().as_ref() == POS_TAG => {
+                if let Some(value) = read_simple_tag(reader, POS_TAG, buf)? {
+                    position = parse_pos(&value);
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == END_VALIDITY_TAG => {
+                state.eind_geldigheid = true;
+                let _ = read_simple_tag(reader, END_VALIDITY_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == BEGIN_VALIDITY_TAG => {
+                state.begin_geldigheid = read_simple_tag(reader, BEGIN_VALIDITY_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_INACTIEF_TAG => {
+                state.tijdstip_inactief = true;
+                let _ = read_simple_tag(reader, TIJDSTIP_INACTIEF_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == TIJDSTIP_NIETBAG_TAG => {
+                state.tijdstip_nietbag = true;
+                let _ = read_simple_tag(reader, TIJDSTIP_NIETBAG_TAG, buf)?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == VOORKOMEN_ID_TAG => {
+                if let Some(value) = read_simple_tag(reader, VOORKOMEN_ID_TAG, buf)? {
+                    state.voorkomen_id = value.parse().ok();
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == VBO_TAG => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if state.is_inactive(reference_date) {
+        return Ok(None);
+    }
+
+    match (id, position) {
+        (Some(id), Some((x, y))) if !address_ids.is_empty() => Ok(Some((
+            id,
+            state.voorkomen_id.unwrap_or(0),
+            address_ids
+                .into_iter()
+                .map(|address_id| AddressPoint { address_id, x, y })
+                .collect(),
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Parse the x and y of a `gml:pos` (`"x y"` or `"x y z"`).
+fn parse_pos(value: &str) -> Option<(f64, f64)> {
+    let mut parts = value.split_whitespace();
+    let x = parts.next()?.parse::<f64>().ok()?;
+    let y = parts.next()?.parse::<f64>().ok()?;
+    (x.is_finite() && y.is_finite()).then_some((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressPoint, parse_verblijfsobjecten};
+
+    const VBO_XML: &str = r#"<sl:bagObject>
+<Objecten:Verblijfsobject>
+  <Objecten:heeftAlsHoofdadres>
+    <Objecten-ref:NummeraanduidingRef domein="NL.IMBAG.Nummeraanduiding">0003200000133985</Objecten-ref:NummeraanduidingRef>
+  </Objecten:heeftAlsHoofdadres>
+  <Objecten:heeftAlsNevenadres>
+    <Objecten-ref:NummeraanduidingRef domein="NL.IMBAG.Nummeraanduiding">0003200000133986</Objecten-ref:NummeraanduidingRef>
+  </Objecten:heeftAlsNevenadres>
+  <Objecten:voorkomen><Historie:Voorkomen>
+    <Historie:voorkomenidentificatie>1</Historie:voorkomenidentificatie>
+    <Historie:beginGeldigheid>2018-03-26</Historie:beginGeldigheid>
+  </Historie:Voorkomen></Objecten:voorkomen>
+  <Objecten:identificatie domein="NL.IMBAG.Verblijfsobject">0003010000125985</Objecten:identificatie>
+  <Objecten:geometrie><Objecten:punt>
+    <gml:Point srsName="urn:ogc:def:crs:EPSG::28992" srsDimension="3">
+      <gml:pos>252750.0 593429.0 0.0</gml:pos>
+    </gml:Point>
+  </Objecten:punt></Objecten:geometrie>
+  <Objecten:status>Verblijfsobject in gebruik</Objecten:status>
+</Objecten:Verblijfsobject>
+<Objecten:Verblijfsobject>
+  <Objecten:heeftAlsHoofdadres>
+    <Objecten-ref:NummeraanduidingRef domein="NL.IMBAG.Nummeraanduiding">0003200000133987</Objecten-ref:NummeraanduidingRef>
+  </Objecten:heeftAlsHoofdadres>
+  <Objecten:identificatie domein="NL.IMBAG.Verblijfsobject">0003010000125986</Objecten:identificatie>
+  <Objecten:geometrie><Objecten:vlak>
+    <gml:Polygon><gml:exterior><gml:LinearRing>
+      <gml:posList>1.0 2.0 3.0 4.0</gml:posList>
+    </gml:LinearRing></gml:exterior></gml:Polygon>
+  </Objecten:vlak></Objecten:geometrie>
+</Objecten:Verblijfsobject>
+</sl:bagObject>"#;
+
+    #[test]
+    fn points_are_assigned_to_every_address_of_an_object() {
+        let mut points = parse_verblijfsobjecten(VBO_XML.as_bytes(), "2025-12-08").unwrap();
+        points.sort_by_key(|point| point.address_id);
+
+        assert_eq!(
+            points,
+            vec![
+                AddressPoint {
+                    address_id: 3200000133985,
+                    x: 252750.0,
+                    y: 593429.0,
+                },
+                AddressPoint {
+                    address_id: 3200000133986,
+                    x: 252750.0,
+                    y: 593429.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn future_objects_are_skipped() {
+        let points = parse_verblijfsobjecten(VBO_XML.as_bytes(), "2018-01-01").unwrap();
+
+        assert!(points.is_empty());
+    }
+}
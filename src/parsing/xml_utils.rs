@@ -2,6 +2,8 @@ use std::io::BufRead;
 
 use quick_xml::{escape::resolve_predefined_entity, events::Event, reader::Reader};
 
+use super::report::Outcome;
+
 /// Per-voorkomen lifecycle signals collected while streaming a BAG object.
 ///
 /// A voorkomen is outside the active lifecycle when any of these hold:
@@ -28,11 +30,24 @@ impl VoorkomenState {
     }
 }
 
-pub(crate) const END_VALIDITY_TAG: &[u8] = b"Historie:eindGeldigheid";
-pub(crate) const BEGIN_VALIDITY_TAG: &[u8] = b"Historie:beginGeldigheid";
-pub(crate) const TIJDSTIP_INACTIEF_TAG: &[u8] = b"Historie:tijdstipInactief";
-pub(crate) const TIJDSTIP_NIETBAG_TAG: &[u8] = b"Historie:tijdstipNietBAG";
-pub(crate) const VOORKOMEN_ID_TAG: &[u8] = b"Historie:voorkomenidentificatie";
+/// One voorkomen of a BAG object as read from the XML.
+pub(crate) struct Voorkomen<T> {
+    pub id: u64,
+    pub voorkomen_id: u32,
+    /// Outside the active lifecycle; see [`VoorkomenState::is_inactive`].
+    pub inactive: bool,
+    /// The record, or why the voorkomen doesn't yield one.
+    pub outcome: Outcome<T>,
+}
+
+// Tags are matched on their local name, without the namespace prefix
+// (`Historie:`, `Objecten:`, ...): Kadaster has renamed prefixes between LVBAG
+// versions, and a prefix mismatch would silently yield no records at all.
+pub(crate) const END_VALIDITY_TAG: &[u8] = b"eindGeldigheid";
+pub(crate) const BEGIN_VALIDITY_TAG: &[u8] = b"beginGeldigheid";
+pub(crate) const TIJDSTIP_INACTIEF_TAG: &[u8] = b"tijdstipInactief";
+pub(crate) const TIJDSTIP_NIETBAG_TAG: &[u8] = b"tijdstipNietBAG";
+pub(crate) const VOORKOMEN_ID_TAG: &[u8] = b"voorkomenidentificatie";
 
 /// Read the text content of an element, stopping at its end tag.
 ///
@@ -63,7 +78,7 @@ pub(crate) fn read_simple_tag<B: BufRead>(
                     content.get_or_insert_with(String::new).push_str(expanded);
                 }
             }
-            Event::End(e) if e.name().as_ref() == end => break,
+            Event::End(e) if e.local_name().as_ref() == end => break,
             Event::Eof => break,
             _ => {}
         }
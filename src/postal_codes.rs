@@ -0,0 +1,56 @@
+//! Postal codes per locality.
+//!
+//! Ranges are sorted by postal code, so finding the postal codes of one
+//! locality means scanning all of them. The index stores every distinct
+//! `(locality, postal code)` pair sorted by locality instead, so those of one
+//! locality are a single contiguous run.
+
+/// Distinct `(locality index, encoded postal code)` pairs, sorted.
+#[derive(Clone, Default)]
+pub(crate) struct LocalityPostalCodes {
+    entries: Vec<(u16, u32)>,
+}
+
+impl LocalityPostalCodes {
+    /// Index the given `(locality index, encoded postal code)` pairs.
+    pub(crate) fn build(pairs: impl Iterator<Item = (u16, u32)>) -> Self {
+        let mut entries: Vec<(u16, u32)> = pairs.collect();
+        entries.sort_unstable();
+        entries.dedup();
+        entries.shrink_to_fit();
+
+        Self { entries }
+    }
+
+    /// The encoded postal codes of the localities `first..=last`, sorted per
+    /// locality.
+    pub(crate) fn postal_codes(&self, first: u16, last: u16) -> impl Iterator<Item = u32> + '_ {
+        let start = self
+            .entries
+            .partition_point(|&(locality, _)| locality < first);
+        let end = self
+            .entries
+            .partition_point(|&(locality, _)| locality <= last);
+        self.entries[start..end]
+            .iter()
+            .map(|&(_, postal_code)| postal_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalityPostalCodes;
+
+    #[test]
+    fn postal_codes_are_grouped_per_locality() {
+        let index =
+            LocalityPostalCodes::build([(1, 30), (0, 20), (1, 10), (1, 30), (2, 5)].into_iter());
+
+        assert_eq!(index.postal_codes(1, 1).collect::<Vec<_>>(), vec![10, 30]);
+        assert_eq!(
+            index.postal_codes(0, 1).collect::<Vec<_>>(),
+            vec![20, 10, 30]
+        );
+        assert_eq!(index.postal_codes(3, 3).count(), 0);
+    }
+}
@@ -0,0 +1,131 @@
+//! Nearest-address search over the range positions.
+//!
+//! Positions are bucketed into a square grid when the index is built, so a
+//! query only measures the ranges in the cells its search radius touches.
+
+use crate::RdPoint;
+
+/// Edge of a grid cell in decimetres (250 m).
+const CELL_SIZE: u32 = 2_500;
+
+/// One positioned range.
+#[derive(Clone)]
+struct Entry {
+    cell: u32,
+    range_index: u32,
+    point: RdPoint,
+}
+
+/// Range positions sorted by grid cell.
+#[derive(Clone, Default)]
+pub(crate) struct ReverseIndex {
+    entries: Vec<Entry>,
+}
+
+impl ReverseIndex {
+    /// Index the given `(range index, position)` pairs.
+    pub(crate) fn build(points: impl Iterator<Item = (usize, RdPoint)>) -> Self {
+        let mut entries: Vec<Entry> = points
+            .filter_map(|(index, point)| {
+                Some(Entry {
+                    cell: cell_key(point.x / CELL_SIZE, point.y / CELL_SIZE),
+                    range_index: u32::try_from(index).ok()?,
+                    point,
+                })
+            })
+            .collect();
+        entries.sort_unstable_by_key(|entry| (entry.cell, entry.range_index));
+
+        Self { entries }
+    }
+
+    /// The range closest to RD position `(x, y)` (in metres) that lies within
+    /// `radius` metres, with its distance in metres.
+    pub(crate) fn nearest(&self, x: f64, y: f64, radius: f64) -> Option<(usize, f64)> {
+        if self.entries.is_empty() || !(x.is_finite() && y.is_finite() && radius >= 0.0) {
+            return None;
+        }
+
+        let (x_dm, y_dm, radius_dm) = (x * 10.0, y * 10.0, radius * 10.0);
+        let (cx0, cx1) = cell_span(x_dm, radius_dm)?;
+        let (cy0, cy1) = cell_span(y_dm, radius_dm)?;
+
+        let mut best: Option<(usize, f64)> = None;
+        for cx in cx0..=cx1 {
+            // Cells of one column are contiguous in the sorted entries.
+            let low = cell_key(cx, cy0);
+            let high = cell_key(cx, cy1);
+            let start = self.entries.partition_point(|entry| entry.cell < low);
+            let end = self.entries.partition_point(|entry| entry.cell <= high);
+
+            for entry in &self.entries[start..end] {
+                let dx = f64::from(entry.point.x) - x_dm;
+                let dy = f64::from(entry.point.y) - y_dm;
+                let distance = dx.hypot(dy) / 10.0;
+                if distance <= radius && best.is_none_or(|(_, d)| distance < d) {
+                    best = Some((entry.range_index as usize, distance));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Sort key of a cell: column-major, so each column is one contiguous run.
+fn cell_key(cx: u32, cy: u32) -> u32 {
+    (cx.min(0xffff) << 16) | cy.min(0xffff)
+}
+
+/// First and last cell index covering `center ± radius` (in decimetres), or
+/// `None` when that interval lies entirely outside the grid.
+fn cell_span(center: f64, radius: f64) -> Option<(u32, u32)> {
+    let high = center + radius;
+    if high < 0.0 {
+        return None;
+    }
+    let low = (center - radius).max(0.0);
+    let cell = |value: f64| ((value / f64::from(CELL_SIZE)) as u32).min(0xffff);
+    Some((cell(low), cell(high)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReverseIndex;
+    use crate::RdPoint;
+
+    fn index() -> ReverseIndex {
+        let at = |x, y| RdPoint { x, y };
+        ReverseIndex::build(
+            [
+                (0, at(1_000_000, 4_000_000)),
+                // 300 m east of range 0, in the next cell
+                (1, at(1_003_000, 4_000_000)),
+                (2, at(2_000_000, 5_000_000)),
+            ]
+            .into_iter(),
+        )
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_range_across_cells() {
+        let index = index();
+
+        let (range, distance) = index.nearest(100_260.0, 400_000.0, 500.0).unwrap();
+        assert_eq!(range, 1);
+        assert!((distance - 40.0).abs() < 1e-9);
+
+        let (range, distance) = index.nearest(100_000.0, 400_030.0, 500.0).unwrap();
+        assert_eq!(range, 0);
+        assert!((distance - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nearest_respects_the_radius() {
+        let index = index();
+
+        assert!(index.nearest(100_000.0, 401_000.0, 500.0).is_none());
+        assert!(index.nearest(100_000.0, 401_000.0, 1_000.0).is_some());
+        assert!(index.nearest(-5_000.0, -5_000.0, 500.0).is_none());
+        assert!(ReverseIndex::default().nearest(0.0, 0.0, 500.0).is_none());
+    }
+}
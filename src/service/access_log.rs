@@ -0,0 +1,188 @@
+//! Access log lines in the Combined Log Format used by Apache and nginx,
+//! extended with the request duration:
+//!
+//! ```text
+//! 127.0.0.1 - - [16/Oct/2026:09:12:01 +0000] "GET /lookup?pc=1234AB&n=1 HTTP/1.1" 200 45 "-" "curl/8.5.0" 3
+//! ```
+
+use std::{
+    fmt::Write,
+    net::IpAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::request::RequestHead;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Whether `BAG_ADDRESS_LOOKUP_ACCESS_LOG` asks for access log lines instead
+/// of the regular request logs.
+pub(crate) fn enabled() -> bool {
+    std::env::var("BAG_ADDRESS_LOOKUP_ACCESS_LOG")
+        .map(|v| v == "1" || v.to_lowercase() == "true")
+        .unwrap_or(false)
+}
+
+/// Print the access log line of one response to stdout.
+///
+/// `client` is the address of the client, see [`client_ip`](super::client_ip).
+///
+/// `request` is `None` when the response was sent before a request head
+/// could be read, e.g. on a timeout.
+pub(crate) fn log(
+    client: Option<IpAddr>,
+    request: Option<&RequestHead>,
+    status_code: u16,
+    bytes: usize,
+    duration_ms: Option<u128>,
+) {
+    println!(
+        "{}",
+        format_line(
+            client,
+            request,
+            status_code,
+            bytes,
+            duration_ms,
+            SystemTime::now()
+        )
+    );
+}
+
+/// Format one access log line; missing values are written as `-`.
+fn format_line(
+    client: Option<IpAddr>,
+    request: Option<&RequestHead>,
+    status_code: u16,
+    bytes: usize,
+    duration_ms: Option<u128>,
+    time: SystemTime,
+) -> String {
+    let host = client.map_or_else(|| "-".to_string(), |client| client.to_string());
+    let request_line = request.map_or_else(
+        || "-".to_string(),
+        |request| {
+            escape(&format!(
+                "{} {} {}",
+                request.method, request.target, request.version
+            ))
+        },
+    );
+    let header = |name| {
+        request
+            .and_then(|request| request.header(name))
+            .map_or_else(|| "-".to_string(), escape)
+    };
+    let bytes = if bytes == 0 {
+        "-".to_string()
+    } else {
+        bytes.to_string()
+    };
+    let duration = duration_ms.map_or_else(|| "-".to_string(), |ms| ms.to_string());
+
+    format!(
+        "{host} - - [{}] \"{request_line}\" {status_code} {bytes} \"{}\" \"{}\" {duration}",
+        clf_time(time),
+        header("referer"),
+        header("user-agent"),
+    )
+}
+
+/// Escape quotes, backslashes and control characters the way nginx does, so
+/// a client can't break up the line.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '"' || c == '\\' || c.is_control() {
+            for byte in c.to_string().bytes() {
+                let _ = write!(escaped, "\\x{byte:02X}");
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Format a time as `dd/Mon/yyyy:hh:mm:ss +0000` (UTC).
+fn clf_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{day:02}/{}/{year}:{:02}:{:02}:{:02} +0000",
+        MONTHS[month as usize - 1],
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// Proleptic Gregorian (year, month, day) of a day count since 1970-01-01,
+/// after Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clf_time, format_line};
+    use crate::service::request::RequestHead;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn times_are_formatted_in_utc() {
+        assert_eq!(clf_time(UNIX_EPOCH), "01/Jan/1970:00:00:00 +0000");
+        // 2024-02-29 13:55:36 UTC
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_214_936);
+        assert_eq!(clf_time(leap_day), "29/Feb/2024:13:55:36 +0000");
+    }
+
+    #[test]
+    fn lines_follow_the_combined_log_format() {
+        let request = RequestHead::parse(
+            b"GET /lookup?pc=1234AB&n=10 HTTP/1.1\r\nUser-Agent: curl/8.5.0\r\n\r\n",
+        );
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_214_936);
+
+        let line = format_line(
+            Some("192.0.2.7".parse().unwrap()),
+            Some(&request),
+            200,
+            45,
+            Some(3),
+            time,
+        );
+        assert_eq!(
+            line,
+            "192.0.2.7 - - [29/Feb/2024:13:55:36 +0000] \"GET /lookup?pc=1234AB&n=10 HTTP/1.1\" 200 45 \"-\" \"curl/8.5.0\" 3"
+        );
+
+        let line = format_line(None, None, 408, 0, None, time);
+        assert_eq!(
+            line,
+            "- - - [29/Feb/2024:13:55:36 +0000] \"-\" 408 - \"-\" \"-\" -"
+        );
+    }
+
+    #[test]
+    fn quotes_in_requests_are_escaped() {
+        let request = RequestHead::parse(b"GET /\"x HTTP/1.1\r\nUser-Agent: a\"b\r\n\r\n");
+        let line = format_line(None, Some(&request), 404, 10, Some(0), UNIX_EPOCH);
+        assert!(line.contains("\"GET /\\x22x HTTP/1.1\""));
+        assert!(line.contains("\"a\\x22b\""));
+    }
+}
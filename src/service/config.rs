@@ -0,0 +1,132 @@
+//! Settings of the web service.
+
+use std::{path::PathBuf, time::Duration};
+
+use crate::suggest::{DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD, ScoringConfig};
+
+use super::{
+    DEFAULT_CORS_ORIGIN, DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_REQUEST_BYTES,
+    DEFAULT_REQUEST_TIMEOUT, DEFAULT_SHUTDOWN_GRACE, access_log, database_path, max_request_bytes,
+    metrics, reverse, shutdown_grace, suggest, suggest_cache, trusted_proxies,
+};
+
+/// Settings of a server started with
+/// [`serve_with_config`](super::serve_with_config).
+///
+/// [`ServerConfig::default`] holds the built-in defaults, and
+/// [`ServerConfig::from_env`] the settings the other `serve` functions use.
+/// Override single settings with e.g.
+/// `ServerConfig { quiet: true, ..ServerConfig::from_env() }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    /// Only log errors (`BAG_ADDRESS_LOOKUP_QUIET`). Has no effect when the
+    /// application installed a `tracing` subscriber itself.
+    pub quiet: bool,
+    /// Minimum fuzzy match score of `/suggest` results, unless a request
+    /// asks for another (`BAG_ADDRESS_LOOKUP_SUGGEST_THRESHOLD`).
+    pub suggest_threshold: f32,
+    /// Number of `/suggest` results, unless a request asks for another.
+    pub suggest_limit: usize,
+    /// Scoring of `/suggest` results (`BAG_ADDRESS_LOOKUP_SUGGEST_WEIGHTS`
+    /// and friends).
+    pub suggest_scoring: ScoringConfig,
+    /// Connections served at the same time
+    /// (`BAG_ADDRESS_LOOKUP_MAX_CONNECTIONS`).
+    pub max_connections: usize,
+    /// Time allowed for reading, handling and answering one request
+    /// (`BAG_ADDRESS_LOOKUP_REQUEST_TIMEOUT`).
+    pub timeout: Duration,
+    /// `Access-Control-Allow-Origin` of every response
+    /// (`BAG_ADDRESS_LOOKUP_CORS_ORIGIN`).
+    pub cors: String,
+    /// Database file to serve and to reload on SIGHUP; the embedded database
+    /// when `None` (`BAG_ADDRESS_LOOKUP_DATABASE`).
+    pub database: Option<PathBuf>,
+    /// Time open connections get to finish after shutdown is requested
+    /// (`BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE`).
+    pub shutdown_grace: Duration,
+    /// Reverse proxies in front of the server; with one or more the client
+    /// address is read from their forwarding headers
+    /// (`BAG_ADDRESS_LOOKUP_TRUST_PROXY`).
+    pub trusted_proxies: usize,
+    /// Log one access log line per response instead of the request logs
+    /// (`BAG_ADDRESS_LOOKUP_ACCESS_LOG`).
+    pub access_log: bool,
+    /// Largest request head, in bytes; larger ones are answered with `431`
+    /// (`BAG_ADDRESS_LOOKUP_MAX_HEADER_BYTES`).
+    pub max_request_bytes: usize,
+    /// Largest `/reverse` search radius, in metres
+    /// (`BAG_ADDRESS_LOOKUP_REVERSE_RADIUS`).
+    pub reverse_radius: f64,
+    /// Prefix of the `/metrics` metric names; must be a valid Prometheus
+    /// metric name (`BAG_ADDRESS_LOOKUP_METRICS_PREFIX`).
+    pub metrics_prefix: String,
+    /// Number of recent `/suggest` results kept; `0` disables the cache
+    /// (`BAG_ADDRESS_LOOKUP_SUGGEST_CACHE_SIZE`).
+    pub suggest_cache_size: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            quiet: false,
+            suggest_threshold: DEFAULT_SUGGEST_THRESHOLD,
+            suggest_limit: DEFAULT_SUGGEST_LIMIT,
+            suggest_scoring: ScoringConfig::default(),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            cors: DEFAULT_CORS_ORIGIN.to_string(),
+            database: None,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            trusted_proxies: 0,
+            access_log: false,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            reverse_radius: reverse::DEFAULT_REVERSE_RADIUS,
+            metrics_prefix: metrics::DEFAULT_METRICS_PREFIX.to_string(),
+            suggest_cache_size: suggest_cache::DEFAULT_SUGGEST_CACHE_SIZE,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read the settings from the `BAG_ADDRESS_LOOKUP_*` environment
+    /// variables, keeping the default of those unset or invalid.
+    pub fn from_env() -> Self {
+        let quiet = std::env::var("BAG_ADDRESS_LOOKUP_QUIET")
+            .map(|v| v == "1" || v.to_lowercase() == "true")
+            .unwrap_or(false);
+        let max_connections = std::env::var("BAG_ADDRESS_LOOKUP_MAX_CONNECTIONS")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        let timeout = std::env::var("BAG_ADDRESS_LOOKUP_REQUEST_TIMEOUT")
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|value| *value > 0)
+            .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_secs);
+        let cors = std::env::var("BAG_ADDRESS_LOOKUP_CORS_ORIGIN")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| DEFAULT_CORS_ORIGIN.to_string());
+
+        Self {
+            quiet,
+            suggest_threshold: suggest::suggest_threshold(),
+            suggest_limit: DEFAULT_SUGGEST_LIMIT,
+            suggest_scoring: suggest::scoring_config(),
+            max_connections,
+            timeout,
+            cors,
+            database: database_path(),
+            shutdown_grace: shutdown_grace(),
+            trusted_proxies: trusted_proxies(),
+            access_log: access_log::enabled(),
+            max_request_bytes: max_request_bytes(),
+            reverse_radius: reverse::reverse_radius(),
+            metrics_prefix: metrics::metrics_prefix(),
+            suggest_cache_size: suggest_cache::suggest_cache_size(),
+        }
+    }
+}
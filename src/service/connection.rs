@@ -0,0 +1,152 @@
+//! The streams requests are served over.
+//!
+//! Requests are read from and answered on any [`Connection`]. A [`Listener`]
+//! accepts streams, and a [`Transport`] turns an accepted stream into a
+//! connection, e.g. by doing a TLS handshake.
+
+use std::{
+    future::Future,
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+    task::Poll,
+};
+
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+};
+
+/// A client connection: a byte stream and, when known, the client address.
+pub(crate) trait Connection: AsyncRead + AsyncWrite + Unpin + Send {
+    /// Address of the client, for the access log.
+    fn peer(&self) -> Option<SocketAddr>;
+}
+
+impl Connection for TcpStream {
+    fn peer(&self) -> Option<SocketAddr> {
+        TcpStream::peer_addr(self).ok()
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    /// Unix socket clients have no IP address.
+    fn peer(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// A bound socket connections are accepted on.
+pub(crate) trait Listener: Send + 'static {
+    type Stream: Connection + 'static;
+
+    /// Wait for the next connection.
+    fn accept(&self) -> impl Future<Output = std::io::Result<Self::Stream>> + Send;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    async fn accept(&self) -> std::io::Result<TcpStream> {
+        let (stream, _) = TcpListener::accept(self).await?;
+        Ok(stream)
+    }
+}
+
+/// Several bound sockets, e.g. for both IPv4 and IPv6.
+pub(crate) struct Listeners {
+    listeners: Vec<TcpListener>,
+    /// The listener the next accept polls first.
+    next: AtomicUsize,
+}
+
+impl From<Vec<TcpListener>> for Listeners {
+    fn from(listeners: Vec<TcpListener>) -> Self {
+        Self {
+            listeners,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Accepts on whichever listener has a connection first. Every accept starts
+/// at the next listener, so a busy one cannot starve the others.
+impl Listener for Listeners {
+    type Stream = TcpStream;
+
+    async fn accept(&self) -> std::io::Result<TcpStream> {
+        let count = self.listeners.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        std::future::poll_fn(|cx| {
+            for offset in 0..count {
+                let listener = &self.listeners[(start + offset) % count];
+                if let Poll::Ready(accepted) = listener.poll_accept(cx) {
+                    return Poll::Ready(accepted.map(|(stream, _)| stream));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    async fn accept(&self) -> std::io::Result<UnixStream> {
+        let (stream, _) = UnixListener::accept(self).await?;
+        Ok(stream)
+    }
+}
+
+/// Prepares an accepted stream `S` for serving requests.
+pub(crate) trait Transport<S>: Clone + Send + Sync + 'static {
+    type Stream: Connection + 'static;
+
+    /// Open the connection; fails when e.g. the TLS handshake does.
+    fn open(&self, stream: S) -> impl Future<Output = std::io::Result<Self::Stream>> + Send;
+}
+
+/// Serves requests over the accepted stream as is.
+#[derive(Clone, Copy)]
+pub(crate) struct Plain;
+
+impl<S: Connection + 'static> Transport<S> for Plain {
+    type Stream = S;
+
+    async fn open(&self, stream: S) -> std::io::Result<S> {
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Listener, Listeners};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn busy_listeners_do_not_starve_the_others() {
+        let busy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let quiet = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (busy_addr, quiet_addr) = (busy.local_addr().unwrap(), quiet.local_addr().unwrap());
+        let listeners = Listeners::from(vec![busy, quiet]);
+
+        // Both listeners have clients waiting, the first one more.
+        let _clients = [
+            TcpStream::connect(busy_addr).await.unwrap(),
+            TcpStream::connect(busy_addr).await.unwrap(),
+            TcpStream::connect(quiet_addr).await.unwrap(),
+        ];
+        let first = listeners.accept().await.unwrap();
+        let second = listeners.accept().await.unwrap();
+
+        let mut accepted_on = [first.local_addr().unwrap(), second.local_addr().unwrap()];
+        accepted_on.sort();
+        let mut expected = [busy_addr, quiet_addr];
+        expected.sort();
+        assert_eq!(accepted_on, expected);
+    }
+}
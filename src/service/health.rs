@@ -0,0 +1,90 @@
+use serde::Serialize;
+
+use crate::database::DatabaseHandle;
+
+use super::Response;
+
+/// JSON body of the `/health` endpoint.
+#[derive(Serialize)]
+struct Health {
+    status: &'static str,
+    ranges: usize,
+    localities: usize,
+}
+
+/// Handle the `/health` endpoint: report whether the database is loaded.
+///
+/// Responds with 503 when the database holds no address ranges, so a load
+/// balancer takes the instance out of rotation.
+pub(crate) fn handle_health(database: &DatabaseHandle) -> Response {
+    let ranges = database.range_count();
+    let (status_code, status) = if ranges == 0 {
+        (503, "unavailable")
+    } else {
+        (200, "ok")
+    };
+
+    let body = serde_json::to_string(&Health {
+        status,
+        ranges,
+        localities: database.locality_count(),
+    })
+    .expect("serialize health");
+    Response::new(status_code, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{send_request, test_database};
+    use crate::{Database, DatabaseHandle};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn health_reports_counts() {
+        let db = Arc::new(test_database());
+        let response = send_request("GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("{\"status\":\"ok\",\"ranges\":1,\"localities\":4}"));
+    }
+
+    #[tokio::test]
+    async fn small_body_is_not_compressed() {
+        let db = Arc::new(test_database());
+        let response =
+            send_request("GET /health HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(!response.contains("Content-Encoding"));
+        assert!(response.ends_with("{\"status\":\"ok\",\"ranges\":1,\"localities\":4}"));
+    }
+
+    #[tokio::test]
+    async fn health_empty_database_is_unavailable() {
+        let db = Arc::new(DatabaseHandle::Decoded(Database {
+            localities: Vec::new(),
+            locality_codes: Vec::new(),
+            public_spaces: Vec::new(),
+            ranges: Vec::new(),
+            municipalities: Vec::new(),
+            provinces: Vec::new(),
+            municipality_codes: Vec::new(),
+            locality_municipality: Vec::new(),
+            municipality_province: Vec::new(),
+            locality_had_suffix: Vec::new(),
+            municipality_had_suffix: Vec::new(),
+            house_number_suffixes: Vec::new(),
+            range_points: Vec::new(),
+            checksum: None,
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
+        }));
+        let response = send_request("GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        assert!(response.contains("{\"status\":\"unavailable\",\"ranges\":0,\"localities\":0}"));
+    }
+}
@@ -37,8 +37,9 @@ pub(crate) fn handle_localities(database: &DatabaseHandle) -> Response {
 
 #[cfg(test)]
 mod tests {
-    use super::super::test_utils::{send_request, test_database};
-    use std::sync::Arc;
+    use super::super::test_utils::{send_request, send_request_bytes, test_database};
+    use flate2::read::GzDecoder;
+    use std::{io::Read, sync::Arc};
 
     #[tokio::test]
     async fn localities_returns_list() {
@@ -55,4 +56,32 @@ mod tests {
         assert!(response.contains("\"unique\":"));
         assert!(response.contains("\"had_suffix\":"));
     }
+
+    #[tokio::test]
+    async fn localities_gzip_when_accepted() {
+        let db = Arc::new(test_database());
+        let plain = send_request(
+            "GET /localities HTTP/1.1\r\nConnection: close\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        let plain_body = plain.split_once("\r\n\r\n").unwrap().1;
+
+        let response = send_request_bytes(
+            "GET /localities HTTP/1.1\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n",
+            db,
+        )
+        .await;
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = std::str::from_utf8(&response[..split]).unwrap();
+        let body = &response[split + 4..];
+
+        assert!(head.contains("Content-Encoding: gzip\r\n"));
+        assert!(head.contains("Vary: Accept-Encoding\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+
+        let mut decoded = String::new();
+        GzDecoder::new(body).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, plain_body);
+    }
 }
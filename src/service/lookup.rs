@@ -1,42 +1,294 @@
-use crate::database::DatabaseHandle;
+use serde::Deserialize;
 
-use super::{Response, json_error, json_ok, query::parse_query};
+use crate::database::{
+    AddressResult, DatabaseHandle, HouseNumberSuffix, encode_house_number_suffix,
+};
+
+use super::{
+    ApiError, Response, address_json, json_ok,
+    query::{check_strict, parse_query},
+};
+
+/// Maximum number of entries accepted by `POST /lookup/batch`.
+const MAX_BATCH_SIZE: usize = 1000;
+
+/// Maximum number of house numbers in a comma-separated `n` of `GET /lookup`.
+const MAX_HOUSE_NUMBERS: usize = 50;
+
+/// Maximum number of house numbers spanned by a range `n` of `GET /lookup`.
+const MAX_HOUSE_NUMBER_SPAN: u32 = 500;
+
+/// Lookup parameters: `pc` (postal code), `n` (house number) and optionally
+/// `hl` (house letter), `toev` (house number addition) and `geo` (include
+/// `lat` / `lon`).
+///
+/// This is also the JSON body accepted by `POST /lookup` and the shape of
+/// each entry of `POST /lookup/batch`.
+#[derive(Default, Deserialize)]
+struct LookupRequest {
+    pc: Option<String>,
+    n: Option<u32>,
+    hl: Option<String>,
+    toev: Option<String>,
+    #[serde(default)]
+    geo: bool,
+}
+
+/// Query keys understood by `GET /lookup`.
+pub(super) const QUERY_KEYS: &[&str] = &["pc", "n", "hl", "toev", "geo"];
 
 /// Handle the `/lookup` endpoint using `pc` (postal code) and `n` (house number).
+///
+/// `n` may also be a comma-separated list like `10,12,14`, answered by
+/// [`lookup_list_response`], or a range like `10-20`, answered by
+/// [`lookup_range_response`].
 pub(crate) fn handle_lookup(database: &DatabaseHandle, query: &str) -> Response {
-    let mut postal_code = None;
-    let mut house_number = None;
+    let mut request = LookupRequest::default();
+    let mut house_numbers = None;
+
+    let Ok(pairs) = parse_query(query) else {
+        return ApiError::InvalidQueryEncoding.into();
+    };
+
+    if pairs.is_empty() {
+        return ApiError::EmptyQuery.into();
+    }
+
+    if let Err(error) = check_strict(&pairs, QUERY_KEYS) {
+        return error.into();
+    }
 
-    for (key, value) in parse_query(query) {
+    for (key, value) in pairs {
         match key.as_str() {
-            "pc" => postal_code = Some(value),
-            "n" => house_number = value.parse::<u32>().ok(),
+            "pc" => request.pc = Some(value),
+            "n" => house_numbers = Some(value),
+            "hl" => request.hl = Some(value),
+            "toev" => request.toev = Some(value),
+            "geo" => request.geo = matches!(value.as_str(), "1" | "true"),
             _ => {}
         }
     }
 
-    let Some(postal_code) = postal_code else {
-        return Response::new(400, json_error("missing postal_code"));
+    match house_numbers {
+        Some(list) if list.contains(',') => lookup_list_response(database, &request, &list),
+        Some(range) if range.contains('-') => lookup_range_response(database, &request, &range),
+        house_number => {
+            request.n = house_number.and_then(|value| value.parse::<u32>().ok());
+            lookup_response(database, &request)
+        }
+    }
+}
+
+/// Handle `POST /lookup` with a JSON body like `{"pc":"1234AB","n":11}`.
+pub(crate) fn handle_lookup_body(database: &DatabaseHandle, body: &[u8]) -> Response {
+    let Ok(request) = serde_json::from_slice::<LookupRequest>(body) else {
+        return ApiError::InvalidJsonBody.into();
+    };
+
+    lookup_response(database, &request)
+}
+
+/// Handle `POST /lookup/batch` with a JSON array of `{"pc":…,"n":…}` objects.
+///
+/// Responds with an array aligned by index with the request: each entry is a
+/// `{"pr":…,"wp":…}` object, or `null` when the entry is invalid or not found.
+pub(crate) fn handle_lookup_batch(database: &DatabaseHandle, body: &[u8]) -> Response {
+    let Ok(requests) = serde_json::from_slice::<Vec<LookupRequest>>(body) else {
+        return ApiError::InvalidJsonBody.into();
+    };
+
+    if requests.len() > MAX_BATCH_SIZE {
+        return ApiError::BatchTooLarge.into();
+    }
+
+    let results: Vec<_> = requests
+        .iter()
+        .map(|request| {
+            let postal_code = request
+                .pc
+                .as_deref()
+                .filter(|pc| is_valid_postal_code(pc))?;
+            let (address, suffix) = find_address(database, request, postal_code, request.n?)?;
+            Some(address_json(&address, suffix.as_ref(), request.geo))
+        })
+        .collect();
+
+    Response::new(
+        200,
+        serde_json::to_string(&results).expect("serialize batch response"),
+    )
+}
+
+/// Validate the lookup parameters and resolve the address.
+fn lookup_response(database: &DatabaseHandle, request: &LookupRequest) -> Response {
+    let Some(postal_code) = request.pc.as_deref() else {
+        return ApiError::MissingPostalCode.into();
     };
 
-    let Some(house_number) = house_number else {
-        return Response::new(400, json_error("missing house_number"));
+    let Some(house_number) = request.n else {
+        return ApiError::MissingHouseNumber.into();
     };
 
-    if !is_valid_postal_code(&postal_code) {
-        return Response::new(400, json_error("invalid postal_code"));
+    if let Err(error) = validate(request, postal_code) {
+        return error.into();
     }
 
-    match database.lookup(&postal_code, house_number) {
-        Some((public_space, locality)) => {
-            let body = json_ok(public_space, locality);
+    match find_address(database, request, postal_code, house_number) {
+        Some((address, suffix)) => {
+            let body = json_ok(&address, suffix.as_ref(), request.geo);
             Response::new(200, body)
         }
-        None => Response::new(404, json_error("address not found")),
+        None => not_found(database, postal_code, house_number).into(),
+    }
+}
+
+/// Why no address was found: the postal code is unknown, the house number is
+/// unknown under it, or the house letter / addition don't match.
+fn not_found(database: &DatabaseHandle, postal_code: &str, house_number: u32) -> ApiError {
+    if !database.has_postal_code(postal_code) {
+        ApiError::PostalCodeNotFound
+    } else if database
+        .lookup_detailed(postal_code, house_number)
+        .is_none()
+    {
+        ApiError::HouseNumberNotFound
+    } else {
+        ApiError::AddressNotFound
     }
 }
 
-/// Validate Dutch postal code format: 4 digits + 2 uppercase letters.
+/// Look up each house number of a comma-separated `list` under the same
+/// postal code.
+///
+/// Responds with an object mapping each house number to a `{"pr":…,"wp":…}`
+/// object, or `null` when that address is not found.
+fn lookup_list_response(
+    database: &DatabaseHandle,
+    request: &LookupRequest,
+    list: &str,
+) -> Response {
+    let Some(postal_code) = request.pc.as_deref() else {
+        return ApiError::MissingPostalCode.into();
+    };
+
+    let Ok(house_numbers) = list
+        .split(',')
+        .map(|value| value.parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return ApiError::InvalidHouseNumber.into();
+    };
+
+    if house_numbers.len() > MAX_HOUSE_NUMBERS {
+        return ApiError::TooManyHouseNumbers.into();
+    }
+
+    if let Err(error) = validate(request, postal_code) {
+        return error.into();
+    }
+
+    let results: serde_json::Map<String, serde_json::Value> = house_numbers
+        .into_iter()
+        .map(|house_number| {
+            let result = find_address(database, request, postal_code, house_number)
+                .map(|(address, suffix)| address_json(&address, suffix.as_ref(), request.geo))
+                .unwrap_or_default();
+            (house_number.to_string(), result)
+        })
+        .collect();
+
+    Response::new(
+        200,
+        serde_json::to_string(&results).expect("serialize lookup list response"),
+    )
+}
+
+/// Look up every house number of a `range` like `10-20` (both ends included)
+/// under the same postal code.
+///
+/// Responds with an array holding a `{"n":…,"pr":…,"wp":…}` object for each
+/// house number that is found, in increasing order.
+fn lookup_range_response(
+    database: &DatabaseHandle,
+    request: &LookupRequest,
+    range: &str,
+) -> Response {
+    let Some(postal_code) = request.pc.as_deref() else {
+        return ApiError::MissingPostalCode.into();
+    };
+
+    let Some((start, end)) = range
+        .split_once('-')
+        .and_then(|(start, end)| Some((start.parse::<u32>().ok()?, end.parse::<u32>().ok()?)))
+        .filter(|(start, end)| start <= end)
+    else {
+        return ApiError::InvalidHouseNumberRange.into();
+    };
+
+    if end - start >= MAX_HOUSE_NUMBER_SPAN {
+        return ApiError::TooManyHouseNumbers.into();
+    }
+
+    if let Err(error) = validate(request, postal_code) {
+        return error.into();
+    }
+
+    let hits: Vec<_> = (start..=end)
+        .filter_map(|house_number| {
+            let (address, suffix) = find_address(database, request, postal_code, house_number)?;
+            let mut hit = address_json(&address, suffix.as_ref(), request.geo);
+            hit["n"] = house_number.into();
+            Some(hit)
+        })
+        .collect();
+
+    Response::new(
+        200,
+        serde_json::to_string(&hits).expect("serialize lookup range response"),
+    )
+}
+
+/// Check the postal code, house letter and addition of a lookup.
+fn validate(request: &LookupRequest, postal_code: &str) -> Result<(), ApiError> {
+    if !is_valid_postal_code(postal_code) {
+        return Err(ApiError::InvalidPostalCode);
+    }
+
+    if encode_house_number_suffix(request.hl.as_deref(), None).is_none() {
+        return Err(ApiError::InvalidHouseLetter);
+    }
+
+    if encode_house_number_suffix(None, request.toev.as_deref()).is_none() {
+        return Err(ApiError::InvalidHouseNumberAddition);
+    }
+
+    Ok(())
+}
+
+/// Resolve an address. When the request names a house letter or addition,
+/// the address must exist with exactly that letter and addition, and the
+/// matching record is returned alongside the address.
+fn find_address<'a>(
+    database: &'a DatabaseHandle,
+    request: &LookupRequest,
+    postal_code: &str,
+    house_number: u32,
+) -> Option<(AddressResult<'a>, Option<HouseNumberSuffix>)> {
+    let address = database.lookup_detailed(postal_code, house_number)?;
+
+    let letter = request.hl.as_deref().filter(|value| !value.is_empty());
+    let addition = request.toev.as_deref().filter(|value| !value.is_empty());
+    if letter.is_none() && addition.is_none() {
+        return Some((address, None));
+    }
+
+    let suffix = database.house_number_suffix(postal_code, house_number, letter, addition)?;
+    Some((address, Some(suffix)))
+}
+
+/// Validate Dutch postal code format: 4 digits + 2 letters.
+///
+/// The letters may be lowercase; the database lookup uppercases them.
 fn is_valid_postal_code(value: &str) -> bool {
     let bytes = value.as_bytes();
     if bytes.len() != 6 {
@@ -45,7 +297,7 @@ fn is_valid_postal_code(value: &str) -> bool {
     if !bytes[..4].iter().all(|b| b.is_ascii_digit()) {
         return false;
     }
-    bytes[4].is_ascii_uppercase() && bytes[5].is_ascii_uppercase()
+    bytes[4].is_ascii_alphabetic() && bytes[5].is_ascii_alphabetic()
 }
 
 #[cfg(test)]
@@ -63,7 +315,120 @@ mod tests {
         .await;
 
         assert!(response.starts_with("HTTP/1.1 200 OK"));
-        assert!(response.contains("{\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_with_geo_returns_coordinates() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=11&geo=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"lat\":52.37"));
+        assert!(response.contains("\"lon\":4.89"));
+
+        // Without geo=1 the coordinates are left out.
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(!response.contains("\"lat\""));
+    }
+
+    #[tokio::test]
+    async fn lookup_decodes_percent_encoded_values() {
+        // `%41` is `A` and `%20` a trailing space that the postal code
+        // validation rejects, so only the first request succeeds.
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234%41B&n=%311 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+
+        let response = send_request(
+            "GET /lookup?pc=1234AB%20&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(
+            response
+                .contains("{\"code\":\"INVALID_POSTAL_CODE\",\"error\":\"invalid postal_code\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_invalid_percent_escape() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=1%G1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains(
+            "{\"code\":\"INVALID_QUERY_ENCODING\",\"error\":\"invalid query encoding\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn lookup_empty_query() {
+        let db = Arc::new(test_database());
+        for target in ["/lookup", "/lookup?"] {
+            let response = send_request(
+                &format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+                db.clone(),
+            )
+            .await;
+
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+            assert!(
+                response.contains(
+                    "{\"code\":\"EMPTY_QUERY\",\"error\":\"no query parameters provided\"}"
+                )
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn lookup_strict_mode_rejects_unknown_parameters() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pcode=1234AB&n=11&strict=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains(
+            "{\"code\":\"UNKNOWN_PARAMETERS\",\"error\":\"unknown query parameters: pcode\"}"
+        ));
+
+        // Without strict mode the typo is ignored and `pc` is missing.
+        let response = send_request(
+            "GET /lookup?pcode=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.contains("\"code\":\"MISSING_POSTAL_CODE\""));
     }
 
     #[tokio::test]
@@ -73,7 +438,10 @@ mod tests {
             send_request("GET /lookup?n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
 
         assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
-        assert!(response.contains("{\"error\":\"missing postal_code\"}"));
+        assert!(
+            response
+                .contains("{\"code\":\"MISSING_POSTAL_CODE\",\"error\":\"missing postal_code\"}")
+        );
     }
 
     #[tokio::test]
@@ -86,20 +454,54 @@ mod tests {
         .await;
 
         assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
-        assert!(response.contains("{\"error\":\"missing house_number\"}"));
+        assert!(
+            response
+                .contains("{\"code\":\"MISSING_HOUSE_NUMBER\",\"error\":\"missing house_number\"}")
+        );
     }
 
     #[tokio::test]
     async fn lookup_invalid_postal_code() {
         let db = Arc::new(test_database());
         let response = send_request(
-            "GET /lookup?pc=1234ab&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            "GET /lookup?pc=1234a1&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
             db,
         )
         .await;
 
         assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
-        assert!(response.contains("{\"error\":\"invalid postal_code\"}"));
+        assert!(
+            response
+                .contains("{\"code\":\"INVALID_POSTAL_CODE\",\"error\":\"invalid postal_code\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_accepts_lowercase_postal_code() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234ab&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+
+        let body = r#"[{"pc":"1234aB","n":10}]"#;
+        let request = format!(
+            "POST /lookup/batch HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+        assert!(
+            response.ends_with(
+                "[{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}]"
+            )
+        );
     }
 
     #[tokio::test]
@@ -112,20 +514,318 @@ mod tests {
         .await;
 
         assert!(response.starts_with("HTTP/1.1 404 Not Found"));
-        assert!(response.contains("{\"error\":\"address not found\"}"));
+        assert!(
+            response.contains(
+                "{\"code\":\"POSTAL_CODE_NOT_FOUND\",\"error\":\"postal code not found\"}"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_of_an_unknown_house_number_names_it() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=999 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(response.contains(
+            "{\"code\":\"HOUSE_NUMBER_NOT_FOUND\",\"error\":\"house number not found for postal code\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn lookup_accepts_a_list_of_house_numbers() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10,99,11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(
+            "{\"10\":{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"},\"11\":{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"},\"99\":null}"
+        ));
+
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10,x HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("\"code\":\"INVALID_HOUSE_NUMBER\""));
+
+        let list = vec!["1"; 51].join(",");
+        let response = send_request(
+            &format!("GET /lookup?pc=1234AB&n={list} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("\"code\":\"TOO_MANY_HOUSE_NUMBERS\""));
+    }
+
+    #[tokio::test]
+    async fn lookup_accepts_a_range_of_house_numbers() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=9-11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(
+            "[{\"gm\":\"Amsterdam\",\"n\":10,\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"},{\"gm\":\"Amsterdam\",\"n\":11,\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}]"
+        ));
+
+        for range in ["20-10", "10-", "a-b"] {
+            let response = send_request(
+                &format!("GET /lookup?pc=1234AB&n={range} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+                db.clone(),
+            )
+            .await;
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+            assert!(response.contains("\"code\":\"INVALID_HOUSE_NUMBER_RANGE\""));
+        }
+
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=1-501 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("\"code\":\"TOO_MANY_HOUSE_NUMBERS\""));
+    }
+
+    #[tokio::test]
+    async fn lookup_batch_preserves_order() {
+        let db = Arc::new(test_database());
+        let body = r#"[{"pc":"1234AB","n":10},{"pc":"1234AB","n":99},{"pc":"bogus","n":10},{"pc":"1234AB"},{"pc":"1234AB","n":11}]"#;
+        let request = format!(
+            "POST /lookup/batch HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(
+            "[{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"},null,null,null,{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}]"
+        ));
+    }
+
+    #[tokio::test]
+    async fn lookup_batch_too_large() {
+        let db = Arc::new(test_database());
+        let entries = vec![r#"{"pc":"1234AB","n":10}"#; 1001];
+        let body = format!("[{}]", entries.join(","));
+        let request = format!(
+            "POST /lookup/batch HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 413 Content Too Large"));
+        assert!(response.contains("{\"code\":\"BATCH_TOO_LARGE\",\"error\":\"batch too large\"}"));
+    }
+
+    #[tokio::test]
+    async fn lookup_with_house_letter() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10&hl=a HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(
+            "{\"gm\":\"Amsterdam\",\"hl\":\"A\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}"
+        ));
+
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10&hl=B HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(response.contains("\"code\":\"ADDRESS_NOT_FOUND\""));
+    }
+
+    #[tokio::test]
+    async fn lookup_with_house_number_addition() {
+        let db = Arc::new(test_database());
+        let body = r#"{"pc":"1234AB","n":11,"toev":"2"}"#;
+        let request = format!(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(
+            "{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"toev\":\"2\",\"wp\":\"Amsterdam\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn lookup_without_letter_matches_numeric_range() {
+        // 10A exists, but a plain lookup of 10 keeps resolving via the range.
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10&hl= HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_invalid_house_letter() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10&hl=AB HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(
+            response
+                .contains("{\"code\":\"INVALID_HOUSE_LETTER\",\"error\":\"invalid house_letter\"}")
+        );
     }
 
     #[tokio::test]
     async fn method_not_allowed() {
         let db = Arc::new(test_database());
         let response = send_request(
-            "POST /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            "DELETE /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
             db,
         )
         .await;
 
         assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed"));
-        assert!(response.contains("{\"error\":\"method not allowed\"}"));
+        assert!(
+            response.contains("{\"code\":\"METHOD_NOT_ALLOWED\",\"error\":\"method not allowed\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn post_lookup_success() {
+        let db = Arc::new(test_database());
+        let body = r#"{"pc":"1234AB","n":11}"#;
+        let request = format!(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn post_lookup_missing_field() {
+        let db = Arc::new(test_database());
+        let body = r#"{"pc":"1234AB"}"#;
+        let request = format!(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(
+            response
+                .contains("{\"code\":\"MISSING_HOUSE_NUMBER\",\"error\":\"missing house_number\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn post_lookup_malformed_json() {
+        let db = Arc::new(test_database());
+        let body = r#"{"pc":"1234AB","n":"#;
+        let request = format!(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(
+            response.contains("{\"code\":\"INVALID_JSON_BODY\",\"error\":\"invalid JSON body\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn post_lookup_without_content_length() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\n\r\n{\"pc\":\"1234AB\",\"n\":11}",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 411 Length Required"));
+        assert!(response.contains("{\"code\":\"LENGTH_REQUIRED\",\"error\":\"length required\"}"));
+    }
+
+    #[tokio::test]
+    async fn post_body_is_consumed_before_next_request() {
+        let db = Arc::new(test_database());
+        let body = r#"{"pc":"1234AB","n":10}"#;
+        let request = format!(
+            "POST /lookup HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}\
+             GET /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn long_request_line_with_valid_query() {
+        let db = Arc::new(test_database());
+        let padding = "x".repeat(500);
+        let request =
+            format!("GET /lookup?pc=1234AB&n=11&pad={padding} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_request_head() {
+        let db = Arc::new(test_database());
+        let mut request =
+            String::from("GET /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n");
+        request.push_str(&("X-Long: ".to_string() + &"a".repeat(9000) + "\r\n\r\n"));
+
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+        assert!(response.contains(
+            "{\"code\":\"REQUEST_HEADER_TOO_LARGE\",\"error\":\"request header too large\"}"
+        ));
     }
 
     #[tokio::test]
@@ -138,6 +838,42 @@ mod tests {
         let response = send_request(&request, db).await;
 
         assert!(response.starts_with("HTTP/1.1 200 OK"));
-        assert!(response.contains("{\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}"));
+        assert!(
+            response
+                .contains("{\"gm\":\"Amsterdam\",\"pr\":\"Stationsstraat\",\"wp\":\"Amsterdam\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn keep_alive_serves_pipelined_requests() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\n\r\n\
+             GET /lookup?pc=1234AB&n=99 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            db,
+        )
+        .await;
+
+        let (first, second) = response
+            .split_once("HTTP/1.1 404 Not Found")
+            .expect("second response");
+        assert!(first.starts_with("HTTP/1.1 200 OK"));
+        assert!(first.contains("Connection: keep-alive\r\nKeep-Alive: timeout=5\r\n"));
+        assert!(second.contains("Connection: close\r\n"));
+        assert!(second.contains("\"code\":\"HOUSE_NUMBER_NOT_FOUND\""));
+    }
+
+    #[tokio::test]
+    async fn http10_request_closes_connection() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=11 HTTP/1.0\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(!response.contains("Keep-Alive"));
     }
 }
@@ -0,0 +1,273 @@
+//! Request metrics in the Prometheus text exposition format.
+//!
+//! Counters are plain atomics (plus a small map for the per-status counts)
+//! shared by all connections; rendering takes a snapshot, so a scrape may see
+//! counters from slightly different moments, as Prometheus allows.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use super::Response;
+
+/// Default prefix for all metric names.
+pub(super) const DEFAULT_METRICS_PREFIX: &str = "bag_address_lookup";
+
+/// Upper bounds (in milliseconds) of the request duration histogram buckets.
+const DURATION_BUCKETS_MS: [u64; 11] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Request counters and latency histogram shared by all connections.
+pub(crate) struct Metrics {
+    prefix: String,
+    requests_total: AtomicU64,
+    responses_by_status: Mutex<BTreeMap<u16, u64>>,
+    duration_buckets: [AtomicU64; DURATION_BUCKETS_MS.len()],
+    duration_count: AtomicU64,
+    duration_sum_ms: AtomicU64,
+}
+
+impl Metrics {
+    /// Create empty metrics whose names start with `prefix`.
+    pub(crate) fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            requests_total: AtomicU64::new(0),
+            responses_by_status: Mutex::new(BTreeMap::new()),
+            duration_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            duration_count: AtomicU64::new(0),
+            duration_sum_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Count one answered request, and its duration when it was measured.
+    pub(crate) fn record(&self, status_code: u16, duration_ms: Option<u128>) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        *self
+            .responses_by_status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(status_code)
+            .or_insert(0) += 1;
+
+        if let Some(duration_ms) = duration_ms {
+            let duration_ms = u64::try_from(duration_ms).unwrap_or(u64::MAX);
+            for (bucket, bound) in self.duration_buckets.iter().zip(DURATION_BUCKETS_MS) {
+                if duration_ms <= bound {
+                    bucket.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            self.duration_count.fetch_add(1, Ordering::Relaxed);
+            self.duration_sum_ms
+                .fetch_add(duration_ms, Ordering::Relaxed);
+        }
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> String {
+        let prefix = &self.prefix;
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_requests_total Total number of answered HTTP requests."
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_requests_total counter");
+        let _ = writeln!(
+            out,
+            "{prefix}_requests_total {}",
+            self.requests_total.load(Ordering::Relaxed)
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_responses_total Answered HTTP requests by status code."
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_responses_total counter");
+        let by_status = self
+            .responses_by_status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        for (status_code, count) in by_status {
+            let _ = writeln!(
+                out,
+                "{prefix}_responses_total{{status=\"{status_code}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_request_duration_seconds Time spent reading, handling and answering a request."
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_request_duration_seconds histogram");
+        for (bucket, bound) in self.duration_buckets.iter().zip(DURATION_BUCKETS_MS) {
+            let _ = writeln!(
+                out,
+                "{prefix}_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound as f64 / 1000.0,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = self.duration_count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "{prefix}_request_duration_seconds_bucket{{le=\"+Inf\"}} {count}"
+        );
+        let _ = writeln!(
+            out,
+            "{prefix}_request_duration_seconds_sum {}",
+            self.duration_sum_ms.load(Ordering::Relaxed) as f64 / 1000.0
+        );
+        let _ = writeln!(out, "{prefix}_request_duration_seconds_count {count}");
+
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_METRICS_PREFIX)
+    }
+}
+
+/// Handle the `/metrics` endpoint.
+pub(crate) fn handle_metrics(metrics: &Metrics) -> Response {
+    Response::with_content_type(200, metrics.render(), CONTENT_TYPE)
+}
+
+/// Read the metric name prefix from `BAG_ADDRESS_LOOKUP_METRICS_PREFIX`,
+/// falling back to the default when unset or not a valid metric name.
+pub(super) fn metrics_prefix() -> String {
+    std::env::var("BAG_ADDRESS_LOOKUP_METRICS_PREFIX")
+        .ok()
+        .filter(|value| is_valid_metric_name(value))
+        .unwrap_or_else(|| DEFAULT_METRICS_PREFIX.to_string())
+}
+
+/// Whether `name` matches the Prometheus metric name grammar
+/// `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+fn is_valid_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, is_valid_metric_name};
+    use crate::service::test_utils::{send_request, test_database};
+    use std::sync::Arc;
+
+    /// Parse a text exposition into `(name with labels, value)` samples,
+    /// checking that every sample belongs to a family declared by `# TYPE`.
+    fn parse_exposition(text: &str) -> Vec<(String, f64)> {
+        let mut families = Vec::new();
+        let mut samples = Vec::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').expect("TYPE line");
+                assert!(matches!(kind, "counter" | "gauge" | "histogram"));
+                families.push(name.to_string());
+                continue;
+            }
+            if line.starts_with("# HELP ") {
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').expect("sample line");
+            let name = series.split('{').next().unwrap();
+            assert!(is_valid_metric_name(name), "invalid name {name}");
+            if let Some(labels) = series.strip_prefix(name).filter(|l| !l.is_empty()) {
+                assert!(labels.starts_with('{') && labels.ends_with('}'));
+                for label in labels[1..labels.len() - 1].split(',') {
+                    let (key, value) = label.split_once('=').expect("label");
+                    assert!(is_valid_metric_name(key));
+                    assert!(value.starts_with('"') && value.ends_with('"'));
+                }
+            }
+            assert!(
+                families
+                    .iter()
+                    .any(|family| name.starts_with(family.as_str())),
+                "sample {name} without TYPE"
+            );
+            samples.push((series.to_string(), value.parse::<f64>().expect("value")));
+        }
+        samples
+    }
+
+    #[test]
+    fn exposition_format_parses() {
+        let metrics = Metrics::new("test_prefix");
+        metrics.record(200, Some(3));
+        metrics.record(200, Some(70));
+        metrics.record(404, None);
+
+        let samples = parse_exposition(&metrics.render());
+        let value = |series: &str| {
+            samples
+                .iter()
+                .find(|(name, _)| name == series)
+                .map(|(_, value)| *value)
+        };
+
+        assert_eq!(value("test_prefix_requests_total"), Some(3.0));
+        assert_eq!(
+            value("test_prefix_responses_total{status=\"200\"}"),
+            Some(2.0)
+        );
+        assert_eq!(
+            value("test_prefix_responses_total{status=\"404\"}"),
+            Some(1.0)
+        );
+        assert_eq!(
+            value("test_prefix_request_duration_seconds_bucket{le=\"0.005\"}"),
+            Some(1.0)
+        );
+        assert_eq!(
+            value("test_prefix_request_duration_seconds_bucket{le=\"0.1\"}"),
+            Some(2.0)
+        );
+        assert_eq!(
+            value("test_prefix_request_duration_seconds_bucket{le=\"+Inf\"}"),
+            Some(2.0)
+        );
+        assert_eq!(
+            value("test_prefix_request_duration_seconds_sum"),
+            Some(0.073)
+        );
+        assert_eq!(
+            value("test_prefix_request_duration_seconds_count"),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn metric_name_validation() {
+        assert!(is_valid_metric_name("bag_address_lookup"));
+        assert!(is_valid_metric_name("ns:bag_1"));
+        assert!(!is_valid_metric_name(""));
+        assert!(!is_valid_metric_name("1bag"));
+        assert!(!is_valid_metric_name("bag-lookup"));
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint() {
+        let db = Arc::new(test_database());
+        let response = send_request("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n"));
+        assert!(response.contains("# TYPE bag_address_lookup_requests_total counter"));
+    }
+}
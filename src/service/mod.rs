@@ -1,7 +1,12 @@
+use flate2::{Compression, write::GzEncoder};
 use serde_json::json;
 use std::{
+    borrow::Cow,
     error::Error,
     future::Future,
+    io::Write,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -9,56 +14,370 @@ use std::{
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::TcpListener,
+    sync::{Semaphore, watch},
+    task::JoinSet,
 };
+use tracing::{
+    Instrument, Level, error, field::Empty, info, info_span, level_filters::LevelFilter, warn,
+};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+
+/// Default maximum time allowed for handling a single request (read + process
+/// + write).
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// Maximum time allowed for handling a single connection (read + process + write).
-const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long an idle keep-alive connection waits for the next request.
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
 
-/// Upper bound on request header bytes consumed per connection.
+/// Default upper bound on request header bytes consumed per connection.
 ///
 /// Large enough for realistic browser requests (cookies, Accept-*, Sec-Fetch-*,
 /// Referer) while bounding memory. Closing a TCP socket with unread bytes
 /// pending in the receive queue makes Linux emit a RST instead of FIN, which
 /// surfaces as `ERR_CONNECTION_RESET` in the browser — so we read through the
 /// end-of-headers marker rather than stopping at a fixed byte count.
-const MAX_REQUEST_BYTES: usize = 8192;
+const DEFAULT_MAX_REQUEST_BYTES: usize = 8192;
+
+/// Size of the on-stack buffer used for the request head. Requests that fit
+/// are parsed without a heap allocation.
+const STACK_REQUEST_BYTES: usize = 1024;
+
+/// Upper bound on bytes discarded after rejecting an oversized request head or
+/// body.
+const MAX_DISCARD_BYTES: usize = 64 * 1024;
+
+/// Upper bound on the `Content-Length` accepted for a POST body.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Default time open connections get to finish after shutdown is requested.
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Default number of connections served at the same time.
+const DEFAULT_MAX_CONNECTIONS: usize = 1024;
 
-use crate::database::DatabaseHandle;
+/// How long a connection accepted at the limit waits for a free slot before
+/// it is answered with `503`. Accepting pauses meanwhile, so further clients
+/// queue in the listen backlog. Shutdown closes the waiting connection.
+const CONNECTION_QUEUE_TIMEOUT: Duration = Duration::from_secs(1);
 
+use crate::{
+    database::{AddressResult, DatabaseError, DatabaseHandle, HouseNumberSuffix},
+    suggest::ScoringConfig,
+};
+
+mod access_log;
+mod config;
+mod connection;
+mod health;
 mod localities_list;
 mod lookup;
+mod metrics;
 mod municipalities;
+mod openapi;
 mod query;
+mod reload;
+mod request;
+mod reverse;
+mod stats;
 mod suggest;
+mod suggest_cache;
+#[cfg(feature = "tls")]
+mod tls;
+
+pub use config::ServerConfig;
+use connection::{Connection, Listener, Listeners, Plain, Transport};
+use metrics::Metrics;
+use reload::{DatabaseSlot, Hangups};
+use request::RequestHead;
+use suggest_cache::SuggestCache;
+#[cfg(feature = "tls")]
+pub use tls::{serve_tls, serve_tls_with_shutdown};
+
+/// Default `Access-Control-Allow-Origin`: any origin may call the API.
+const DEFAULT_CORS_ORIGIN: &str = "*";
+
+/// How long browsers may cache a CORS preflight answer, in seconds.
+const CORS_MAX_AGE_SECS: u32 = 86400;
+
+/// Bodies shorter than this are never gzip-compressed.
+const GZIP_MIN_BYTES: usize = 256;
+
+/// Content type of the JSON responses.
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
 
 /// Minimal response wrapper for handler results.
 struct Response {
     status_code: u16,
     body: String,
+    content_type: &'static str,
+    /// Entity tag of a cacheable response, sent as the `ETag` header.
+    etag: Option<String>,
 }
 
 impl Response {
     /// Construct a response with status code and serialized JSON body.
     fn new(status_code: u16, body: String) -> Self {
-        Self { status_code, body }
+        Self::with_content_type(status_code, body, JSON_CONTENT_TYPE)
+    }
+
+    /// Construct a response with a non-JSON body.
+    fn with_content_type(status_code: u16, body: String, content_type: &'static str) -> Self {
+        Self {
+            status_code,
+            body,
+            content_type,
+            etag: None,
+        }
+    }
+
+    /// A `304 Not Modified` answer to a conditional request.
+    fn not_modified() -> Self {
+        Self::new(304, String::new())
+    }
+}
+
+/// Error responses, each with a stable machine-readable `code` next to the
+/// human-readable `error` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ApiError {
+    InvalidQueryEncoding,
+    /// A `GET` without any query parameters.
+    EmptyQuery,
+    InvalidJsonBody,
+    MissingPostalCode,
+    InvalidPostalCode,
+    MissingHouseNumber,
+    InvalidHouseNumber,
+    InvalidHouseNumberRange,
+    TooManyHouseNumbers,
+    InvalidHouseLetter,
+    InvalidHouseNumberAddition,
+    /// No range is registered under the postal code.
+    PostalCodeNotFound,
+    /// The postal code is known, but not with this house number.
+    HouseNumberNotFound,
+    /// The house number is known, but not with this letter or addition.
+    AddressNotFound,
+    BatchTooLarge,
+    MissingWp,
+    InvalidType,
+    MissingLat,
+    MissingLon,
+    /// Query keys rejected in strict mode, with a message naming them.
+    UnknownParameters(String),
+    InvalidLat,
+    InvalidLon,
+    NoAddressWithinRadius,
+    NotFound,
+    MethodNotAllowed,
+    RequestTimeout,
+    LengthRequired,
+    RequestBodyTooLarge,
+    IncompleteRequestBody,
+    RequestHeaderTooLarge,
+    TooManyConnections,
+    /// An unexpected failure. Its details are logged, not sent to the client.
+    Internal,
+}
+
+impl ApiError {
+    /// HTTP status, `code` and `error` message of the response.
+    fn parts(&self) -> (u16, &'static str, &str) {
+        match self {
+            Self::InvalidQueryEncoding => (400, "INVALID_QUERY_ENCODING", "invalid query encoding"),
+            Self::EmptyQuery => (400, "EMPTY_QUERY", "no query parameters provided"),
+            Self::InvalidJsonBody => (400, "INVALID_JSON_BODY", "invalid JSON body"),
+            Self::MissingPostalCode => (400, "MISSING_POSTAL_CODE", "missing postal_code"),
+            Self::InvalidPostalCode => (400, "INVALID_POSTAL_CODE", "invalid postal_code"),
+            Self::MissingHouseNumber => (400, "MISSING_HOUSE_NUMBER", "missing house_number"),
+            Self::InvalidHouseNumber => (400, "INVALID_HOUSE_NUMBER", "invalid house_number"),
+            Self::InvalidHouseNumberRange => (
+                400,
+                "INVALID_HOUSE_NUMBER_RANGE",
+                "invalid house_number range",
+            ),
+            Self::TooManyHouseNumbers => (400, "TOO_MANY_HOUSE_NUMBERS", "too many house numbers"),
+            Self::InvalidHouseLetter => (400, "INVALID_HOUSE_LETTER", "invalid house_letter"),
+            Self::InvalidHouseNumberAddition => (
+                400,
+                "INVALID_HOUSE_NUMBER_ADDITION",
+                "invalid house_number_addition",
+            ),
+            Self::PostalCodeNotFound => (404, "POSTAL_CODE_NOT_FOUND", "postal code not found"),
+            Self::HouseNumberNotFound => (
+                404,
+                "HOUSE_NUMBER_NOT_FOUND",
+                "house number not found for postal code",
+            ),
+            Self::AddressNotFound => (404, "ADDRESS_NOT_FOUND", "address not found"),
+            Self::BatchTooLarge => (413, "BATCH_TOO_LARGE", "batch too large"),
+            Self::MissingWp => (400, "MISSING_WP", "missing wp"),
+            Self::InvalidType => (400, "INVALID_TYPE", "invalid type"),
+            Self::MissingLat => (400, "MISSING_LAT", "missing lat"),
+            Self::MissingLon => (400, "MISSING_LON", "missing lon"),
+            Self::UnknownParameters(message) => (400, "UNKNOWN_PARAMETERS", message),
+            Self::InvalidLat => (400, "INVALID_LAT", "invalid lat"),
+            Self::InvalidLon => (400, "INVALID_LON", "invalid lon"),
+            Self::NoAddressWithinRadius => {
+                (404, "NO_ADDRESS_WITHIN_RADIUS", "no address within radius")
+            }
+            Self::NotFound => (404, "NOT_FOUND", "not found"),
+            Self::MethodNotAllowed => (405, "METHOD_NOT_ALLOWED", "method not allowed"),
+            Self::RequestTimeout => (408, "REQUEST_TIMEOUT", "request timeout"),
+            Self::LengthRequired => (411, "LENGTH_REQUIRED", "length required"),
+            Self::RequestBodyTooLarge => (413, "REQUEST_BODY_TOO_LARGE", "request body too large"),
+            Self::IncompleteRequestBody => {
+                (400, "INCOMPLETE_REQUEST_BODY", "incomplete request body")
+            }
+            Self::RequestHeaderTooLarge => {
+                (431, "REQUEST_HEADER_TOO_LARGE", "request header too large")
+            }
+            Self::TooManyConnections => (503, "TOO_MANY_CONNECTIONS", "too many connections"),
+            Self::Internal => (500, "INTERNAL_ERROR", "internal error"),
+        }
+    }
+}
+
+impl From<ApiError> for Response {
+    fn from(error: ApiError) -> Self {
+        let (status_code, code, message) = error.parts();
+        let body = serde_json::to_string(&json!({ "code": code, "error": message }))
+            .expect("serialize error response");
+        Response::new(status_code, body)
+    }
+}
+
+/// Level of the default log subscriber: `quiet` leaves only errors.
+fn log_level(quiet: bool) -> LevelFilter {
+    if quiet {
+        LevelFilter::ERROR
+    } else {
+        LevelFilter::INFO
     }
 }
 
-/// Enable/disable request logging via `BAG_ADDRESS_LOOKUP_QUIET`.
-fn logging_disabled() -> bool {
-    std::env::var("BAG_ADDRESS_LOOKUP_QUIET")
-        .map(|v| v == "1" || v.to_lowercase() == "true")
-        .unwrap_or(false)
+/// Install a plain-text log subscriber unless the application set one up
+/// already. Warnings and errors go to stderr, the rest to stdout.
+fn init_logging(quiet: bool) {
+    if tracing::dispatcher::has_been_set() {
+        return;
+    }
+    let writer = std::io::stderr
+        .with_max_level(Level::WARN)
+        .or_else(std::io::stdout);
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(log_level(quiet))
+        .with_writer(writer)
+        .with_target(false)
+        .without_time()
+        .try_init();
+}
+
+/// Read the request header cap from `BAG_ADDRESS_LOOKUP_MAX_HEADER_BYTES`.
+fn max_request_bytes() -> usize {
+    std::env::var("BAG_ADDRESS_LOOKUP_MAX_HEADER_BYTES")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_MAX_REQUEST_BYTES)
+}
+
+/// Number of reverse proxies in front of the service whose forwarding
+/// headers can be trusted, from `BAG_ADDRESS_LOOKUP_TRUST_PROXY`: a count, or
+/// `true` for one.
+fn trusted_proxies() -> usize {
+    std::env::var("BAG_ADDRESS_LOOKUP_TRUST_PROXY")
+        .ok()
+        .and_then(|v| {
+            v.parse::<usize>()
+                .ok()
+                .or_else(|| v.eq_ignore_ascii_case("true").then_some(1))
+        })
+        .unwrap_or(0)
+}
+
+/// Address of the client that sent `request`: the forwarded address behind
+/// `trusted_proxies` proxies, when they forwarded one, the socket peer
+/// otherwise.
+fn client_ip(
+    peer: Option<SocketAddr>,
+    request: Option<&RequestHead>,
+    trusted_proxies: usize,
+) -> Option<IpAddr> {
+    request
+        .and_then(|request| request.forwarded_for(trusted_proxies))
+        .or(peer.map(|peer| peer.ip()))
+}
+
+/// Limits on the connections the accept loop keeps open.
+struct ConnectionLimits {
+    /// Connections served at the same time.
+    max_connections: usize,
+    /// Time allowed for reading, handling and answering one request.
+    request_timeout: Duration,
+    /// Time open connections get to finish after shutdown is requested.
+    shutdown_grace: Duration,
+}
+
+impl ConnectionLimits {
+    /// The limits of `config`.
+    fn new(config: &ServerConfig) -> Self {
+        Self {
+            max_connections: config.max_connections,
+            request_timeout: config.timeout,
+            shutdown_grace: config.shutdown_grace,
+        }
+    }
+}
+
+/// Read the shutdown grace period from `BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE`
+/// (in whole seconds).
+fn shutdown_grace() -> Duration {
+    std::env::var("BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map_or(DEFAULT_SHUTDOWN_GRACE, Duration::from_secs)
+}
+
+/// Read the database file path from `BAG_ADDRESS_LOOKUP_DATABASE`.
+fn database_path() -> Option<PathBuf> {
+    std::env::var_os("BAG_ADDRESS_LOOKUP_DATABASE")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
 }
 
 /// Start a BAG lookup HTTP server on the given address.
+///
+/// The server is configured by the `BAG_ADDRESS_LOOKUP_*` environment
+/// variables, see [`ServerConfig::from_env`].
 pub async fn serve(addr: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
     let listener = TcpListener::bind(addr).await?;
 
     serve_with_shutdown(listener, tokio::signal::ctrl_c()).await
 }
 
+/// Start a BAG lookup HTTP server on the given address with the settings of
+/// `config` rather than those of the environment.
+pub async fn serve_with_config(
+    addr: &str,
+    config: ServerConfig,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = TcpListener::bind(addr).await?;
+
+    serve_configured(listener, Plain, config, tokio::signal::ctrl_c()).await
+}
+
 /// Start the server with a shutdown future (e.g. Ctrl-C).
+///
+/// The server logs through `tracing`; without a global subscriber a plain
+/// text one is installed.
+///
+/// On Unix, SIGHUP reloads the database from `BAG_ADDRESS_LOOKUP_DATABASE`
+/// without interrupting open connections. At most
+/// `BAG_ADDRESS_LOOKUP_MAX_CONNECTIONS` connections are served at once. Once
+/// `shutdown` completes no new connections are accepted, and open ones get
+/// the grace period from `BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE` to finish before
+/// they are dropped.
 pub async fn serve_with_shutdown<F>(
     listener: TcpListener,
     shutdown: F,
@@ -66,196 +385,967 @@ pub async fn serve_with_shutdown<F>(
 where
     F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
 {
-    let database = Arc::new(DatabaseHandle::load()?);
+    serve_configured(listener, Plain, ServerConfig::from_env(), shutdown).await
+}
+
+/// Start a BAG lookup HTTP server listening on all of the given addresses,
+/// e.g. both `0.0.0.0:8080` and `[::]:8080`.
+pub async fn serve_many(addrs: &[&str]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut listeners = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        listeners.push(TcpListener::bind(addr).await?);
+    }
+
+    serve_many_with_shutdown(listeners, tokio::signal::ctrl_c()).await
+}
+
+/// Like [`serve_with_shutdown`], accepting connections on all `listeners`.
+/// All of them are closed once `shutdown` completes.
+pub async fn serve_many_with_shutdown<F>(
+    listeners: Vec<TcpListener>,
+    shutdown: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    if listeners.is_empty() {
+        return Err("No addresses to listen on".into());
+    }
+
+    serve_configured(
+        Listeners::from(listeners),
+        Plain,
+        ServerConfig::from_env(),
+        shutdown,
+    )
+    .await
+}
+
+/// Start a BAG lookup HTTP server on a Unix domain socket at `path`.
+///
+/// Binding fails when `path` exists, e.g. left over from an earlier run.
+#[cfg(unix)]
+pub async fn serve_unix(path: impl AsRef<Path>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = tokio::net::UnixListener::bind(path)?;
+
+    serve_unix_with_shutdown(listener, tokio::signal::ctrl_c()).await
+}
+
+/// Like [`serve_with_shutdown`], accepting connections on a Unix domain
+/// socket.
+#[cfg(unix)]
+pub async fn serve_unix_with_shutdown<F>(
+    listener: tokio::net::UnixListener,
+    shutdown: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    serve_configured(listener, Plain, ServerConfig::from_env(), shutdown).await
+}
+
+/// Builds a [`Server`] from a [`ServerConfig`], starting from
+/// [`ServerConfig::from_env`].
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use bag_address_lookup::ServerBuilder;
+///
+/// ServerBuilder::new()
+///     .bind("0.0.0.0:8080")
+///     .quiet(true)
+///     .max_connections(512)
+///     .database_path("bag.bin")
+///     .build()
+///     .await?
+///     .run()
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServerBuilder {
+    addrs: Vec<String>,
+    config: ServerConfig,
+}
+
+impl Default for ServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerBuilder {
+    /// A builder without addresses and with the settings of the environment.
+    pub fn new() -> Self {
+        Self {
+            addrs: Vec::new(),
+            config: ServerConfig::from_env(),
+        }
+    }
+
+    /// Listen on `addr`, e.g. `0.0.0.0:8080`. Call it again to listen on
+    /// more addresses.
+    pub fn bind(mut self, addr: impl Into<String>) -> Self {
+        self.addrs.push(addr.into());
+        self
+    }
+
+    /// Replace all settings with those of `config`.
+    pub fn config(mut self, config: ServerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// See [`ServerConfig::quiet`].
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.config.quiet = quiet;
+        self
+    }
+
+    /// See [`ServerConfig::suggest_threshold`].
+    pub fn suggest_threshold(mut self, threshold: f32) -> Self {
+        self.config.suggest_threshold = threshold;
+        self
+    }
+
+    /// See [`ServerConfig::suggest_limit`].
+    pub fn suggest_limit(mut self, limit: usize) -> Self {
+        self.config.suggest_limit = limit;
+        self
+    }
+
+    /// See [`ServerConfig::suggest_scoring`].
+    pub fn suggest_scoring(mut self, scoring: ScoringConfig) -> Self {
+        self.config.suggest_scoring = scoring;
+        self
+    }
+
+    /// See [`ServerConfig::max_connections`].
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.config.max_connections = max_connections;
+        self
+    }
+
+    /// See [`ServerConfig::timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    /// See [`ServerConfig::cors`].
+    pub fn cors(mut self, origin: impl Into<String>) -> Self {
+        self.config.cors = origin.into();
+        self
+    }
+
+    /// Serve the database file at `path` rather than the embedded one.
+    pub fn database_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.database = Some(path.into());
+        self
+    }
+
+    /// See [`ServerConfig::shutdown_grace`].
+    pub fn shutdown_grace(mut self, grace: Duration) -> Self {
+        self.config.shutdown_grace = grace;
+        self
+    }
+
+    /// See [`ServerConfig::trusted_proxies`].
+    pub fn trusted_proxies(mut self, proxies: usize) -> Self {
+        self.config.trusted_proxies = proxies;
+        self
+    }
+
+    /// See [`ServerConfig::access_log`].
+    pub fn access_log(mut self, enabled: bool) -> Self {
+        self.config.access_log = enabled;
+        self
+    }
+
+    /// See [`ServerConfig::max_request_bytes`].
+    pub fn max_request_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_request_bytes = bytes;
+        self
+    }
+
+    /// See [`ServerConfig::reverse_radius`].
+    pub fn reverse_radius(mut self, metres: f64) -> Self {
+        self.config.reverse_radius = metres;
+        self
+    }
+
+    /// See [`ServerConfig::metrics_prefix`].
+    pub fn metrics_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config.metrics_prefix = prefix.into();
+        self
+    }
+
+    /// See [`ServerConfig::suggest_cache_size`].
+    pub fn suggest_cache_size(mut self, size: usize) -> Self {
+        self.config.suggest_cache_size = size;
+        self
+    }
+
+    /// Set up logging, load the database and bind the addresses. Fails when
+    /// no address was given, the database cannot be loaded or an address
+    /// cannot be bound.
+    pub async fn build(self) -> Result<Server, Box<dyn Error + Send + Sync>> {
+        if self.addrs.is_empty() {
+            return Err("No addresses to listen on".into());
+        }
+
+        let database = prepare(&self.config)?;
+        let mut listeners = Vec::with_capacity(self.addrs.len());
+        for addr in &self.addrs {
+            listeners.push(TcpListener::bind(addr).await?);
+        }
+
+        Ok(Server {
+            listeners,
+            database,
+            config: self.config,
+        })
+    }
+}
+
+/// A bound server with its database loaded, made by [`ServerBuilder`].
+pub struct Server {
+    listeners: Vec<TcpListener>,
+    database: DatabaseHandle<'static>,
+    config: ServerConfig,
+}
+
+impl Server {
+    /// The addresses the server listens on, e.g. to find the port picked
+    /// for `127.0.0.1:0`.
+    pub fn local_addrs(&self) -> std::io::Result<Vec<SocketAddr>> {
+        self.listeners.iter().map(TcpListener::local_addr).collect()
+    }
+
+    /// Serve requests until Ctrl-C.
+    pub async fn run(self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.run_with_shutdown(tokio::signal::ctrl_c()).await
+    }
+
+    /// Serve requests until `shutdown` completes, like
+    /// [`serve_with_shutdown`].
+    pub async fn run_with_shutdown<F>(self, shutdown: F) -> Result<(), Box<dyn Error + Send + Sync>>
+    where
+        F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+    {
+        let limits = ConnectionLimits::new(&self.config);
+        serve_connections_over(
+            Listeners::from(self.listeners),
+            Plain,
+            Arc::new(DatabaseSlot::new(self.database)),
+            shutdown,
+            limits,
+            Arc::new(self.config),
+        )
+        .await
+    }
+}
+
+/// Set up logging, load the database and serve it on `listener` with the
+/// settings of `config`.
+async fn serve_configured<L, T, F>(
+    listener: L,
+    transport: T,
+    config: ServerConfig,
+    shutdown: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    L: Listener,
+    T: Transport<L::Stream>,
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    let database = prepare(&config)?;
+    let limits = ConnectionLimits::new(&config);
+    serve_connections_over(
+        listener,
+        transport,
+        Arc::new(DatabaseSlot::new(database)),
+        shutdown,
+        limits,
+        Arc::new(config),
+    )
+    .await
+}
+
+/// Set up logging and load the database of `config`.
+fn prepare(config: &ServerConfig) -> Result<DatabaseHandle<'static>, Box<dyn Error + Send + Sync>> {
+    init_logging(config.quiet);
+    let database = match DatabaseHandle::open(config.database.as_deref()) {
+        Err(DatabaseError::NotEmbedded) => {
+            return Err("no database is embedded in this build; set \
+                        BAG_ADDRESS_LOOKUP_DATABASE to a database file"
+                .into());
+        }
+        result => result?,
+    };
 
     if database.is_empty() {
         return Err("Database is empty; rebuild the database file".into());
     }
 
-    if !logging_disabled() {
-        println!("[bag-address-lookup] database initialized");
-    }
+    info!("database initialized");
+
+    Ok(database)
+}
 
+/// Accept connections until `shutdown` completes, then wait up to the
+/// shutdown grace period for the open ones to finish.
+#[cfg(test)]
+async fn serve_connections<L, F>(
+    listener: L,
+    database: Arc<DatabaseSlot>,
+    shutdown: F,
+    limits: ConnectionLimits,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    L: Listener,
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    let config = Arc::new(ServerConfig::default());
+    serve_connections_over(listener, Plain, database, shutdown, limits, config).await
+}
+
+/// Accept connections until `shutdown` completes, opening every accepted
+/// connection with `transport` before serving requests on it with the
+/// settings of `config`. Then wait up to the shutdown grace period for the
+/// open ones to finish.
+async fn serve_connections_over<L, T, F>(
+    listener: L,
+    transport: T,
+    database: Arc<DatabaseSlot>,
+    shutdown: F,
+    limits: ConnectionLimits,
+    config: Arc<ServerConfig>,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    L: Listener,
+    T: Transport<L::Stream>,
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    let metrics = Arc::new(Metrics::new(&config.metrics_prefix));
+    let suggest_cache = Arc::new(SuggestCache::new(config.suggest_cache_size));
+    let mut hangups = Hangups::new()?;
     let mut shutdown = Box::pin(shutdown);
+    let mut connections = JoinSet::new();
+    // Tells the open connections that shutdown started.
+    let (stop, stopping) = watch::channel(false);
+    let slots = Arc::new(Semaphore::new(
+        limits.max_connections.min(Semaphore::MAX_PERMITS),
+    ));
 
     loop {
         tokio::select! {
             _ = &mut shutdown => break,
+            _ = hangups.recv() => {
+                let database = database.clone();
+                let path = config.database.clone();
+                tokio::task::spawn_blocking(move || reload::reload(&database, path.as_deref()));
+            }
+            // Reap finished connections so the set doesn't grow unbounded.
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
             accept = listener.accept() => {
-                let (stream, _) = accept?;
-                let db = database.clone();
-                tokio::spawn(async move {
-                    let mut stream = stream;
-                    match tokio::time::timeout(
-                        CONNECTION_TIMEOUT,
-                        handle_connection(&mut stream, db),
-                    )
-                    .await
-                    {
-                        Ok(Err(err)) => {
-                            let _ = write_response(
+                let stream = accept?;
+                let span = match stream.peer() {
+                    Some(peer) => info_span!("connection", %peer),
+                    None => info_span!("connection"),
+                };
+                let metrics = metrics.clone();
+                let config = config.clone();
+                let transport = transport.clone();
+                let request_timeout = limits.request_timeout;
+                // Waiting for a slot pauses accepting, but not shutdown.
+                let slot = tokio::select! {
+                    _ = &mut shutdown => break,
+                    slot = tokio::time::timeout(
+                        CONNECTION_QUEUE_TIMEOUT,
+                        slots.clone().acquire_owned(),
+                    ) => slot,
+                };
+                let Ok(Ok(slot)) = slot else {
+                    connections.spawn(async move {
+                        let response = Response::from(ApiError::TooManyConnections);
+                        let _ = tokio::time::timeout(request_timeout, async {
+                            let mut stream = transport.open(stream).await?;
+                            write_response(
                                 &mut stream,
-                                500,
-                                &json_error(&err.to_string()),
+                                &metrics,
+                                &config,
+                                &response,
                                 None,
-                            )
-                            .await;
-                        }
-                        Err(_elapsed) => {
-                            let _ = write_response(
-                                &mut stream,
-                                408,
-                                &json_error("request timeout"),
                                 None,
+                                false,
                             )
-                            .await;
-                        }
-                        Ok(Ok(())) => {}
+                            .await
+                        })
+                        .await;
+                    }.instrument(span));
+                    continue;
+                };
+                let db = database.snapshot();
+                let suggest_cache = suggest_cache.clone();
+                let stopping = stopping.clone();
+                connections.spawn(async move {
+                    // The slot is released when the connection is done.
+                    let _slot = slot;
+                    let mut stream =
+                        match tokio::time::timeout(request_timeout, transport.open(stream)).await {
+                            Ok(Ok(stream)) => stream,
+                            Ok(Err(err)) => {
+                                warn!(error = %err, "could not open connection");
+                                return;
+                            }
+                            Err(_elapsed) => {
+                                warn!("timed out opening connection");
+                                return;
+                            }
+                        };
+                    if let Err(err) = handle_connection(
+                        &mut stream,
+                        db,
+                        &metrics,
+                        &config,
+                        &suggest_cache,
+                        request_timeout,
+                        stopping,
+                    )
+                    .await
+                    {
+                        error!(error = %err, "connection failed");
+                        let response = Response::from(ApiError::Internal);
+                        let _ = write_response(
+                            &mut stream,
+                            &metrics,
+                            &config,
+                            &response,
+                            None,
+                            None,
+                            false,
+                        )
+                        .await;
                     }
-                });
+                }.instrument(span));
             }
         }
     }
 
+    drop(listener);
+    stop.send_replace(true);
+    if !connections.is_empty() {
+        info!(
+            connections = connections.len(),
+            "shutting down, waiting for open connections"
+        );
+    }
+    let drained = tokio::time::timeout(limits.shutdown_grace, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        warn!(
+            connections = connections.len(),
+            "dropping connections after the shutdown grace period"
+        );
+    }
+
     Ok(())
 }
 
-/// Handle a single HTTP connection and route to the correct handler.
+/// Handle a single HTTP connection, serving requests until either side
+/// closes it or it sits idle for longer than [`KEEP_ALIVE_TIMEOUT`].
+///
+/// A request that takes longer than `timeout` to arrive and be answered, such
+/// as one from a client that stalls halfway, gets a `408` and closes the
+/// connection. Once `stopping` turns true, an idle connection is closed and
+/// the request in flight is answered with `Connection: close`.
 async fn handle_connection(
-    stream: &mut tokio::net::TcpStream,
-    database: Arc<DatabaseHandle>,
+    stream: &mut impl Connection,
+    database: Arc<DatabaseHandle<'static>>,
+    metrics: &Metrics,
+    config: &ServerConfig,
+    suggest_cache: &SuggestCache,
+    timeout: Duration,
+    mut stopping: watch::Receiver<bool>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let start = Instant::now();
-    let mut buffer = Vec::with_capacity(1024);
-    let mut chunk = [0u8; 1024];
+    let mut buffer = HeadBuffer::new();
+    let mut first_request = true;
 
     loop {
-        let read = stream.read(&mut chunk).await?;
-        if read == 0 {
-            break;
-        }
-        buffer.extend_from_slice(&chunk[..read]);
-        if find_header_end(&buffer).is_some() {
-            break;
+        if !first_request && buffer.is_empty() {
+            // Idle between requests: close quietly on timeout, EOF or shutdown.
+            let read = tokio::select! {
+                read = tokio::time::timeout(KEEP_ALIVE_TIMEOUT, buffer.read_from(stream)) => read,
+                Ok(_) = stopping.wait_for(|stopping| *stopping) => return Ok(()),
+            };
+            match read {
+                Ok(Ok(0)) | Err(_) => return Ok(()),
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => return Err(err.into()),
+            }
         }
-        if buffer.len() >= MAX_REQUEST_BYTES {
-            break;
+        first_request = false;
+
+        // `handle_request` fills in the fields once the request head is read.
+        let span = info_span!("request", method = Empty, path = Empty);
+        match tokio::time::timeout(
+            timeout,
+            handle_request(
+                stream,
+                &mut buffer,
+                &database,
+                metrics,
+                config,
+                suggest_cache,
+                &stopping,
+            )
+            .instrument(span),
+        )
+        .await
+        {
+            Ok(Ok(true)) => {}
+            Ok(Ok(false)) => return Ok(()),
+            Ok(Err(err)) => return Err(err),
+            Err(_elapsed) => {
+                let response = Response::from(ApiError::RequestTimeout);
+                write_response(stream, metrics, config, &response, None, None, false).await?;
+                return Ok(());
+            }
         }
     }
+}
 
-    let request = String::from_utf8_lossy(&buffer);
+/// Read one request head from the connection, route it to the correct
+/// handler and write the response.
+///
+/// Returns whether the connection should stay open for another request,
+/// which it never does once `stopping` turns true.
+async fn handle_request(
+    stream: &mut impl Connection,
+    buffer: &mut HeadBuffer,
+    database: &Arc<DatabaseHandle<'static>>,
+    metrics: &Metrics,
+    config: &ServerConfig,
+    suggest_cache: &SuggestCache,
+    stopping: &watch::Receiver<bool>,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let start = Instant::now();
+    let max_bytes = config.max_request_bytes;
 
-    let mut lines = request.lines();
-    let request_line = lines.next().unwrap_or_default();
-    let mut parts = request_line.split_whitespace();
-    let method = parts.next().unwrap_or_default();
-    let target = parts.next().unwrap_or_default();
+    let head_end = loop {
+        if let Some(end) = find_header_end(buffer.as_slice()) {
+            break end;
+        }
+        if buffer.len() >= max_bytes {
+            let response = Response::from(ApiError::RequestHeaderTooLarge);
+            let duration_ms = start.elapsed().as_millis();
+            write_response(
+                stream,
+                metrics,
+                config,
+                &response,
+                Some(duration_ms),
+                None,
+                false,
+            )
+            .await?;
+            discard_remaining(stream).await;
+            return Ok(false);
+        }
+        if buffer.read_from(stream).await? == 0 {
+            if buffer.is_empty() {
+                return Ok(false);
+            }
+            break buffer.len();
+        }
+    };
 
-    if !logging_disabled() {
-        println!(
-            "[bag-address-lookup] received request: {} {}",
-            method, target
-        );
+    let request = RequestHead::parse(&buffer.as_slice()[..head_end]);
+    buffer.consume(head_end);
+
+    let mut keep_alive = request.keep_alive() && !*stopping.borrow();
+    let mut unread_body = false;
+    let target = request.target.as_str();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let span = tracing::Span::current();
+    span.record("method", request.method.as_str());
+    span.record("path", path);
+    let access_log = config.access_log;
+    if !access_log {
+        info!(query, "received request");
     }
 
-    if method != "GET" {
-        let response = Response::new(405, json_error("method not allowed"));
+    if request.method == "OPTIONS" {
+        // CORS preflight: answered for every path, before the method check.
+        // Preflights carry no body; if one is announced, don't reuse the stream.
+        let keep_alive = keep_alive
+            && request
+                .header("content-length")
+                .is_none_or(|value| value == "0");
         let duration_ms = start.elapsed().as_millis();
-        write_response(
-            stream,
-            response.status_code,
-            &response.body,
-            Some(duration_ms),
-        )
-        .await?;
-        return Ok(());
+        metrics.record(204, Some(duration_ms));
+        write_preflight_response(stream, &config.cors, keep_alive).await?;
+        if access_log {
+            let client = client_ip(stream.peer(), Some(&request), config.trusted_proxies);
+            access_log::log(client, Some(&request), 204, 0, Some(duration_ms));
+        }
+        return Ok(keep_alive);
     }
 
-    let (path, query) = target.split_once('?').unwrap_or((target, ""));
-
-    if path == "/" {
-        return write_html_response(stream, API_DOCS_HTML).await;
-    }
+    // Lookup and suggest results only change when the database does.
+    let etag = (request.method == "GET" && matches!(path, "/lookup" | "/suggest"))
+        .then(|| database.checksum().map(etag_for))
+        .flatten();
+    let not_modified = etag.as_ref().is_some_and(|etag| {
+        request
+            .header("if-none-match")
+            .is_some_and(|tags| etag_matches(tags, etag))
+    });
+    let mut response = match request.method.as_str() {
+        "GET" => {
+            if path == "/" {
+                let duration_ms = start.elapsed().as_millis();
+                metrics.record(200, Some(duration_ms));
+                write_html_response(stream, API_DOCS_HTML, keep_alive).await?;
+                if access_log {
+                    let client = client_ip(stream.peer(), Some(&request), config.trusted_proxies);
+                    let bytes = API_DOCS_HTML.len();
+                    access_log::log(client, Some(&request), 200, bytes, Some(duration_ms));
+                }
+                return Ok(keep_alive);
+            }
 
-    let response = match path {
-        "/suggest" => suggest::handle_suggest(database.as_ref(), query),
-        "/lookup" => lookup::handle_lookup(database.as_ref(), query),
-        "/localities" => localities_list::handle_localities(database.as_ref()),
-        "/municipalities" => municipalities::handle_municipalities(database.as_ref()),
-        _ => Response::new(404, json_error("not found")),
+            match path {
+                "/health" => health::handle_health(database),
+                "/metrics" => metrics::handle_metrics(metrics),
+                "/suggest" => suggest::handle_suggest(database, config, suggest_cache, query),
+                "/lookup" => lookup::handle_lookup(database, query),
+                "/lookup/batch" => ApiError::MethodNotAllowed.into(),
+                "/reverse" => reverse::handle_reverse(database, config, query),
+                "/localities" => localities_list::handle_localities(database),
+                "/municipalities" => municipalities::handle_municipalities(database),
+                "/stats" => stats::handle_stats(database),
+                "/openapi.json" => openapi::handle_openapi(),
+                _ => ApiError::NotFound.into(),
+            }
+        }
+        "POST" => match read_body(stream, buffer, &request).await? {
+            Ok(body) => match path {
+                "/suggest" => suggest::handle_suggest_body(database, config, suggest_cache, &body),
+                "/lookup" => lookup::handle_lookup_body(database, &body),
+                "/lookup/batch" => lookup::handle_lookup_batch(database, &body),
+                _ => ApiError::MethodNotAllowed.into(),
+            },
+            Err(error) => {
+                // The body was not (fully) read, so the stream is out of sync.
+                keep_alive = false;
+                unread_body = true;
+                error.into()
+            }
+        },
+        _ => {
+            // A request body may follow that we don't read, so never reuse the connection.
+            keep_alive = false;
+            ApiError::MethodNotAllowed.into()
+        }
     };
+    // The precondition only applies to responses that would succeed.
+    if response.status_code == 200 && not_modified {
+        response = Response::not_modified();
+    }
+    if matches!(response.status_code, 200 | 304) {
+        response.etag = etag;
+    }
+    // Shutdown may have started while the body was read.
+    keep_alive &= !*stopping.borrow();
 
     let duration_ms = start.elapsed().as_millis();
     write_response(
         stream,
-        response.status_code,
-        &response.body,
+        metrics,
+        config,
+        &response,
         Some(duration_ms),
+        Some(&request),
+        keep_alive,
     )
     .await?;
-    Ok(())
+    if unread_body {
+        discard_remaining(stream).await;
+    }
+    Ok(keep_alive)
+}
+
+/// Read the POST body announced by `Content-Length`, starting with any bytes
+/// already buffered after the request head.
+///
+/// Returns the error to answer with when the length is missing, too large or
+/// the client closes the connection early.
+async fn read_body(
+    stream: &mut impl Connection,
+    buffer: &mut HeadBuffer,
+    request: &RequestHead,
+) -> std::io::Result<Result<Vec<u8>, ApiError>> {
+    let Some(length) = request
+        .header("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+    else {
+        return Ok(Err(ApiError::LengthRequired));
+    };
+
+    if length > MAX_BODY_BYTES {
+        return Ok(Err(ApiError::RequestBodyTooLarge));
+    }
+
+    while buffer.len() < length {
+        if buffer.read_from(stream).await? == 0 {
+            return Ok(Err(ApiError::IncompleteRequestBody));
+        }
+    }
+
+    let body = buffer.as_slice()[..length].to_vec();
+    buffer.consume(length);
+    Ok(Ok(body))
+}
+
+/// Request head bytes, kept on the stack while they fit in
+/// [`STACK_REQUEST_BYTES`] and spilled to a growable heap buffer beyond that.
+struct HeadBuffer {
+    stack: [u8; STACK_REQUEST_BYTES],
+    stack_len: usize,
+    heap: Vec<u8>,
+}
+
+impl HeadBuffer {
+    fn new() -> Self {
+        Self {
+            stack: [0u8; STACK_REQUEST_BYTES],
+            stack_len: 0,
+            heap: Vec::new(),
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        if self.heap.is_empty() {
+            &self.stack[..self.stack_len]
+        } else {
+            &self.heap
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop the first `count` bytes, keeping any pipelined input after them.
+    fn consume(&mut self, count: usize) {
+        if self.heap.is_empty() {
+            self.stack.copy_within(count..self.stack_len, 0);
+            self.stack_len -= count;
+        } else {
+            self.heap.drain(..count);
+            if self.heap.is_empty() {
+                self.stack_len = 0;
+            }
+        }
+    }
+
+    /// Read the next chunk from `stream`, returning the number of bytes read.
+    async fn read_from(&mut self, stream: &mut impl Connection) -> std::io::Result<usize> {
+        if self.heap.is_empty() && self.stack_len < STACK_REQUEST_BYTES {
+            let read = stream.read(&mut self.stack[self.stack_len..]).await?;
+            self.stack_len += read;
+            return Ok(read);
+        }
+
+        if self.heap.is_empty() {
+            self.heap.extend_from_slice(&self.stack);
+        }
+        let mut chunk = [0u8; STACK_REQUEST_BYTES];
+        let read = stream.read(&mut chunk).await?;
+        self.heap.extend_from_slice(&chunk[..read]);
+        Ok(read)
+    }
+}
+
+/// Drain (a bounded amount of) unread input so closing the socket sends a FIN
+/// rather than a RST that would discard the response we just wrote.
+async fn discard_remaining(stream: &mut impl Connection) {
+    let mut chunk = [0u8; STACK_REQUEST_BYTES];
+    let mut discarded = 0;
+    while discarded < MAX_DISCARD_BYTES {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => discarded += read,
+        }
+    }
+}
+
+/// `Connection` header line(s) for a response.
+fn connection_headers(keep_alive: bool) -> String {
+    if keep_alive {
+        format!(
+            "Connection: keep-alive\r\nKeep-Alive: timeout={}\r\n",
+            KEEP_ALIVE_TIMEOUT.as_secs()
+        )
+    } else {
+        "Connection: close\r\n".to_string()
+    }
+}
+
+/// Write the `204 No Content` answer to a CORS preflight request.
+async fn write_preflight_response(
+    stream: &mut impl Connection,
+    cors_origin: &str,
+    keep_alive: bool,
+) -> std::io::Result<()> {
+    let header = format!(
+        "HTTP/1.1 204 No Content\r\nAccess-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nAccess-Control-Max-Age: {CORS_MAX_AGE_SECS}\r\n{}\r\n",
+        cors_origin,
+        connection_headers(keep_alive)
+    );
+    stream.write_all(header.as_bytes()).await?;
+    if keep_alive {
+        Ok(())
+    } else {
+        stream.shutdown().await
+    }
 }
 
-/// Write an HTML response and close the connection.
+/// Write an HTML response, closing the connection unless `keep_alive` is set.
 async fn write_html_response(
-    stream: &mut tokio::net::TcpStream,
+    stream: &mut impl Connection,
     body: &str,
+    keep_alive: bool,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let header = format!(
-        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
-        body.len()
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n{}\r\n",
+        body.len(),
+        connection_headers(keep_alive)
     );
     stream.write_all(header.as_bytes()).await?;
     stream.write_all(body.as_bytes()).await?;
-    stream.shutdown().await?;
+    if !keep_alive {
+        stream.shutdown().await?;
+    }
     Ok(())
 }
 
-/// Write the HTTP response with JSON body and close the connection.
+/// Write the HTTP response, closing the connection unless `keep_alive` is set.
+///
+/// Every response is counted in `metrics` and logged. The body is gzipped
+/// when `request` accepts it; `request` is `None` for responses sent before
+/// a request head was read.
 async fn write_response(
-    stream: &mut tokio::net::TcpStream,
-    status_code: u16,
-    body: &str,
+    stream: &mut impl Connection,
+    metrics: &Metrics,
+    config: &ServerConfig,
+    response: &Response,
     duration_ms: Option<u128>,
+    request: Option<&RequestHead>,
+    keep_alive: bool,
 ) -> std::io::Result<()> {
+    let status_code = response.status_code;
+    let body = response.body.as_str();
+    metrics.record(status_code, duration_ms);
+
     let status_text = match status_code {
         200 => "OK",
+        304 => "Not Modified",
         400 => "Bad Request",
         404 => "Not Found",
         405 => "Method Not Allowed",
         408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
         _ => "Internal Server Error",
     };
 
-    if !logging_disabled() {
+    // Small bodies are sent as-is: gzip framing would outweigh the savings.
+    let gzip = request.is_some_and(RequestHead::accepts_gzip);
+    let compressible = body.len() >= GZIP_MIN_BYTES;
+    let (payload, encoding_headers) = if gzip && compressible {
+        (
+            Cow::Owned(gzip_bytes(body.as_bytes())?),
+            "Content-Encoding: gzip\r\nVary: Accept-Encoding\r\n",
+        )
+    } else if compressible {
+        (Cow::Borrowed(body.as_bytes()), "Vary: Accept-Encoding\r\n")
+    } else {
+        (Cow::Borrowed(body.as_bytes()), "")
+    };
+
+    if config.access_log {
+        let client = client_ip(stream.peer(), request, config.trusted_proxies);
+        access_log::log(client, request, status_code, payload.len(), duration_ms);
+    } else {
+        // Server faults are errors, rejected requests warnings.
         let preview = log_preview(body);
-        if status_code == 200 {
-            if let Some(duration_ms) = duration_ms {
-                println!(
-                    "[bag-address-lookup] successful lookup ({} ms): {}",
-                    duration_ms, preview
-                );
-            } else {
-                println!("[bag-address-lookup] successful lookup: {}", preview);
-            }
-        } else if let Some(duration_ms) = duration_ms {
-            eprintln!(
-                "[bag-address-lookup] error {} ({} ms): {}",
-                status_code, duration_ms, preview
-            );
-        } else {
-            eprintln!("[bag-address-lookup] error {}: {}", status_code, preview);
+        match status_code {
+            500.. => error!(status = status_code, duration_ms, body = %preview, "request failed"),
+            400.. => warn!(status = status_code, duration_ms, body = %preview, "request rejected"),
+            _ => info!(status = status_code, duration_ms, body = %preview, "request served"),
         }
     }
 
+    let etag_header = response
+        .etag
+        .as_ref()
+        .map_or_else(String::new, |etag| format!("ETag: {etag}\r\n"));
+    // A 304 has no body, and its headers mustn't describe an empty one.
+    let content_headers = if status_code == 304 {
+        String::new()
+    } else {
+        format!(
+            "Content-Type: {}\r\nContent-Length: {}\r\n",
+            response.content_type,
+            payload.len()
+        )
+    };
     let header = format!(
-        "HTTP/1.1 {status_code} {status_text}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
-        body.len()
+        "HTTP/1.1 {status_code} {status_text}\r\n{content_headers}{encoding_headers}{etag_header}Access-Control-Allow-Origin: {}\r\n{}\r\n",
+        config.cors,
+        connection_headers(keep_alive)
     );
 
     stream.write_all(header.as_bytes()).await?;
-    stream.write_all(body.as_bytes()).await?;
-    stream.shutdown().await
+    stream.write_all(&payload).await?;
+    if keep_alive {
+        Ok(())
+    } else {
+        stream.shutdown().await
+    }
+}
+
+/// Weak entity tag for responses built from the database with `checksum`.
+///
+/// It is weak because the gzip-compressed and plain bodies share it.
+fn etag_for(checksum: u32) -> String {
+    format!("W/\"{checksum:08x}\"")
+}
+
+/// Whether an `If-None-Match` header value lists `etag`, using the weak
+/// comparison RFC 9110 prescribes for it.
+fn etag_matches(header: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    header.trim() == "*" || header.split(',').any(|tag| opaque(tag) == opaque(etag))
+}
+
+/// Compress `bytes` into a gzip member.
+fn gzip_bytes(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 2), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
 }
 
 const API_DOCS_HTML: &str = include_str!("api_docs.html");
@@ -292,27 +1382,59 @@ fn find_header_end(buffer: &[u8]) -> Option<usize> {
 }
 
 /// JSON for a successful lookup response.
-pub(crate) fn json_ok(public_space: &str, locality: &str) -> String {
-    serde_json::to_string(&json!({ "pr": public_space, "wp": locality }))
-        .expect("serialize ok response")
+pub(crate) fn json_ok(
+    address: &AddressResult<'_>,
+    suffix: Option<&HouseNumberSuffix>,
+    geo: bool,
+) -> String {
+    serde_json::to_string(&address_json(address, suffix, geo)).expect("serialize ok response")
 }
 
-/// JSON for an error response.
-pub(crate) fn json_error(message: &str) -> String {
-    serde_json::to_string(&json!({ "error": message })).expect("serialize error response")
+/// JSON object for a resolved address, with `gm` added when the locality
+/// belongs to a known municipality and `hl` / `toev` when the lookup matched a
+/// house letter or addition. With `geo`, `lat` / `lon` (WGS84) are added when
+/// the matched range has a position.
+pub(crate) fn address_json(
+    address: &AddressResult<'_>,
+    suffix: Option<&HouseNumberSuffix>,
+    geo: bool,
+) -> serde_json::Value {
+    let mut value = json!({ "pr": address.public_space, "wp": address.locality });
+    if let Some(municipality) = address.municipality {
+        value["gm"] = json!(municipality);
+    }
+    if geo && let Some(point) = address.point {
+        // Six decimals is about 10 cm, finer than the stored decimetres.
+        let (lat, lon) = point.to_wgs84();
+        value["lat"] = json!((lat * 1e6).round() / 1e6);
+        value["lon"] = json!((lon * 1e6).round() / 1e6);
+    }
+    if let Some(suffix) = suffix {
+        if let Some(letter) = suffix.letter() {
+            value["hl"] = json!(letter.to_string());
+        }
+        if let Some(addition) = suffix.addition() {
+            value["toev"] = json!(addition);
+        }
+    }
+    value
 }
 
 #[cfg(test)]
 pub(crate) mod test_utils {
-    use super::handle_connection;
-    use crate::{Database, DatabaseHandle, NumberRange, encode_pc};
+    use super::{
+        DEFAULT_REQUEST_TIMEOUT, ServerConfig, handle_connection, metrics::Metrics,
+        suggest_cache::SuggestCache,
+    };
+    use crate::{Database, DatabaseHandle, HouseNumberSuffix, NumberRange, RdPoint, encode_pc};
     use std::sync::Arc;
     use tokio::{
         io::{AsyncReadExt, AsyncWriteExt},
         net::{TcpListener, TcpStream},
+        sync::watch,
     };
 
-    pub(crate) fn test_database() -> DatabaseHandle {
+    pub(crate) fn test_database() -> DatabaseHandle<'static> {
         let localities = vec![
             "Amsterdam".to_string(),
             "Bolsward".to_string(),
@@ -349,6 +1471,26 @@ pub(crate) mod test_utils {
         let municipality_province = vec![1, 3, 0, 2];
         let locality_had_suffix = vec![false, false, false, false];
         let municipality_had_suffix = vec![false, false, false, false];
+        // 1234AB 10A and 1234AB 11-2
+        let house_number_suffixes = vec![
+            HouseNumberSuffix {
+                postal_code: encode_pc(b"1234AB"),
+                house_number: 10,
+                letter: b'A',
+                addition: [0; 4],
+            },
+            HouseNumberSuffix {
+                postal_code: encode_pc(b"1234AB"),
+                house_number: 11,
+                letter: 0,
+                addition: *b"2\0\0\0",
+            },
+        ];
+        // The Stationsstraat range lies on the Dam
+        let range_points = vec![RdPoint {
+            x: 1_213_180,
+            y: 4_873_620,
+        }];
 
         DatabaseHandle::Decoded(Database {
             localities,
@@ -362,24 +1504,612 @@ pub(crate) mod test_utils {
             municipality_province,
             locality_had_suffix,
             municipality_had_suffix,
+            house_number_suffixes,
+            range_points,
+            checksum: Some(0x0123_abcd),
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
         })
     }
 
-    pub(crate) async fn send_request(request: &str, db: Arc<DatabaseHandle>) -> String {
+    pub(crate) async fn send_request(request: &str, db: Arc<DatabaseHandle<'static>>) -> String {
+        String::from_utf8(send_request_bytes(request, db).await).unwrap()
+    }
+
+    /// Like [`send_request`], for responses whose body may not be UTF-8.
+    pub(crate) async fn send_request_bytes(
+        request: &str,
+        db: Arc<DatabaseHandle<'static>>,
+    ) -> Vec<u8> {
+        send_request_with_config(request, db, ServerConfig::default()).await
+    }
+
+    /// Like [`send_request_bytes`], serving with the settings of `config`.
+    pub(crate) async fn send_request_with_config(
+        request: &str,
+        db: Arc<DatabaseHandle<'static>>,
+        config: ServerConfig,
+    ) -> Vec<u8> {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
 
         let server = tokio::spawn(async move {
             let (mut stream, _) = listener.accept().await.unwrap();
-            let _ = handle_connection(&mut stream, db).await;
+            let (_stop, stopping) = watch::channel(false);
+            let _ = handle_connection(
+                &mut stream,
+                db,
+                &Metrics::default(),
+                &config,
+                &SuggestCache::default(),
+                DEFAULT_REQUEST_TIMEOUT,
+                stopping,
+            )
+            .await;
         });
 
         let mut client = TcpStream::connect(addr).await.unwrap();
         client.write_all(request.as_bytes()).await.unwrap();
         client.shutdown().await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        let _ = server.await;
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ApiError, CONNECTION_QUEUE_TIMEOUT, ConnectionLimits, KEEP_ALIVE_TIMEOUT, Response,
+        ServerBuilder, ServerConfig, client_ip,
+        connection::Listeners,
+        etag_matches,
+        reload::DatabaseSlot,
+        request::RequestHead,
+        serve_connections,
+        test_utils::{send_request, send_request_with_config, test_database},
+    };
+    #[cfg(unix)]
+    use crate::test_support::TempPath;
+    use std::{
+        error::Error,
+        net::SocketAddr,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::oneshot,
+        task::JoinHandle,
+    };
+
+    const REQUEST_LINE: &[u8] = b"GET /lookup?pc=1234AB&n=10 HTTP/1.1\r\n";
+    const REQUEST_HEADERS: &[u8] = b"Host: localhost\r\nConnection: close\r\n\r\n";
+
+    /// Run the accept loop on a local port until the returned sender fires.
+    async fn start_server(
+        limits: ConnectionLimits,
+    ) -> (
+        SocketAddr,
+        oneshot::Sender<()>,
+        JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_connections(
+            listener,
+            Arc::new(DatabaseSlot::new(test_database())),
+            async move {
+                let _ = shutdown.await;
+                Ok(())
+            },
+            limits,
+        ));
+        (addr, trigger, server)
+    }
+
+    async fn read_response(client: &mut (impl AsyncRead + Unpin)) -> String {
         let mut response = String::new();
         client.read_to_string(&mut response).await.unwrap();
-        let _ = server.await;
         response
     }
+
+    #[tokio::test]
+    async fn shutdown_lets_open_requests_finish() {
+        let (addr, trigger, server) = start_server(ConnectionLimits {
+            max_connections: 16,
+            request_timeout: Duration::from_secs(5),
+            shutdown_grace: Duration::from_secs(5),
+        })
+        .await;
+
+        // Start a request, shut down while it is still being sent, then finish it.
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(REQUEST_LINE).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        trigger.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.write_all(REQUEST_HEADERS).await.unwrap();
+
+        let response = read_response(&mut client).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"pr\":\"Stationsstraat\""));
+
+        server.await.unwrap().unwrap();
+        // The listener is closed once shutdown starts.
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_idle_keep_alive_connections() {
+        let (addr, trigger, server) = start_server(ConnectionLimits {
+            max_connections: 16,
+            request_timeout: Duration::from_secs(5),
+            shutdown_grace: Duration::from_secs(10),
+        })
+        .await;
+
+        // One client idles after its first request, another is halfway
+        // through one when shutdown starts.
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        idle.write_all(REQUEST_LINE).await.unwrap();
+        idle.write_all(b"Host: localhost\r\n\r\n").await.unwrap();
+        let mut buffer = [0; 4096];
+        let read = idle.read(&mut buffer).await.unwrap();
+        assert!(buffer[..read].starts_with(b"HTTP/1.1 200 OK"));
+        let mut busy = TcpStream::connect(addr).await.unwrap();
+        busy.write_all(REQUEST_LINE).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let start = Instant::now();
+        trigger.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        busy.write_all(b"Host: localhost\r\n\r\n").await.unwrap();
+        let response = read_response(&mut busy).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Connection: close\r\n"), "{response}");
+        assert_eq!(idle.read(&mut buffer).await.unwrap(), 0);
+
+        server.await.unwrap().unwrap();
+        assert!(start.elapsed() < KEEP_ALIVE_TIMEOUT / 5);
+    }
+
+    #[tokio::test]
+    async fn requests_are_served_on_every_listener() {
+        let listeners = vec![
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+        ];
+        let addrs: Vec<SocketAddr> = listeners
+            .iter()
+            .map(|listener| listener.local_addr().unwrap())
+            .collect();
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_connections(
+            Listeners::from(listeners),
+            Arc::new(DatabaseSlot::new(test_database())),
+            async move {
+                let _ = shutdown.await;
+                Ok(())
+            },
+            ConnectionLimits {
+                max_connections: 16,
+                request_timeout: Duration::from_secs(5),
+                shutdown_grace: Duration::from_secs(5),
+            },
+        ));
+
+        // Clients of both listeners are served at the same time.
+        let clients: Vec<_> = addrs
+            .iter()
+            .flat_map(|&addr| [addr; 8])
+            .map(|addr| {
+                tokio::spawn(async move {
+                    let mut client = TcpStream::connect(addr).await.unwrap();
+                    client.write_all(REQUEST_LINE).await.unwrap();
+                    client.write_all(REQUEST_HEADERS).await.unwrap();
+                    read_response(&mut client).await
+                })
+            })
+            .collect();
+        for client in clients {
+            assert!(client.await.unwrap().starts_with("HTTP/1.1 200 OK"));
+        }
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        // Shutdown closes all listeners.
+        for addr in &addrs {
+            assert!(TcpStream::connect(addr).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn built_servers_serve_their_database_file() {
+        let server = ServerBuilder::new()
+            .bind("127.0.0.1:0")
+            .quiet(true)
+            .max_connections(4)
+            .metrics_prefix("built")
+            .database_path(concat!(env!("CARGO_MANIFEST_DIR"), "/test/bag.bin"))
+            .build()
+            .await
+            .unwrap();
+        let addr = server.local_addrs().unwrap()[0];
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let server = tokio::spawn(server.run_with_shutdown(async move {
+            let _ = shutdown.await;
+            Ok(())
+        }));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"GET /lookup?pc=1234AB&n=56 HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        client.write_all(REQUEST_HEADERS).await.unwrap();
+        let response = read_response(&mut client).await;
+        assert!(
+            response.contains("\"pr\":\"Abel Eppensstraat\""),
+            "{response}"
+        );
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"GET /metrics HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        client.write_all(REQUEST_HEADERS).await.unwrap();
+        let response = read_response(&mut client).await;
+        assert!(
+            response.contains("\nbuilt_requests_total 1\n"),
+            "{response}"
+        );
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn builder_settings_override_the_environment() {
+        // No other test reads these variables.
+        unsafe {
+            std::env::set_var("BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE", "30");
+            std::env::set_var("BAG_ADDRESS_LOOKUP_REVERSE_RADIUS", "1000");
+        }
+        let from_env = ServerBuilder::new();
+        let builder = ServerBuilder::new()
+            .shutdown_grace(Duration::from_secs(2))
+            .trusted_proxies(2)
+            .access_log(true)
+            .max_request_bytes(4096)
+            .reverse_radius(50.0)
+            .metrics_prefix("custom")
+            .suggest_cache_size(0);
+        unsafe {
+            std::env::remove_var("BAG_ADDRESS_LOOKUP_SHUTDOWN_GRACE");
+            std::env::remove_var("BAG_ADDRESS_LOOKUP_REVERSE_RADIUS");
+        }
+
+        assert_eq!(from_env.config.shutdown_grace, Duration::from_secs(30));
+        assert_eq!(from_env.config.reverse_radius, 1000.0);
+        assert_eq!(
+            builder.config,
+            ServerConfig {
+                shutdown_grace: Duration::from_secs(2),
+                trusted_proxies: 2,
+                access_log: true,
+                max_request_bytes: 4096,
+                reverse_radius: 50.0,
+                metrics_prefix: "custom".to_string(),
+                suggest_cache_size: 0,
+                ..from_env.config
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn builders_need_an_address() {
+        let result = ServerBuilder::new().quiet(true).build().await;
+        assert!(result.is_err());
+    }
+
+    #[cfg(any(feature = "create", feature = "no_embedded_db"))]
+    #[tokio::test]
+    async fn builds_without_an_embedded_database_need_a_file() {
+        let result = ServerBuilder::new()
+            .bind("127.0.0.1:0")
+            .config(ServerConfig {
+                quiet: true,
+                ..ServerConfig::default()
+            })
+            .build()
+            .await;
+        let Err(err) = result else {
+            panic!("served without a database");
+        };
+        assert!(
+            err.to_string().contains("BAG_ADDRESS_LOOKUP_DATABASE"),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn requests_are_served_over_unix_sockets() {
+        let path = TempPath::new("service.sock");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_connections(
+            listener,
+            Arc::new(DatabaseSlot::new(test_database())),
+            async move {
+                let _ = shutdown.await;
+                Ok(())
+            },
+            ConnectionLimits {
+                max_connections: 16,
+                request_timeout: Duration::from_secs(5),
+                shutdown_grace: Duration::from_secs(5),
+            },
+        ));
+
+        let mut client = tokio::net::UnixStream::connect(&path).await.unwrap();
+        client.write_all(REQUEST_LINE).await.unwrap();
+        client.write_all(REQUEST_HEADERS).await.unwrap();
+        let response = read_response(&mut client).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"pr\":\"Stationsstraat\""));
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn connections_beyond_the_limit_wait_then_get_503() {
+        let (addr, trigger, server) = start_server(ConnectionLimits {
+            max_connections: 1,
+            request_timeout: Duration::from_secs(5),
+            shutdown_grace: Duration::from_secs(5),
+        })
+        .await;
+
+        // The first client holds the only slot with an unfinished request.
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(REQUEST_LINE).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // A second client waits for the slot, and is turned away when it
+        // doesn't free up in time.
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        second.write_all(REQUEST_LINE).await.unwrap();
+        second.write_all(REQUEST_HEADERS).await.unwrap();
+        let response = read_response(&mut second).await;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        assert!(response.contains("\"code\":\"TOO_MANY_CONNECTIONS\""));
+
+        // Finishing the first request releases the slot for the next client.
+        first.write_all(REQUEST_HEADERS).await.unwrap();
+        assert!(
+            read_response(&mut first)
+                .await
+                .starts_with("HTTP/1.1 200 OK")
+        );
+        let mut third = TcpStream::connect(addr).await.unwrap();
+        third.write_all(REQUEST_LINE).await.unwrap();
+        third.write_all(REQUEST_HEADERS).await.unwrap();
+        assert!(
+            read_response(&mut third)
+                .await
+                .starts_with("HTTP/1.1 200 OK")
+        );
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_does_not_wait_for_a_connection_slot() {
+        let (addr, trigger, server) = start_server(ConnectionLimits {
+            max_connections: 1,
+            request_timeout: Duration::from_secs(5),
+            shutdown_grace: Duration::from_secs(5),
+        })
+        .await;
+
+        // The first client holds the only slot, the second waits for it.
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(REQUEST_LINE).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Shutdown closes the listener and the waiting connection well
+        // before the slot wait times out.
+        trigger.send(()).unwrap();
+        tokio::time::sleep(CONNECTION_QUEUE_TIMEOUT / 5).await;
+        assert!(TcpStream::connect(addr).await.is_err());
+        let mut response = Vec::new();
+        let _ = second.read_to_end(&mut response).await;
+        assert!(response.is_empty());
+
+        first.write_all(REQUEST_HEADERS).await.unwrap();
+        assert!(
+            read_response(&mut first)
+                .await
+                .starts_with("HTTP/1.1 200 OK")
+        );
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn stalled_requests_time_out() {
+        let (addr, trigger, server) = start_server(ConnectionLimits {
+            max_connections: 16,
+            request_timeout: Duration::from_millis(200),
+            shutdown_grace: Duration::from_secs(5),
+        })
+        .await;
+
+        // The client sends half a request line and then goes quiet.
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"GET /lookup?pc=1234AB").await.unwrap();
+
+        let response = tokio::time::timeout(Duration::from_secs(5), read_response(&mut client))
+            .await
+            .expect("server should time out the stalled request");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(response.contains("\"code\":\"REQUEST_TIMEOUT\""));
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn handlers_use_the_server_config() {
+        let config = ServerConfig {
+            suggest_limit: 1,
+            cors: "https://example.org".to_string(),
+            ..ServerConfig::default()
+        };
+        let response = send_request_with_config(
+            "GET /suggest?wp=dam HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            Arc::new(test_database()),
+            config,
+        )
+        .await;
+        let response = String::from_utf8(response).unwrap();
+
+        assert!(response.contains("Access-Control-Allow-Origin: https://example.org\r\n"));
+        // Without the limit "Rotterdam" would follow.
+        assert!(response.ends_with("[\"Amsterdam\"]"));
+    }
+
+    #[tokio::test]
+    async fn request_heads_are_capped_by_the_server_config() {
+        let config = ServerConfig {
+            max_request_bytes: 2048,
+            ..ServerConfig::default()
+        };
+        let request = format!(
+            "GET /lookup?pc=1234AB&n=10 HTTP/1.1\r\nUser-Agent: {}\r\n\r\n",
+            "a".repeat(4096)
+        );
+        let response = send_request_with_config(&request, Arc::new(test_database()), config).await;
+        assert!(response.starts_with(b"HTTP/1.1 431 "));
+
+        // The default cap of 8 KiB lets the same request through.
+        let response =
+            send_request_with_config(&request, Arc::new(test_database()), ServerConfig::default())
+                .await;
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn forwarded_clients_are_only_used_behind_a_trusted_proxy() {
+        let peer = Some("10.0.0.2:51234".parse().unwrap());
+        let request = RequestHead::parse(
+            b"GET / HTTP/1.1\r\nX-Forwarded-For: 203.0.113.9, 192.0.2.7\r\n\r\n",
+        );
+
+        // The proxy appended the real client; the left-most entry is spoofed.
+        assert_eq!(
+            client_ip(peer, Some(&request), 1),
+            Some("192.0.2.7".parse().unwrap())
+        );
+        assert_eq!(
+            client_ip(peer, Some(&request), 0),
+            Some("10.0.0.2".parse().unwrap())
+        );
+        // Requests without the header fall back to the socket peer.
+        let request = RequestHead::parse(b"GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(
+            client_ip(peer, Some(&request), 1),
+            Some("10.0.0.2".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn internal_errors_hide_their_details() {
+        let response = Response::from(ApiError::Internal);
+        assert_eq!(response.status_code, 500);
+        assert_eq!(
+            response.body,
+            r#"{"code":"INTERNAL_ERROR","error":"internal error"}"#
+        );
+    }
+
+    #[test]
+    fn etags_match_weakly() {
+        assert!(etag_matches("W/\"0123abcd\"", "W/\"0123abcd\""));
+        assert!(etag_matches("\"ffff\", \"0123abcd\"", "W/\"0123abcd\""));
+        assert!(etag_matches("*", "W/\"0123abcd\""));
+        assert!(!etag_matches("W/\"ffff\"", "W/\"0123abcd\""));
+    }
+
+    #[tokio::test]
+    async fn conditional_lookups_are_answered_with_304() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\r\nETag: W/\"0123abcd\"\r\n"));
+
+        let response = send_request(
+            "GET /lookup?pc=1234AB&n=10 HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: W/\"0123abcd\"\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 304 Not Modified"));
+        assert!(response.contains("\r\nETag: W/\"0123abcd\"\r\n"));
+        assert!(!response.contains("Content-Length"));
+        assert!(response.ends_with("\r\n\r\n"));
+
+        // A stale tag gets the full response; errors carry no tag.
+        let response = send_request(
+            "GET /suggest?wp=Amst HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: W/\"ffff\"\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let response = send_request(
+            "GET /lookup?pc=9999ZZ&n=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(!response.contains("ETag"));
+    }
+
+    #[tokio::test]
+    async fn failing_conditional_requests_get_their_error() {
+        let db = Arc::new(test_database());
+        for (request, status) in [
+            ("GET /lookup?pc=bogus&n=1", "400 Bad Request"),
+            ("GET /lookup?pc=9999ZZ&n=1", "404 Not Found"),
+            ("GET /suggest?n=1", "400 Bad Request"),
+        ] {
+            for tag in ["*", "W/\"0123abcd\""] {
+                let response = send_request(
+                    &format!(
+                        "{request} HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: {tag}\r\n\r\n"
+                    ),
+                    db.clone(),
+                )
+                .await;
+                assert!(
+                    response.starts_with(&format!("HTTP/1.1 {status}")),
+                    "{request} {tag}: {response}"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,289 @@
+//! OpenAPI 3.0 description of the lookup and suggest endpoints, served at
+//! `/openapi.json`.
+//!
+//! The parameters are those the handlers read, and the error responses are
+//! derived from the [`ApiError`] variants they return, so the document can't
+//! drift from the handlers on either.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use super::{ApiError, Response, lookup, suggest};
+
+/// Handle the `/openapi.json` endpoint.
+pub(crate) fn handle_openapi() -> Response {
+    Response::new(
+        200,
+        serde_json::to_string(&document()).expect("serialize OpenAPI document"),
+    )
+}
+
+/// Errors `GET /lookup` responds with.
+fn lookup_errors() -> Vec<ApiError> {
+    vec![
+        ApiError::InvalidQueryEncoding,
+        ApiError::EmptyQuery,
+        ApiError::UnknownParameters(String::new()),
+        ApiError::MissingPostalCode,
+        ApiError::MissingHouseNumber,
+        ApiError::InvalidHouseNumber,
+        ApiError::InvalidHouseNumberRange,
+        ApiError::TooManyHouseNumbers,
+        ApiError::InvalidPostalCode,
+        ApiError::InvalidHouseLetter,
+        ApiError::InvalidHouseNumberAddition,
+        ApiError::PostalCodeNotFound,
+        ApiError::HouseNumberNotFound,
+        ApiError::AddressNotFound,
+    ]
+}
+
+/// Errors `GET /suggest` responds with.
+fn suggest_errors() -> Vec<ApiError> {
+    vec![
+        ApiError::InvalidQueryEncoding,
+        ApiError::EmptyQuery,
+        ApiError::UnknownParameters(String::new()),
+        ApiError::MissingWp,
+        ApiError::InvalidPostalCode,
+        ApiError::InvalidType,
+    ]
+}
+
+/// Schema and description of a query parameter of `path`, by name.
+fn describe_parameter(path: &str, name: &str) -> Option<(Value, &'static str)> {
+    let string = json!({ "type": "string" });
+    let flag = json!({ "type": "string", "enum": ["1", "true", "0", "false"] });
+    Some(match name {
+        "pc" if path == "/suggest" => (
+            json!({ "type": "string", "pattern": "^[0-9]{1,4}$|^[0-9]{4}[A-Za-z]{1,2}$" }),
+            "Postal code prefix, e.g. 12; only localities with an address under it \
+             are suggested, and no municipalities",
+        ),
+        "pc" => (
+            json!({ "type": "string", "pattern": "^[0-9]{4}[A-Za-z]{2}$" }),
+            "Postal code, e.g. 1234AB",
+        ),
+        "n" => (
+            json!({ "type": "string", "pattern": "^[0-9]+((,[0-9]+)*|-[0-9]+)$" }),
+            "House number; up to 50 comma-separated house numbers, answered with an \
+             object mapping each to its address or null; or a range like 10-20 of up \
+             to 500 numbers, answered with an array of the addresses found",
+        ),
+        "hl" => (string, "House letter, e.g. A; the address must have it"),
+        "toev" => (
+            json!({ "type": "string", "maxLength": 4 }),
+            "House number addition; the address must have it",
+        ),
+        "geo" => (flag, "Add the WGS84 position as lat and lon"),
+        "wp" => (string, "Locality or municipality name (prefix / fuzzy)"),
+        "municipalities" => (flag, "Include municipalities (default true)"),
+        "aliases" => (flag, "Match known alternative names (default false)"),
+        "limit" => (
+            json!({ "type": "integer", "minimum": 0, "maximum": 100 }),
+            "Maximum number of suggestions (default 10)",
+        ),
+        "min_score" => (
+            json!({ "type": "number", "minimum": 0, "maximum": 1 }),
+            "Minimum fuzzy match score, overriding the server default",
+        ),
+        "type" => (
+            json!({ "type": "string", "enum": ["locality", "street"] }),
+            "Suggest localities (default) or public space names",
+        ),
+        "verbose" => (
+            flag,
+            "Return objects with the name, score, municipality and province",
+        ),
+        "highlight" => (flag, "Return objects with the byte offsets of the match"),
+        "strict" => (flag, "Reject unknown query parameters"),
+        _ => return None,
+    })
+}
+
+/// Parameter objects for the query `keys` of the endpoint at `path`, plus
+/// `strict`.
+fn parameters(path: &str, keys: &[&str]) -> Vec<Value> {
+    keys.iter()
+        .chain(&["strict"])
+        .map(|&name| {
+            let (schema, description) = describe_parameter(path, name)
+                .unwrap_or_else(|| panic!("undocumented query parameter {name}"));
+            json!({
+                "name": name,
+                "in": "query",
+                "required": matches!((path, name), ("/lookup", "pc" | "n") | ("/suggest", "wp")),
+                "description": description,
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+/// Response objects for `errors`, grouped by status, each listing its codes.
+fn error_responses(errors: &[ApiError]) -> BTreeMap<String, Value> {
+    let mut codes: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+    for error in errors {
+        let (status, code, _) = error.parts();
+        codes.entry(status).or_default().push(code);
+    }
+    codes
+        .into_iter()
+        .map(|(status, codes)| {
+            let response = json!({
+                "description": codes.join(", "),
+                "content": { "application/json": { "schema": {
+                    "allOf": [
+                        { "$ref": "#/components/schemas/Error" },
+                        { "properties": { "code": { "enum": codes } } },
+                    ],
+                } } },
+            });
+            (status.to_string(), response)
+        })
+        .collect()
+}
+
+fn document() -> Value {
+    let mut lookup_responses = error_responses(&lookup_errors());
+    lookup_responses.insert(
+        "200".to_string(),
+        json!({
+            "description": "The address; for a list or range of house numbers, an \
+                            object or array of addresses",
+            "content": { "application/json": { "schema": { "oneOf": [
+                { "$ref": "#/components/schemas/Address" },
+                {
+                    "type": "object",
+                    "additionalProperties": {
+                        "allOf": [{ "$ref": "#/components/schemas/Address" }],
+                        "nullable": true,
+                    },
+                },
+                { "type": "array", "items": { "$ref": "#/components/schemas/Address" } },
+            ] } } },
+        }),
+    );
+
+    let mut suggest_responses = error_responses(&suggest_errors());
+    suggest_responses.insert(
+        "200".to_string(),
+        json!({
+            "description": "Matching names, best first; objects with verbose or highlight",
+            "content": { "application/json": { "schema": {
+                "type": "array",
+                "items": { "oneOf": [
+                    { "type": "string" },
+                    { "$ref": "#/components/schemas/Suggestion" },
+                ] },
+            } } },
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "BAG Address Lookup API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/lookup": { "get": {
+                "summary": "Look up an address by postal code and house number",
+                "parameters": parameters("/lookup", lookup::QUERY_KEYS),
+                "responses": lookup_responses,
+            } },
+            "/suggest": { "get": {
+                "summary": "Suggest locality, municipality or public space names",
+                "parameters": parameters("/suggest", suggest::QUERY_KEYS),
+                "responses": suggest_responses,
+            } },
+        },
+        "components": { "schemas": {
+            "Address": {
+                "type": "object",
+                "required": ["pr", "wp"],
+                "properties": {
+                    "pr": { "type": "string", "description": "Public space (street) name" },
+                    "wp": { "type": "string", "description": "Locality name" },
+                    "gm": { "type": "string", "description": "Municipality name, when known" },
+                    "hl": { "type": "string", "description": "Matched house letter" },
+                    "toev": { "type": "string", "description": "Matched house number addition" },
+                    "lat": { "type": "number", "description": "WGS84 latitude, with geo=1" },
+                    "lon": { "type": "number", "description": "WGS84 longitude, with geo=1" },
+                    "n": { "type": "integer", "description": "House number, in range results" },
+                },
+            },
+            "Suggestion": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "score": { "type": "number", "description": "Match score, with verbose=1" },
+                    "municipality": {
+                        "type": "string",
+                        "description": "Municipality of a locality, with verbose=1",
+                    },
+                    "province": {
+                        "type": "string",
+                        "description": "Province code of a locality or municipality, with verbose=1",
+                    },
+                    "highlight": {
+                        "type": "array",
+                        "items": { "type": "integer" },
+                        "description": "Byte offsets [start, end] of the match, with highlight=1",
+                    },
+                },
+            },
+            "Error": {
+                "type": "object",
+                "required": ["code", "error"],
+                "properties": {
+                    "code": { "type": "string", "description": "Stable machine-readable code" },
+                    "error": { "type": "string", "description": "Human-readable message" },
+                },
+            },
+        } },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{send_request, test_database};
+    use super::{describe_parameter, lookup, suggest};
+    use std::sync::Arc;
+
+    #[test]
+    fn every_query_parameter_is_documented() {
+        for name in lookup::QUERY_KEYS {
+            assert!(describe_parameter("/lookup", name).is_some(), "{name}");
+        }
+        for name in suggest::QUERY_KEYS {
+            assert!(describe_parameter("/suggest", name).is_some(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_document_describes_lookup() {
+        let db = Arc::new(test_database());
+        let response =
+            send_request("GET /openapi.json HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let document: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(document["openapi"], "3.0.3");
+
+        let lookup = &document["paths"]["/lookup"]["get"];
+        assert_eq!(lookup["parameters"][0]["name"], "pc");
+        let not_found = &lookup["responses"]["404"]["content"]["application/json"]["schema"];
+        assert_eq!(
+            not_found["allOf"][1]["properties"]["code"]["enum"],
+            serde_json::json!([
+                "POSTAL_CODE_NOT_FOUND",
+                "HOUSE_NUMBER_NOT_FOUND",
+                "ADDRESS_NOT_FOUND"
+            ])
+        );
+    }
+}
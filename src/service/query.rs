@@ -7,38 +7,101 @@
 //! form payload — as `form_urlencoded` does — silently rewrites a queried `+`
 //! into a space. We therefore percent-decode `%XX` escapes only and leave `+`
 //! untouched.
+//!
+//! A `%` that is not followed by two hex digits is not a valid escape (RFC 3986
+//! §2.1). Rather than guessing what the client meant, the whole query is
+//! rejected so the handler can answer with a 400.
 
 use percent_encoding::percent_decode_str;
 
+use super::ApiError;
+
+/// Error returned when a query component contains a malformed `%` escape.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct InvalidEscape;
+
 /// Parse a URI query string into `(key, value)` pairs.
 ///
 /// Pairs are separated by `&`; the first `=` in a pair splits the key from the
 /// value (so a value may itself contain `=`). Both sides are percent-decoded,
 /// with invalid UTF-8 replaced lossily. `+` is preserved literally. A pair with
 /// no `=` yields an empty value, and empty segments (e.g. from a trailing or
-/// doubled `&`) are skipped.
-pub(crate) fn parse_query(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+/// doubled `&`) are skipped. Any malformed `%` escape fails the whole query.
+pub(crate) fn parse_query(query: &str) -> Result<Vec<(String, String)>, InvalidEscape> {
     query
         .split('&')
         .filter(|segment| !segment.is_empty())
         .map(|segment| match segment.split_once('=') {
-            Some((key, value)) => (decode(key), decode(value)),
-            None => (decode(segment), String::new()),
+            Some((key, value)) => Ok((decode(key)?, decode(value)?)),
+            None => Ok((decode(segment)?, String::new())),
         })
+        .collect()
+}
+
+/// In strict mode, requested with `strict=1` (or `true`), fail when `pairs`
+/// holds keys other than `known` and `strict`, naming them in the error.
+/// Otherwise unknown keys are ignored.
+pub(crate) fn check_strict(pairs: &[(String, String)], known: &[&str]) -> Result<(), ApiError> {
+    let strict = pairs
+        .iter()
+        .any(|(key, value)| key == "strict" && matches!(value.as_str(), "1" | "true"));
+    if !strict {
+        return Ok(());
+    }
+
+    let mut unknown: Vec<&str> = pairs
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .filter(|key| *key != "strict" && !known.contains(key))
+        .collect();
+    unknown.sort_unstable();
+    unknown.dedup();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::UnknownParameters(format!(
+            "unknown query parameters: {}",
+            unknown.join(", ")
+        )))
+    }
 }
 
 /// Percent-decode a single query component, replacing invalid UTF-8 lossily.
-fn decode(value: &str) -> String {
-    percent_decode_str(value).decode_utf8_lossy().into_owned()
+fn decode(value: &str) -> Result<String, InvalidEscape> {
+    if !has_valid_escapes(value) {
+        return Err(InvalidEscape);
+    }
+    Ok(percent_decode_str(value).decode_utf8_lossy().into_owned())
+}
+
+/// Return true when every `%` in `value` starts a `%XX` hex escape.
+fn has_valid_escapes(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let valid = bytes
+                .get(i + 1..i + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+            if !valid {
+                return false;
+            }
+            i += 3;
+        } else {
+            i += 1;
+        }
+    }
+    true
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_query;
+    use super::{InvalidEscape, check_strict, parse_query};
+    use crate::service::ApiError;
 
-    /// Collect the parser output into an owned vector for assertions.
+    /// Unwrap the parser output into an owned vector for assertions.
     fn pairs(query: &str) -> Vec<(String, String)> {
-        parse_query(query).collect()
+        parse_query(query).unwrap()
     }
 
     #[test]
@@ -107,9 +170,36 @@ mod tests {
     }
 
     #[test]
-    fn invalid_percent_escape_is_kept_literally() {
-        // A truncated or malformed `%` escape is left as-is rather than erroring.
-        assert_eq!(pairs("wp=bad%2"), [("wp".into(), "bad%2".into())]);
-        assert_eq!(pairs("wp=x%GZy"), [("wp".into(), "x%GZy".into())]);
+    fn invalid_percent_escape_is_rejected() {
+        // A truncated or malformed `%` escape fails the whole query.
+        assert_eq!(parse_query("wp=bad%2"), Err(InvalidEscape));
+        assert_eq!(parse_query("wp=x%GZy"), Err(InvalidEscape));
+        assert_eq!(parse_query("w%p=x"), Err(InvalidEscape));
+        assert_eq!(parse_query("n=1&wp=100%"), Err(InvalidEscape));
+    }
+
+    #[test]
+    fn literal_percent_is_expressible_as_escape() {
+        assert_eq!(pairs("wp=100%25"), [("wp".into(), "100%".into())]);
+    }
+
+    #[test]
+    fn strict_mode_names_unknown_keys() {
+        let known = ["pc", "n"];
+        assert_eq!(check_strict(&pairs("pcode=1234AB&n=1"), &known), Ok(()));
+        assert_eq!(check_strict(&pairs("pc=1234AB&strict=1"), &known), Ok(()));
+        assert_eq!(
+            check_strict(&pairs("pcode=1234AB&n=1&x&strict=1"), &known),
+            Err(ApiError::UnknownParameters(
+                "unknown query parameters: pcode, x".to_string()
+            ))
+        );
+        // Repeated keys are named once, also when they are not adjacent.
+        assert_eq!(
+            check_strict(&pairs("pcode=1&n=2&pcode=3&strict=1"), &known),
+            Err(ApiError::UnknownParameters(
+                "unknown query parameters: pcode".to_string()
+            ))
+        );
     }
 }
@@ -0,0 +1,139 @@
+//! Swapping in a rebuilt database while the server keeps running.
+//!
+//! The server keeps the current database in a [`DatabaseSlot`] and hands
+//! every new connection a clone of its `Arc`. A reload replaces that `Arc`;
+//! connections that already hold the old one keep using it until they close,
+//! so no request sees a half-loaded database.
+
+use std::{
+    error::Error,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::database::DatabaseHandle;
+
+/// The database served to new connections.
+pub(crate) struct DatabaseSlot {
+    current: RwLock<Arc<DatabaseHandle<'static>>>,
+}
+
+impl DatabaseSlot {
+    pub(crate) fn new(database: DatabaseHandle<'static>) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(database)),
+        }
+    }
+
+    /// The database a new connection should use.
+    pub(crate) fn snapshot(&self) -> Arc<DatabaseHandle<'static>> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Serve `database` to all connections accepted from now on.
+    pub(crate) fn replace(&self, database: DatabaseHandle<'static>) {
+        let database = Arc::new(database);
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = database;
+    }
+}
+
+/// Load the database at `path` and swap it in. The current database stays in
+/// place when the file cannot be loaded or is empty.
+pub(crate) fn reload_from_path(
+    slot: &DatabaseSlot,
+    path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let database = DatabaseHandle::open(Some(path))?;
+    if database.is_empty() {
+        return Err("Database is empty; keeping the current database".into());
+    }
+    slot.replace(database);
+    Ok(())
+}
+
+/// Reload the database from `path`, the configured database file, logging
+/// the outcome. Runs on a blocking thread: decoding a full database takes a
+/// while.
+pub(crate) fn reload(slot: &DatabaseSlot, path: Option<&Path>) {
+    let Some(path) = path else {
+        tracing::error!("reload requested, but no database file is configured");
+        return;
+    };
+
+    match reload_from_path(slot, path) {
+        Ok(()) => tracing::info!(path = %path.display(), "database reloaded"),
+        Err(err) => tracing::error!(path = %path.display(), %err, "reloading the database failed"),
+    }
+}
+
+/// Stream of reload requests: SIGHUP on Unix, nothing elsewhere.
+#[cfg(unix)]
+pub(crate) struct Hangups(tokio::signal::unix::Signal);
+
+#[cfg(not(unix))]
+pub(crate) struct Hangups;
+
+impl Hangups {
+    #[cfg(unix)]
+    pub(crate) fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        Ok(Self(signal(SignalKind::hangup())?))
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    /// Wait for the next reload request.
+    pub(crate) async fn recv(&mut self) {
+        #[cfg(unix)]
+        if self.0.recv().await.is_some() {
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DatabaseSlot, reload_from_path};
+    use crate::service::test_utils::test_database;
+    use std::path::Path;
+
+    #[test]
+    fn reload_swaps_database_for_new_snapshots() {
+        let slot = DatabaseSlot::new(test_database());
+        let before = slot.snapshot();
+        assert!(before.lookup("1234AB", 56).is_none());
+
+        reload_from_path(&slot, Path::new("test/bag.bin")).unwrap();
+
+        let after = slot.snapshot();
+        assert_eq!(
+            after.lookup("1234AB", 56),
+            Some(("Abel Eppensstraat", "Hoogerheide"))
+        );
+        // Connections holding the old snapshot keep their data.
+        assert_eq!(
+            before.lookup("1234AB", 10),
+            Some(("Stationsstraat", "Amsterdam"))
+        );
+    }
+
+    #[test]
+    fn failed_reload_keeps_current_database() {
+        let slot = DatabaseSlot::new(test_database());
+
+        assert!(reload_from_path(&slot, Path::new("test/does-not-exist.bin")).is_err());
+
+        assert!(slot.snapshot().lookup("1234AB", 10).is_some());
+    }
+}
@@ -0,0 +1,247 @@
+//! HTTP/1.x request head parsing.
+//!
+//! Only the parts the service acts on are extracted: the request line and the
+//! header fields. Header names are matched case-insensitively (RFC 9110 §5.1);
+//! values are kept as sent, minus surrounding whitespace.
+
+use std::net::{IpAddr, SocketAddr};
+
+/// Request line and header fields of a single HTTP request.
+pub(crate) struct RequestHead {
+    pub(crate) method: String,
+    pub(crate) target: String,
+    pub(crate) version: String,
+    headers: Vec<(String, String)>,
+}
+
+impl RequestHead {
+    /// Parse a request head (everything up to and including the blank line).
+    ///
+    /// Malformed input never fails: missing request-line parts become empty
+    /// strings and header lines without a `:` are ignored, so the router can
+    /// answer with a regular error response.
+    pub(crate) fn parse(raw: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(raw);
+        let mut lines = text.lines();
+
+        let request_line = lines.next().unwrap_or_default();
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+        let version = parts.next().unwrap_or_default().to_string();
+
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        Self {
+            method,
+            target,
+            version,
+            headers,
+        }
+    }
+
+    /// Value of the first header named `name` (case-insensitive), if present.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Client address in the `Forwarded` header, or else in `X-Forwarded-For`,
+    /// behind `trusted_proxies` reverse proxies.
+    ///
+    /// Each proxy appends the address it received the request from, so the
+    /// list is read from the right: the last proxy is the socket peer, and
+    /// the right-most `trusted_proxies - 1` entries were added by the other
+    /// proxies. Entries further left were sent by the client and can be
+    /// spoofed. A shorter list yields its left-most entry.
+    pub(crate) fn forwarded_for(&self, trusted_proxies: usize) -> Option<IpAddr> {
+        let hops = trusted_proxies.checked_sub(1)?;
+        let nodes: Vec<&str> = if let Some(forwarded) = self.header("forwarded") {
+            forwarded
+                .split(',')
+                .map(|element| {
+                    element
+                        .split(';')
+                        .filter_map(|pair| pair.split_once('='))
+                        .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                        .map_or("", |(_, node)| node)
+                })
+                .collect()
+        } else {
+            self.header("x-forwarded-for")?.split(',').collect()
+        };
+        let index = nodes.len().saturating_sub(hops + 1);
+        parse_node(nodes.get(index)?)
+    }
+
+    /// Whether the client asked to keep the connection open after this request.
+    ///
+    /// HTTP/1.1 connections are persistent unless the client sends
+    /// `Connection: close`; HTTP/1.0 connections only persist when the client
+    /// explicitly sends `Connection: keep-alive`.
+    pub(crate) fn keep_alive(&self) -> bool {
+        match self.version.as_str() {
+            "HTTP/1.1" => !self.has_connection_token("close"),
+            "HTTP/1.0" => self.has_connection_token("keep-alive"),
+            _ => false,
+        }
+    }
+
+    /// Whether the client accepts a gzip-encoded response body.
+    ///
+    /// True when `Accept-Encoding` lists `gzip` (or `x-gzip`, or `*` without
+    /// an explicit gzip entry) with a non-zero quality value.
+    pub(crate) fn accepts_gzip(&self) -> bool {
+        let Some(value) = self.header("accept-encoding") else {
+            return false;
+        };
+
+        let mut wildcard = false;
+        for entry in value.split(',') {
+            let mut params = entry.split(';');
+            let coding = params.next().unwrap_or_default().trim();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+                return quality > 0.0;
+            }
+            if coding == "*" {
+                wildcard = quality > 0.0;
+            }
+        }
+        wildcard
+    }
+
+    /// Whether the comma-separated `Connection` header contains `token`.
+    fn has_connection_token(&self, token: &str) -> bool {
+        self.header("connection").is_some_and(|value| {
+            value
+                .split(',')
+                .any(|part| part.trim().eq_ignore_ascii_case(token))
+        })
+    }
+}
+
+/// Parse a forwarded node: an IP address, optionally quoted, in brackets or
+/// with a port. Obfuscated identifiers and `unknown` yield `None`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| node.strip_prefix('[')?.strip_suffix(']')?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RequestHead;
+
+    #[test]
+    fn parses_request_line_and_headers() {
+        let head = RequestHead::parse(
+            b"GET /lookup?pc=1234AB&n=11 HTTP/1.1\r\nHost: localhost\r\nX-Test:  a:b \r\n\r\n",
+        );
+
+        assert_eq!(head.method, "GET");
+        assert_eq!(head.target, "/lookup?pc=1234AB&n=11");
+        assert_eq!(head.version, "HTTP/1.1");
+        assert_eq!(head.header("host"), Some("localhost"));
+        assert_eq!(head.header("X-TEST"), Some("a:b"));
+        assert_eq!(head.header("missing"), None);
+    }
+
+    #[test]
+    fn forwarded_for_skips_the_trusted_hops_from_the_right() {
+        let forwarded_for =
+            |raw: &[u8], trusted_proxies| RequestHead::parse(raw).forwarded_for(trusted_proxies);
+
+        // Behind one proxy the client sets every entry but the right-most one.
+        let spoofed = b"GET / HTTP/1.1\r\nX-Forwarded-For: 203.0.113.9, 192.0.2.7\r\n\r\n";
+        assert_eq!(
+            forwarded_for(spoofed, 1),
+            Some("192.0.2.7".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_for(spoofed, 2),
+            Some("203.0.113.9".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_for(spoofed, 3),
+            Some("203.0.113.9".parse().unwrap())
+        );
+        assert_eq!(forwarded_for(spoofed, 0), None);
+        assert_eq!(
+            forwarded_for(
+                b"GET / HTTP/1.1\r\nForwarded: for=10.0.0.1, for=\"[2001:db8::1]:4711\";proto=https\r\n\r\n",
+                1
+            ),
+            Some("2001:db8::1".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_for(
+                b"GET / HTTP/1.1\r\nForwarded: proto=http;For=192.0.2.60\r\n\r\n",
+                1
+            ),
+            Some("192.0.2.60".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_for(b"GET / HTTP/1.1\r\nForwarded: for=unknown\r\n\r\n", 1),
+            None
+        );
+        assert_eq!(forwarded_for(b"GET / HTTP/1.1\r\n\r\n", 1), None);
+    }
+
+    #[test]
+    fn empty_input_yields_empty_request_line() {
+        let head = RequestHead::parse(b"");
+
+        assert_eq!(head.method, "");
+        assert_eq!(head.target, "");
+        assert_eq!(head.version, "");
+    }
+
+    #[test]
+    fn http11_keeps_alive_unless_closed() {
+        assert!(RequestHead::parse(b"GET / HTTP/1.1\r\n\r\n").keep_alive());
+        assert!(!RequestHead::parse(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").keep_alive());
+        assert!(
+            !RequestHead::parse(b"GET / HTTP/1.1\r\nconnection: Upgrade, Close\r\n\r\n")
+                .keep_alive()
+        );
+    }
+
+    #[test]
+    fn http10_closes_unless_kept_alive() {
+        assert!(!RequestHead::parse(b"GET / HTTP/1.0\r\n\r\n").keep_alive());
+        assert!(
+            RequestHead::parse(b"GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n").keep_alive()
+        );
+    }
+
+    #[test]
+    fn accept_encoding_gzip() {
+        let accepts = |value: &str| {
+            RequestHead::parse(
+                format!("GET / HTTP/1.1\r\nAccept-Encoding: {value}\r\n\r\n").as_bytes(),
+            )
+            .accepts_gzip()
+        };
+
+        assert!(accepts("gzip"));
+        assert!(accepts("br, GZIP;q=0.5"));
+        assert!(accepts("*"));
+        assert!(!accepts("identity"));
+        assert!(!accepts("gzip;q=0"));
+        assert!(!accepts("*, gzip;q=0"));
+        assert!(!RequestHead::parse(b"GET / HTTP/1.1\r\n\r\n").accepts_gzip());
+    }
+}
@@ -0,0 +1,169 @@
+use serde_json::json;
+
+use crate::database::DatabaseHandle;
+
+use super::{
+    ApiError, Response, ServerConfig, address_json,
+    query::{check_strict, parse_query},
+};
+
+/// Default and largest search radius of `/reverse`, in metres.
+pub(super) const DEFAULT_REVERSE_RADIUS: f64 = 500.0;
+
+/// Query keys understood by `GET /reverse`.
+const QUERY_KEYS: &[&str] = &["lat", "lon", "radius"];
+
+/// Handle the `/reverse` endpoint: the address closest to `lat` / `lon`.
+///
+/// `radius` (metres) narrows the search; it is capped at the configured
+/// maximum radius.
+pub(crate) fn handle_reverse(
+    database: &DatabaseHandle,
+    config: &ServerConfig,
+    query: &str,
+) -> Response {
+    let Ok(pairs) = parse_query(query) else {
+        return ApiError::InvalidQueryEncoding.into();
+    };
+
+    if let Err(error) = check_strict(&pairs, QUERY_KEYS) {
+        return error.into();
+    }
+
+    let mut lat = None;
+    let mut lon = None;
+    let mut radius = None;
+    for (key, value) in pairs {
+        match key.as_str() {
+            "lat" => lat = Some(value.parse::<f64>().ok()),
+            "lon" => lon = Some(value.parse::<f64>().ok()),
+            "radius" => radius = value.parse::<f64>().ok(),
+            _ => {}
+        }
+    }
+
+    let Some(lat) = lat else {
+        return ApiError::MissingLat.into();
+    };
+    let Some(lon) = lon else {
+        return ApiError::MissingLon.into();
+    };
+    let Some(lat) = lat.filter(|lat| (-90.0..=90.0).contains(lat)) else {
+        return ApiError::InvalidLat.into();
+    };
+    let Some(lon) = lon.filter(|lon| (-180.0..=180.0).contains(lon)) else {
+        return ApiError::InvalidLon.into();
+    };
+
+    let max_radius = config.reverse_radius;
+    let radius = radius
+        .filter(|radius| *radius >= 0.0)
+        .map_or(max_radius, |radius| radius.min(max_radius));
+
+    match database.reverse(lat, lon, radius) {
+        Some(result) => {
+            let mut value = address_json(&result.address, None, true);
+            value["pc"] = json!(result.postal_code);
+            // Positions are stored in decimetres, so finer is noise.
+            value["distance"] = json!((result.distance * 10.0).round() / 10.0);
+            Response::new(
+                200,
+                serde_json::to_string(&value).expect("serialize reverse response"),
+            )
+        }
+        None => ApiError::NoAddressWithinRadius.into(),
+    }
+}
+
+/// Read the maximum `/reverse` search radius from
+/// `BAG_ADDRESS_LOOKUP_REVERSE_RADIUS`.
+pub(super) fn reverse_radius() -> f64 {
+    std::env::var("BAG_ADDRESS_LOOKUP_REVERSE_RADIUS")
+        .ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .unwrap_or(DEFAULT_REVERSE_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        ServerConfig,
+        test_utils::{send_request, send_request_with_config, test_database},
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn reverse_finds_nearby_address() {
+        let db = Arc::new(test_database());
+        // About 25 m from the Stationsstraat range position on the Dam.
+        let response = send_request(
+            "GET /reverse?lat=52.3733&lon=4.8927 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"pc\":\"1234AB\""));
+        assert!(response.contains("\"pr\":\"Stationsstraat\""));
+        assert!(response.contains("\"wp\":\"Amsterdam\""));
+        assert!(response.contains("\"distance\":"));
+    }
+
+    #[tokio::test]
+    async fn reverse_outside_radius_is_not_found() {
+        let db = Arc::new(test_database());
+        // Utrecht is well beyond 500 m.
+        let response = send_request(
+            "GET /reverse?lat=52.0907&lon=5.1214 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+
+        // A radius below the distance excludes the nearby address as well.
+        let response = send_request(
+            "GET /reverse?lat=52.3733&lon=4.8927&radius=5 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    #[tokio::test]
+    async fn configured_radius_caps_the_search() {
+        let db = Arc::new(test_database());
+        let config = ServerConfig {
+            reverse_radius: 10.0,
+            ..ServerConfig::default()
+        };
+        // The Dam address is about 25 m away, beyond the configured 10 m.
+        let response = send_request_with_config(
+            "GET /reverse?lat=52.3733&lon=4.8927&radius=100 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+            config,
+        )
+        .await;
+        assert!(response.starts_with(b"HTTP/1.1 404 Not Found"));
+    }
+
+    #[tokio::test]
+    async fn reverse_validates_coordinates() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /reverse?lat=52.37 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("{\"code\":\"MISSING_LON\",\"error\":\"missing lon\"}"));
+
+        let response = send_request(
+            "GET /reverse?lat=north&lon=4.89 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("{\"code\":\"INVALID_LAT\",\"error\":\"invalid lat\"}"));
+    }
+}
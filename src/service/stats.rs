@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+use crate::database::DatabaseHandle;
+
+use super::Response;
+
+/// JSON body of the `/stats` endpoint.
+#[derive(Serialize)]
+struct Stats {
+    localities: usize,
+    public_spaces: usize,
+    ranges: usize,
+    total_addresses: u64,
+}
+
+/// Handle the `/stats` endpoint: the counts of [`DatabaseHandle::stats`].
+pub(crate) fn handle_stats(database: &DatabaseHandle) -> Response {
+    let stats = database.stats();
+    let body = serde_json::to_string(&Stats {
+        localities: stats.localities,
+        public_spaces: stats.public_spaces,
+        ranges: stats.ranges,
+        total_addresses: stats.total_addresses,
+    })
+    .expect("serialize stats");
+    Response::new(200, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{send_request, test_database};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn stats_reports_counts() {
+        let db = Arc::new(test_database());
+        let response = send_request("GET /stats HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(
+            "{\"localities\":4,\"public_spaces\":1,\"ranges\":1,\"total_addresses\":3}"
+        ));
+    }
+}
@@ -1,39 +1,220 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::json;
+
 use crate::{
-    database::DatabaseHandle,
-    suggest::{DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD},
+    database::{DatabaseHandle, postal_code_prefix_bounds},
+    suggest::{DEFAULT_SUGGEST_THRESHOLD, ScoringConfig, SuggestAlgorithm, normalize_query},
 };
 
-use super::{Response, json_error, query::parse_query};
+use super::{
+    ApiError, Response, ServerConfig,
+    query::{check_strict, parse_query},
+    suggest_cache::{SuggestCache, SuggestKey},
+};
+
+/// Upper bound for the `limit` parameter, whatever the client asks for.
+const MAX_SUGGEST_LIMIT: usize = 100;
+
+/// Parameters of a suggest request.
+///
+/// This is also the JSON body accepted by `POST /suggest`.
+#[derive(Default, Deserialize)]
+struct SuggestRequest {
+    wp: Option<String>,
+    pc: Option<String>,
+    municipalities: Option<bool>,
+    aliases: Option<bool>,
+    limit: Option<usize>,
+    min_score: Option<f32>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    verbose: Option<bool>,
+    highlight: Option<bool>,
+}
+
+/// Query keys understood by `GET /suggest`.
+pub(super) const QUERY_KEYS: &[&str] = &[
+    "wp",
+    "pc",
+    "municipalities",
+    "aliases",
+    "limit",
+    "min_score",
+    "type",
+    "verbose",
+    "highlight",
+];
 
 /// Handle the `/suggest` endpoint by returning a JSON list of locality and
 /// municipality names matching the `wp` query param.
-pub(crate) fn handle_suggest(database: &DatabaseHandle, query: &str) -> Response {
-    let mut query_text = None;
-    let mut include_municipalities = true;
-    let mut include_aliases = false;
+pub(crate) fn handle_suggest(
+    database: &Arc<DatabaseHandle<'static>>,
+    config: &ServerConfig,
+    cache: &SuggestCache,
+    query: &str,
+) -> Response {
+    let mut request = SuggestRequest::default();
+
+    let Ok(pairs) = parse_query(query) else {
+        return ApiError::InvalidQueryEncoding.into();
+    };
+
+    if pairs.is_empty() {
+        return ApiError::EmptyQuery.into();
+    }
+
+    if let Err(error) = check_strict(&pairs, QUERY_KEYS) {
+        return error.into();
+    }
 
-    for (key, value) in parse_query(query) {
+    for (key, value) in pairs {
         match key.as_str() {
-            "wp" => query_text = Some(value),
-            "municipalities" => include_municipalities = parse_bool(&value),
-            "aliases" => include_aliases = parse_bool(&value),
+            "wp" => request.wp = Some(value),
+            "pc" => request.pc = Some(value),
+            "municipalities" => request.municipalities = Some(parse_bool(&value)),
+            "aliases" => request.aliases = Some(parse_bool(&value)),
+            "limit" => request.limit = value.parse::<usize>().ok(),
+            "min_score" => request.min_score = value.parse::<f32>().ok(),
+            "type" => request.kind = Some(value),
+            "verbose" => request.verbose = Some(parse_bool(&value)),
+            "highlight" => request.highlight = Some(parse_bool(&value)),
             _ => {}
         }
     }
 
-    let Some(query_text) = query_text else {
-        return Response::new(400, json_error("missing wp"));
+    suggest_response(database, config, cache, &request)
+}
+
+/// Handle `POST /suggest` with a JSON body like `{"wp":"Amster","aliases":true}`.
+pub(crate) fn handle_suggest_body(
+    database: &Arc<DatabaseHandle<'static>>,
+    config: &ServerConfig,
+    cache: &SuggestCache,
+    body: &[u8],
+) -> Response {
+    let Ok(request) = serde_json::from_slice::<SuggestRequest>(body) else {
+        return ApiError::InvalidJsonBody.into();
     };
 
-    Response::new(
-        200,
-        suggest_json(
-            database,
-            &query_text,
-            include_municipalities,
-            include_aliases,
-        ),
-    )
+    suggest_response(database, config, cache, &request)
+}
+
+/// Validate the suggest parameters and build the response.
+fn suggest_response(
+    database: &Arc<DatabaseHandle<'static>>,
+    config: &ServerConfig,
+    cache: &SuggestCache,
+    request: &SuggestRequest,
+) -> Response {
+    let Some(query_text) = &request.wp else {
+        return ApiError::MissingWp.into();
+    };
+
+    // An invalid per-request score is ignored rather than rejected.
+    let threshold = request
+        .min_score
+        .filter(|score| is_valid_threshold(*score))
+        .unwrap_or(config.suggest_threshold);
+
+    let limit = request
+        .limit
+        .unwrap_or(config.suggest_limit)
+        .min(MAX_SUGGEST_LIMIT);
+
+    let streets = match request.kind.as_deref().unwrap_or("locality") {
+        "locality" => false,
+        "street" => true,
+        _ => return ApiError::InvalidType.into(),
+    };
+    let municipalities = request.municipalities.unwrap_or(true);
+    let aliases = request.aliases.unwrap_or(false);
+
+    // A postal code prefix narrows down localities; streets ignore it.
+    let postal_area = match request.pc.as_deref().filter(|pc| !pc.is_empty()) {
+        Some(_) if streets => None,
+        Some(pc) if postal_code_prefix_bounds(pc).is_none() => {
+            return ApiError::InvalidPostalCode.into();
+        }
+        pc => pc.map(str::to_ascii_uppercase),
+    };
+
+    let key = SuggestKey {
+        query: normalize_query(query_text),
+        limit,
+        threshold_bits: threshold.to_bits(),
+        streets,
+        municipalities,
+        aliases,
+        postal_area: postal_area.clone(),
+    };
+    let suggestions = cache.get_or_insert_with(database, key, || {
+        let scoring = &config.suggest_scoring;
+        if streets {
+            database.suggest_public_spaces_scored_with(query_text, threshold, limit, scoring)
+        } else if let Some(postal_area) = &postal_area {
+            database.suggest_in_postal_area(
+                query_text,
+                postal_area,
+                threshold,
+                limit,
+                aliases,
+                scoring,
+            )
+        } else {
+            database.suggest_scored_with(
+                query_text,
+                threshold,
+                limit,
+                municipalities,
+                aliases,
+                scoring,
+            )
+        }
+    });
+
+    let verbose = request.verbose.unwrap_or(false);
+    let highlight = request.highlight.unwrap_or(false);
+
+    let body = if verbose || highlight {
+        let entries: Vec<_> = suggestions
+            .iter()
+            .map(|suggestion| {
+                let mut entry = json!({ "name": suggestion.name });
+                if verbose {
+                    entry["score"] = json!(round_score(suggestion.score));
+                    if let Some(municipality) = &suggestion.municipality {
+                        entry["municipality"] = json!(municipality);
+                    }
+                    if let Some(province) = &suggestion.province {
+                        entry["province"] = json!(province);
+                    }
+                }
+                if highlight {
+                    entry["highlight"] = match suggestion.highlight {
+                        Some((start, end)) => json!([start, end]),
+                        None => json!([]),
+                    };
+                }
+                entry
+            })
+            .collect();
+        serde_json::to_string(&entries)
+    } else {
+        let names: Vec<_> = suggestions
+            .iter()
+            .map(|suggestion| suggestion.name.as_str())
+            .collect();
+        serde_json::to_string(&names)
+    };
+
+    Response::new(200, body.expect("serialize suggestions"))
+}
+
+/// Round a match score to 3 decimals for the JSON output.
+fn round_score(score: f32) -> f64 {
+    (f64::from(score) * 1000.0).round() / 1000.0
 }
 
 /// Parse a boolean-ish query parameter. `false`, `0` and `no` (case-insensitive)
@@ -42,38 +223,70 @@ fn parse_bool(value: &str) -> bool {
     !matches!(value.to_ascii_lowercase().as_str(), "false" | "0" | "no")
 }
 
-/// Build the JSON response body: a flat array of suggestion names.
-fn suggest_json(
-    database: &DatabaseHandle,
-    query: &str,
-    include_municipalities: bool,
-    include_aliases: bool,
-) -> String {
-    let names = database.suggest(
-        query,
-        suggest_threshold(),
-        DEFAULT_SUGGEST_LIMIT,
-        include_municipalities,
-        include_aliases,
-    );
-
-    serde_json::to_string(&names).expect("serialize suggestions")
-}
-
 /// Read the minimum fuzzy-match score from the environment.
-fn suggest_threshold() -> f32 {
+pub(super) fn suggest_threshold() -> f32 {
     std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_THRESHOLD")
         .ok()
         .and_then(|value| value.parse::<f32>().ok())
-        .filter(|value| value.is_finite() && *value >= 0.0)
+        .filter(|value| is_valid_threshold(*value))
         .unwrap_or(DEFAULT_SUGGEST_THRESHOLD)
 }
 
+/// Read the fuzzy-match scoring from the environment:
+/// `BAG_ADDRESS_LOOKUP_SUGGEST_WEIGHTS` (`subsequence,dice`),
+/// `BAG_ADDRESS_LOOKUP_SUGGEST_SUBSTRING_BOOST`,
+/// `BAG_ADDRESS_LOOKUP_SUGGEST_START_BOOST`,
+/// `BAG_ADDRESS_LOOKUP_SUGGEST_IGNORE_SEPARATORS` and
+/// `BAG_ADDRESS_LOOKUP_SUGGEST_ALGO` (`dice` or `edit`). Invalid values keep
+/// the default.
+pub(super) fn scoring_config() -> ScoringConfig {
+    let mut scoring = ScoringConfig::default();
+    let weights = std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_WEIGHTS").ok();
+    if let Some((subsequence, dice)) = weights.as_deref().and_then(parse_weights) {
+        scoring.subsequence_weight = subsequence;
+        scoring.dice_weight = dice;
+    }
+    if let Ok(value) = std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_SUBSTRING_BOOST") {
+        scoring.substring_boost = parse_bool(&value);
+    }
+    if let Some(boost) = std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_START_BOOST")
+        .ok()
+        .and_then(|value| value.trim().parse::<f32>().ok())
+        .filter(|value| is_valid_threshold(*value))
+    {
+        scoring.start_boost = boost;
+    }
+    if let Ok(value) = std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_IGNORE_SEPARATORS") {
+        scoring.ignore_separators = parse_bool(&value);
+    }
+    if let Ok(value) = std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_ALGO") {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "dice" => scoring.algorithm = SuggestAlgorithm::Dice,
+            "edit" => scoring.algorithm = SuggestAlgorithm::Edit,
+            _ => {}
+        }
+    }
+    scoring
+}
+
+/// Parse `subsequence,dice` weights; both must be finite and non-negative.
+fn parse_weights(value: &str) -> Option<(f32, f32)> {
+    let (subsequence, dice) = value.split_once(',')?;
+    let subsequence = subsequence.trim().parse::<f32>().ok()?;
+    let dice = dice.trim().parse::<f32>().ok()?;
+    (is_valid_threshold(subsequence) && is_valid_threshold(dice)).then_some((subsequence, dice))
+}
+
+/// Whether `score` can be used as a fuzzy-match threshold.
+fn is_valid_threshold(score: f32) -> bool {
+    score.is_finite() && score >= 0.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         super::test_utils::{send_request, test_database},
-        parse_bool,
+        parse_bool, parse_weights, round_score,
     };
     use std::sync::Arc;
 
@@ -91,6 +304,44 @@ mod tests {
         assert!(response.contains("[\"Amsterdam\"]"));
     }
 
+    #[tokio::test]
+    async fn suggest_narrows_localities_to_a_postal_code_prefix() {
+        let db = Arc::new(test_database());
+        let body = |response: String| response.split_once("\r\n\r\n").unwrap().1.to_string();
+
+        // Only Amsterdam has an address (1234AB); Rotterdam also matches "dam".
+        let response = send_request(
+            "GET /suggest?wp=dam HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(body(response).contains("Rotterdam"));
+
+        for pc in ["1", "12", "1234", "1234ab"] {
+            let response = send_request(
+                &format!("GET /suggest?wp=dam&pc={pc} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+                db.clone(),
+            )
+            .await;
+            assert_eq!(body(response), "[\"Amsterdam\"]", "{pc}");
+        }
+
+        let response = send_request(
+            "GET /suggest?wp=dam&pc=99 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert_eq!(body(response), "[]");
+
+        let response = send_request(
+            "GET /suggest?wp=dam&pc=12a HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("\"code\":\"INVALID_POSTAL_CODE\""));
+    }
+
     #[tokio::test]
     async fn suggest_includes_alias_when_requested() {
         // "Boalsert" is the Frisian alias for the official BAG name "Bolsward".
@@ -164,6 +415,19 @@ mod tests {
         assert!(response.contains("\"Súdwest-Fryslân\""));
     }
 
+    #[tokio::test]
+    async fn suggest_ignores_diacritics() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Sudwest-Fryslan HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("[\"Súdwest-Fryslân\""));
+    }
+
     #[tokio::test]
     async fn suggest_excludes_municipalities_when_requested() {
         let db = Arc::new(test_database());
@@ -190,13 +454,165 @@ mod tests {
         assert!(!response.contains("Saba"));
     }
 
+    #[tokio::test]
+    async fn suggest_respects_limit() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Amster&limit=3 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let names: Vec<String> = serde_json::from_str(body).unwrap();
+        assert!(!names.is_empty() && names.len() <= 3);
+        assert_eq!(names[0], "Amsterdam");
+
+        let response = send_request(
+            "GET /suggest?wp=Amster&limit=0 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.ends_with("\r\n\r\n[]"));
+
+        let response = send_request(
+            "GET /suggest?wp=dam&limit=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let names: Vec<String> = serde_json::from_str(body).unwrap();
+        assert_eq!(names.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn suggest_min_score_overrides_threshold() {
+        let db = Arc::new(test_database());
+        let request = |min_score: &str| {
+            format!(
+                "GET /suggest?wp=Amstrdam&min_score={min_score} HTTP/1.1\r\nHost: localhost\r\n\r\n"
+            )
+        };
+
+        let response = send_request(&request("0.5"), db.clone()).await;
+        assert!(response.contains("\"Amsterdam\""));
+
+        let response = send_request(&request("1.5"), db.clone()).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("\r\n\r\n[]"));
+
+        // Invalid scores fall back to the default threshold.
+        for invalid in ["-1", "NaN", "abc"] {
+            let response = send_request(&request(invalid), db.clone()).await;
+            assert!(response.contains("\"Amsterdam\""), "{invalid}");
+        }
+    }
+
+    #[tokio::test]
+    async fn suggest_streets() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Stations&type=street HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("\r\n\r\n[\"Stationsstraat\"]"));
+
+        // Street names are not candidates for the default locality search.
+        let response = send_request(
+            "GET /suggest?wp=Stations HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(!response.contains("Stationsstraat"));
+
+        let response = send_request(
+            "GET /suggest?wp=Stations&type=house HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("{\"code\":\"INVALID_TYPE\",\"error\":\"invalid type\"}"));
+    }
+
+    #[tokio::test]
+    async fn suggest_verbose_includes_scores() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Amster&verbose=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(body).unwrap();
+        assert_eq!(entries[0]["name"], "Amsterdam");
+        // "amster" is a prefix of "amsterdam": 1.0 + 6/9 + 0.5.
+        assert_eq!(entries[0]["score"], 2.167);
+        assert_eq!(entries[0]["municipality"], "Amsterdam");
+        assert_eq!(entries[0]["province"], "NH");
+    }
+
+    #[tokio::test]
+    async fn suggest_highlight_offsets() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=dam&highlight=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db.clone(),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(
+            "[{\"highlight\":[6,9],\"name\":\"Amsterdam\"},{\"highlight\":[6,9],\"name\":\"Rotterdam\"}]"
+        ));
+
+        // A typo only matches fuzzily, without a contiguous region.
+        let response = send_request(
+            "GET /suggest?wp=Amstrdam&highlight=1&verbose=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(body).unwrap();
+        assert_eq!(entries[0]["name"], "Amsterdam");
+        assert_eq!(entries[0]["highlight"], serde_json::json!([]));
+        assert!(entries[0]["score"].is_number());
+    }
+
+    #[test]
+    fn round_score_keeps_three_decimals() {
+        assert_eq!(round_score(2.166_666_7), 2.167);
+        assert_eq!(round_score(0.7), 0.7);
+    }
+
     #[tokio::test]
     async fn suggest_missing_query() {
         let db = Arc::new(test_database());
-        let response = send_request("GET /suggest HTTP/1.1\r\nHost: localhost\r\n\r\n", db).await;
+        for target in ["/suggest", "/suggest?", "/suggest?&"] {
+            let response = send_request(
+                &format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+                db.clone(),
+            )
+            .await;
+
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+            assert!(
+                response.contains(
+                    "{\"code\":\"EMPTY_QUERY\",\"error\":\"no query parameters provided\"}"
+                )
+            );
+        }
+
+        let response = send_request(
+            "GET /suggest?limit=5 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
 
         assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
-        assert!(response.contains("{\"error\":\"missing wp\"}"));
+        assert!(response.contains("{\"code\":\"MISSING_WP\",\"error\":\"missing wp\"}"));
     }
 
     #[tokio::test]
@@ -212,6 +628,85 @@ mod tests {
         assert!(response.contains("\"Amsterdam\""));
     }
 
+    #[tokio::test]
+    async fn post_suggest_success() {
+        let db = Arc::new(test_database());
+        let body = r#"{"wp":"Boalsert","aliases":true}"#;
+        let request = format!(
+            "POST /suggest HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = send_request(&request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"Boalsert\""));
+    }
+
+    #[tokio::test]
+    async fn post_suggest_malformed_json() {
+        let db = Arc::new(test_database());
+        let request = "POST /suggest HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\n[wp]}";
+        let response = send_request(request, db).await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(
+            response.contains("{\"code\":\"INVALID_JSON_BODY\",\"error\":\"invalid JSON body\"}")
+        );
+    }
+
+    #[tokio::test]
+    async fn suggest_allows_cross_origin_requests() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Amster HTTP/1.1\r\nHost: localhost\r\nOrigin: http://example.com\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Access-Control-Allow-Origin: *\r\n"));
+    }
+
+    #[tokio::test]
+    async fn suggest_cors_preflight() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "OPTIONS /suggest HTTP/1.1\r\nHost: localhost\r\nOrigin: http://example.com\r\n\
+             Access-Control-Request-Method: POST\r\nAccess-Control-Request-Headers: content-type\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 204 No Content"));
+        assert!(response.contains("Access-Control-Allow-Origin: *\r\n"));
+        assert!(response.contains("Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n"));
+        assert!(response.contains("Access-Control-Allow-Headers: Content-Type\r\n"));
+        assert!(!response.contains("Content-Length"));
+    }
+
+    #[tokio::test]
+    async fn suggest_invalid_percent_escape() {
+        let db = Arc::new(test_database());
+        let response = send_request(
+            "GET /suggest?wp=Amster%2 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            db,
+        )
+        .await;
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains(
+            "{\"code\":\"INVALID_QUERY_ENCODING\",\"error\":\"invalid query encoding\"}"
+        ));
+    }
+
+    #[test]
+    fn parse_weights_needs_two_valid_numbers() {
+        assert_eq!(parse_weights("0.7, 0.3"), Some((0.7, 0.3)));
+        assert_eq!(parse_weights("0.7"), None);
+        assert_eq!(parse_weights("0.7,-1"), None);
+        assert_eq!(parse_weights("NaN,0.3"), None);
+    }
+
     #[test]
     fn parse_bool_false_values() {
         assert!(!parse_bool("false"));
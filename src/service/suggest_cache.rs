@@ -0,0 +1,253 @@
+//! Bounded LRU cache of suggest results.
+//!
+//! Type-ahead clients send the same short prefixes over and over, so the
+//! ranked suggestions are kept per normalized query and parameter set. The
+//! cache remembers which database its entries were computed from and empties
+//! itself when it is used with another one, e.g. after a reload.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::{database::DatabaseHandle, suggest::Suggestion};
+
+/// Default number of cached suggest results.
+pub(super) const DEFAULT_SUGGEST_CACHE_SIZE: usize = 1024;
+
+/// Everything a suggest result depends on besides the database.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SuggestKey {
+    /// The query as normalized for matching.
+    pub(crate) query: String,
+    pub(crate) limit: usize,
+    /// Bit pattern of the `f32` threshold, so the key can be hashed.
+    pub(crate) threshold_bits: u32,
+    pub(crate) streets: bool,
+    pub(crate) municipalities: bool,
+    pub(crate) aliases: bool,
+    /// Uppercased postal code prefix the localities are restricted to.
+    pub(crate) postal_area: Option<String>,
+}
+
+/// Suggest results shared by all connections, evicting the least recently
+/// used entry when full.
+pub(crate) struct SuggestCache {
+    capacity: usize,
+    inner: Mutex<CacheInner>,
+}
+
+struct CacheInner {
+    /// The database the cached results were computed from.
+    database: Weak<DatabaseHandle<'static>>,
+    /// Results with the tick of their last use.
+    entries: HashMap<SuggestKey, (u64, Arc<Vec<Suggestion>>)>,
+    tick: u64,
+}
+
+impl SuggestCache {
+    /// Create an empty cache holding at most `capacity` results; `0`
+    /// disables caching.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(CacheInner {
+                database: Weak::new(),
+                entries: HashMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Return the cached result for `key`, or compute and cache it.
+    ///
+    /// `compute` runs without holding the lock, so concurrent misses for the
+    /// same key may both compute; the last one is kept. A result is not kept
+    /// when the cache moved on to another database meanwhile, e.g. after a
+    /// reload.
+    pub(crate) fn get_or_insert_with(
+        &self,
+        database: &Arc<DatabaseHandle<'static>>,
+        key: SuggestKey,
+        compute: impl FnOnce() -> Vec<Suggestion>,
+    ) -> Arc<Vec<Suggestion>> {
+        if self.capacity == 0 {
+            return Arc::new(compute());
+        }
+
+        {
+            let mut inner = self.lock(database);
+            inner.tick += 1;
+            let tick = inner.tick;
+            if let Some((last_used, suggestions)) = inner.entries.get_mut(&key) {
+                *last_used = tick;
+                return suggestions.clone();
+            }
+        }
+
+        let suggestions = Arc::new(compute());
+
+        let mut inner = self.lock_inner();
+        if !inner.tracks(database) {
+            return suggestions;
+        }
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+            let oldest = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                inner.entries.remove(&oldest);
+            }
+        }
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.insert(key, (tick, suggestions.clone()));
+        suggestions
+    }
+
+    /// Lock the cache, dropping all entries first when they were computed
+    /// from another database than `database`.
+    fn lock(
+        &self,
+        database: &Arc<DatabaseHandle<'static>>,
+    ) -> std::sync::MutexGuard<'_, CacheInner> {
+        let mut inner = self.lock_inner();
+        if !inner.tracks(database) {
+            inner.entries.clear();
+            inner.database = Arc::downgrade(database);
+        }
+        inner
+    }
+
+    /// Lock the cache as it is.
+    fn lock_inner(&self) -> std::sync::MutexGuard<'_, CacheInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheInner {
+    /// Whether the entries were computed from `database`.
+    fn tracks(&self, database: &Arc<DatabaseHandle<'static>>) -> bool {
+        // Holding a `Weak` keeps the old allocation alive, so a new database
+        // can never share its address.
+        Weak::ptr_eq(&self.database, &Arc::downgrade(database))
+    }
+}
+
+impl Default for SuggestCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUGGEST_CACHE_SIZE)
+    }
+}
+
+/// Read the cache capacity from `BAG_ADDRESS_LOOKUP_SUGGEST_CACHE_SIZE`,
+/// falling back to the default when unset or not a number.
+pub(super) fn suggest_cache_size() -> usize {
+    std::env::var("BAG_ADDRESS_LOOKUP_SUGGEST_CACHE_SIZE")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_SUGGEST_CACHE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SuggestCache, SuggestKey};
+    use crate::{service::test_utils::test_database, suggest::Suggestion};
+    use std::sync::Arc;
+
+    fn key(query: &str) -> SuggestKey {
+        SuggestKey {
+            query: query.to_string(),
+            limit: 10,
+            threshold_bits: 0.7f32.to_bits(),
+            streets: false,
+            municipalities: true,
+            aliases: false,
+            postal_area: None,
+        }
+    }
+
+    fn suggestions(name: &str) -> Vec<Suggestion> {
+        vec![Suggestion {
+            name: name.to_string(),
+            score: 1.5,
+            highlight: None,
+            municipality: None,
+            province: None,
+        }]
+    }
+
+    #[test]
+    fn second_identical_query_is_cached() {
+        let cache = SuggestCache::new(4);
+        let db = Arc::new(test_database());
+
+        let first = cache.get_or_insert_with(&db, key("ams"), || suggestions("Amsterdam"));
+        let second = cache.get_or_insert_with(&db, key("ams"), || unreachable!("cached"));
+
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = SuggestCache::new(2);
+        let db = Arc::new(test_database());
+
+        cache.get_or_insert_with(&db, key("a"), || suggestions("A"));
+        cache.get_or_insert_with(&db, key("b"), || suggestions("B"));
+        // Touch "a" so "b" becomes the least recently used entry.
+        cache.get_or_insert_with(&db, key("a"), || unreachable!("cached"));
+        cache.get_or_insert_with(&db, key("c"), || suggestions("C"));
+
+        cache.get_or_insert_with(&db, key("a"), || unreachable!("cached"));
+        cache.get_or_insert_with(&db, key("c"), || unreachable!("cached"));
+        let b = cache.get_or_insert_with(&db, key("b"), || suggestions("B again"));
+        assert_eq!(b[0].name, "B again");
+    }
+
+    #[test]
+    fn other_database_invalidates_entries() {
+        let cache = SuggestCache::new(4);
+        let old = Arc::new(test_database());
+        let reloaded = Arc::new(test_database());
+
+        cache.get_or_insert_with(&old, key("ams"), || suggestions("Old"));
+        let result = cache.get_or_insert_with(&reloaded, key("ams"), || suggestions("New"));
+
+        assert_eq!(result[0].name, "New");
+    }
+
+    #[test]
+    fn results_finished_after_a_reload_are_not_kept() {
+        let cache = SuggestCache::new(4);
+        let old = Arc::new(test_database());
+        let reloaded = Arc::new(test_database());
+
+        // A request on the reloaded database is served while one on the old
+        // database is still computing.
+        let result = cache.get_or_insert_with(&old, key("ams"), || {
+            cache.get_or_insert_with(&reloaded, key("utr"), || suggestions("New"));
+            suggestions("Old")
+        });
+        assert_eq!(result[0].name, "Old");
+
+        cache.get_or_insert_with(&reloaded, key("utr"), || unreachable!("cached"));
+        let result = cache.get_or_insert_with(&reloaded, key("ams"), || suggestions("New"));
+        assert_eq!(result[0].name, "New");
+    }
+
+    #[test]
+    fn zero_capacity_disables_caching() {
+        let cache = SuggestCache::new(0);
+        let db = Arc::new(test_database());
+
+        cache.get_or_insert_with(&db, key("ams"), || suggestions("First"));
+        let result = cache.get_or_insert_with(&db, key("ams"), || suggestions("Second"));
+
+        assert_eq!(result[0].name, "Second");
+    }
+}
@@ -0,0 +1,165 @@
+//! Serving over TLS, with rustls.
+//!
+//! Accepted TCP streams go through a TLS handshake before requests are read
+//! from them; everything after that is the same as for plain connections.
+
+use std::{error::Error, future::Future, net::SocketAddr, path::Path, sync::Arc};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        self, ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    },
+    server::TlsStream,
+};
+
+use super::{
+    connection::{Connection, Transport},
+    serve_configured,
+};
+
+impl<S: Connection> Connection for TlsStream<S> {
+    fn peer(&self) -> Option<SocketAddr> {
+        self.get_ref().0.peer()
+    }
+}
+
+impl Transport<TcpStream> for TlsAcceptor {
+    type Stream = TlsStream<TcpStream>;
+
+    fn open(
+        &self,
+        stream: TcpStream,
+    ) -> impl Future<Output = std::io::Result<Self::Stream>> + Send {
+        self.accept(stream)
+    }
+}
+
+/// Read the certificate chain and private key, both PEM encoded.
+fn load_config(cert: &Path, key: &Path) -> Result<ServerConfig, Box<dyn Error + Send + Sync>> {
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("could not read certificate {}: {err}", cert.display()))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|err| format!("could not read private key {}: {err}", key.display()))?;
+
+    let config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(chain, key)?;
+
+    Ok(config)
+}
+
+/// Start a BAG lookup HTTPS server on the given address, with the PEM
+/// encoded certificate chain `cert` and private key `key`.
+pub async fn serve_tls(
+    addr: &str,
+    cert: impl AsRef<Path>,
+    key: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = TcpListener::bind(addr).await?;
+
+    serve_tls_with_shutdown(listener, cert, key, tokio::signal::ctrl_c()).await
+}
+
+/// Like [`serve_with_shutdown`](super::serve_with_shutdown), terminating TLS
+/// with the PEM encoded certificate chain `cert` and private key `key`.
+pub async fn serve_tls_with_shutdown<F>(
+    listener: TcpListener,
+    cert: impl AsRef<Path>,
+    key: impl AsRef<Path>,
+    shutdown: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: Future<Output = Result<(), std::io::Error>> + Send + 'static,
+{
+    let tls = load_config(cert.as_ref(), key.as_ref())?;
+
+    serve_configured(
+        listener,
+        TlsAcceptor::from(Arc::new(tls)),
+        super::ServerConfig::from_env(),
+        shutdown,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        ConnectionLimits, ServerConfig, reload::DatabaseSlot, serve_connections_over,
+        test_utils::test_database,
+    };
+    use super::load_config;
+    use std::{path::Path, sync::Arc, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::oneshot,
+    };
+    use tokio_rustls::{
+        TlsAcceptor, TlsConnector,
+        rustls::{
+            ClientConfig, RootCertStore,
+            crypto::ring::default_provider,
+            pki_types::{CertificateDer, ServerName, pem::PemObject},
+        },
+    };
+
+    const CERT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/tls_cert.pem");
+    const KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/tls_key.pem");
+
+    #[tokio::test]
+    async fn requests_are_served_over_tls() {
+        let config = load_config(Path::new(CERT), Path::new(KEY)).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_connections_over(
+            listener,
+            TlsAcceptor::from(Arc::new(config)),
+            Arc::new(DatabaseSlot::new(test_database())),
+            async move {
+                let _ = shutdown.await;
+                Ok(())
+            },
+            ConnectionLimits {
+                max_connections: 16,
+                request_timeout: Duration::from_secs(5),
+                shutdown_grace: Duration::from_secs(5),
+            },
+            Arc::new(ServerConfig::default()),
+        ));
+
+        // Trust the self-signed certificate the server presents.
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(CertificateDer::from_pem_file(CERT).unwrap())
+            .unwrap();
+        let client = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut stream = TlsConnector::from(Arc::new(client))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
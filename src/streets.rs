@@ -0,0 +1,60 @@
+//! Ranges per street.
+//!
+//! Ranges are sorted by postal code, so finding those of one public space in
+//! one locality means scanning all of them. The index lists every range by
+//! `(public space, locality)` instead, so those of one street are a single
+//! contiguous run.
+
+/// `(public space index, locality index, range index)` per range, sorted.
+#[derive(Clone, Default)]
+pub(crate) struct StreetIndex {
+    entries: Vec<(u32, u16, u32)>,
+}
+
+impl StreetIndex {
+    /// Index the given `(public space index, locality index, range index)`
+    /// triples.
+    pub(crate) fn build(ranges: impl Iterator<Item = (u32, u16, usize)>) -> Self {
+        let mut entries: Vec<(u32, u16, u32)> = ranges
+            .filter_map(|(public_space, locality, index)| {
+                Some((public_space, locality, u32::try_from(index).ok()?))
+            })
+            .collect();
+        entries.sort_unstable();
+
+        Self { entries }
+    }
+
+    /// The indexes of the ranges of `public_space` in the localities
+    /// `first..=last`.
+    pub(crate) fn ranges(
+        &self,
+        public_space: u32,
+        first: u16,
+        last: u16,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let start = self
+            .entries
+            .partition_point(|&(ps, locality, _)| (ps, locality) < (public_space, first));
+        let end = self
+            .entries
+            .partition_point(|&(ps, locality, _)| (ps, locality) <= (public_space, last));
+        self.entries[start..end]
+            .iter()
+            .map(|&(_, _, index)| index as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreetIndex;
+
+    #[test]
+    fn ranges_are_grouped_per_street() {
+        let index = StreetIndex::build([(1, 2, 0), (0, 2, 1), (1, 2, 2), (1, 3, 3)].into_iter());
+
+        assert_eq!(index.ranges(1, 2, 2).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(index.ranges(1, 2, 3).collect::<Vec<_>>(), vec![0, 2, 3]);
+        assert_eq!(index.ranges(0, 3, 3).count(), 0);
+    }
+}
@@ -3,7 +3,9 @@
 //! The scoring lives in core so it can be reused outside of the web service
 //! (for example from the CLI or library consumers).
 
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
+
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 
 use crate::{DatabaseHandle, fryslan_aliases::lookup_alias};
 
@@ -13,6 +15,73 @@ pub const DEFAULT_SUGGEST_THRESHOLD: f32 = 0.7;
 /// Default maximum number of suggestions returned.
 pub const DEFAULT_SUGGEST_LIMIT: usize = 10;
 
+/// How names that don't contain the query are scored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SuggestAlgorithm {
+    /// Weighted in-order character match and bigram overlap, plus a bonus
+    /// for a common prefix.
+    #[default]
+    Dice,
+    /// One minus the Damerau-Levenshtein (optimal string alignment)
+    /// distance, relative to the length of the longer name.
+    Edit,
+}
+
+/// Weights and boosts of the fuzzy match score, see [`fuzzy_score`].
+///
+/// The defaults are what [`DatabaseHandle::suggest`] uses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoringConfig {
+    /// Weight of the fraction of query characters found in order in a name.
+    pub subsequence_weight: f32,
+    /// Weight of the character bigram overlap between query and name.
+    pub dice_weight: f32,
+    /// Whether names containing the query score above `1.0`, ahead of all
+    /// fuzzy matches. Without it they are scored like any other name.
+    pub substring_boost: bool,
+    /// Added to the substring boost when the name starts with the query.
+    pub start_boost: f32,
+    /// Scoring of names that don't contain the query. The weights only
+    /// apply to [`SuggestAlgorithm::Dice`].
+    pub algorithm: SuggestAlgorithm,
+    /// Whether spaces, hyphens and apostrophes are dropped from the query
+    /// and the names before scoring, so `denhaag` matches `Den Haag`. The
+    /// names are still returned as they are.
+    pub ignore_separators: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            subsequence_weight: 0.6,
+            dice_weight: 0.4,
+            substring_boost: true,
+            start_boost: 0.5,
+            algorithm: SuggestAlgorithm::Dice,
+            ignore_separators: false,
+        }
+    }
+}
+
+/// A suggested name together with its fuzzy match score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Display name offered to the caller.
+    pub name: String,
+    /// Fuzzy match score; higher is better. Exact and substring matches
+    /// score above `1.0`, see [`fuzzy_score`] for the details.
+    pub score: f32,
+    /// Byte range `(start, end)` of the contiguous match within `name`, or
+    /// `None` when the query only matched fuzzily.
+    pub highlight: Option<(usize, usize)>,
+    /// Municipality of a suggested locality, to tell same-named localities
+    /// apart. `None` for municipalities, public spaces and unknown places.
+    pub municipality: Option<String>,
+    /// Two-letter code of the province of a suggested locality or
+    /// municipality, when known.
+    pub province: Option<String>,
+}
+
 /// Caribbean Netherlands locality names not present in the BAG/CBS sources we
 /// ingest. Kralendijk and Rincon are the localities of Bonaire; Caribisch
 /// Nederland is otherwise represented at the municipality level.
@@ -22,13 +91,34 @@ static CN_LOCALITIES: &[&str] = &["Kralendijk", "Rincon"];
 /// Caribisch Nederland — not present in the BAG/CBS sources we ingest.
 static CN_MUNICIPALITIES: &[&str] = &["Bonaire", "Saba", "Sint Eustatius"];
 
+/// Which places [`suggest`] offers.
+pub(crate) struct PlaceFilter {
+    /// Offer municipality names.
+    pub(crate) municipalities: bool,
+    /// Offer the Frisian/Dutch aliases of localities.
+    pub(crate) aliases: bool,
+    /// Offer only the localities with these indexes, sorted, and their
+    /// aliases; no municipalities. All localities when `None`.
+    pub(crate) localities: Option<Vec<u16>>,
+}
+
+impl PlaceFilter {
+    fn offers(&self, place: &PlaceCandidate) -> bool {
+        (self.municipalities || !place.municipality)
+            && (self.aliases || !place.alias)
+            && self.localities.as_ref().is_none_or(|localities| {
+                place
+                    .locality
+                    .is_some_and(|index| localities.binary_search(&index).is_ok())
+            })
+    }
+}
+
 /// Suggest locality, municipality and (optionally) alias names matching `query`.
 ///
 /// Candidates scoring below `threshold` are discarded. At most `limit`
 /// highest-scoring distinct names are returned, mixed across localities and
-/// municipalities. When `include_municipalities` is false, municipality names
-/// are not offered as suggestions. When `include_aliases` is false, the
-/// Frisian/Dutch aliases of localities are not offered as suggestions.
+/// municipalities. Only the places `filter` offers are suggested.
 ///
 /// Names that originally carried a stripped province suffix get the province
 /// code appended (e.g. `Bergen` in Limburg becomes `Bergen (LI)`) so the
@@ -40,57 +130,309 @@ pub(crate) fn suggest(
     query: &str,
     threshold: f32,
     limit: usize,
-    include_municipalities: bool,
-    include_aliases: bool,
-) -> Vec<String> {
+    filter: &PlaceFilter,
+    scoring: &ScoringConfig,
+) -> Vec<Suggestion> {
     let normalized = normalize_query(query);
     if normalized.is_empty() {
         return Vec::new();
     }
 
-    // Each candidate is the display name returned to the caller (which may
-    // carry a province code). Fuzzy matching scores against this same string,
-    // so a query that spells out the province suffix can match it. Aliases are
-    // independent candidates — once expanded the originating name is irrelevant.
-    let mut candidates: Vec<String> = Vec::new();
+    let offered = |place: &&PlaceCandidate| filter.offers(place);
+    let candidates;
+    let places = match database.suggest_index() {
+        Some(index) => &index.places,
+        None => {
+            candidates = place_candidates(database);
+            &candidates
+        }
+    };
 
-    for loc in database.locality_details() {
-        if include_aliases && let Some(alias) = lookup_alias(loc.name) {
-            candidates.push(alias.to_string());
+    let mut suggestions = rank_normalized(
+        &normalized,
+        places.iter().filter(offered).map(PlaceCandidate::names),
+        threshold,
+        limit,
+        scoring,
+    );
+    for suggestion in &mut suggestions {
+        // Identical names were merged; a locality's context is the most specific.
+        let place = places
+            .iter()
+            .filter(offered)
+            .filter(|place| place.name == suggestion.name)
+            .min_by_key(|place| place.municipality);
+        if let Some(place) = place {
+            suggestion.municipality.clone_from(&place.parent);
+            suggestion.province.clone_from(&place.province);
         }
+    }
+    suggestions
+}
 
-        candidates.push(display_name(loc.name, loc.province, loc.had_suffix));
+/// A locality or municipality name offered by [`suggest`].
+#[derive(Clone)]
+pub(crate) struct PlaceCandidate {
+    normalized: String,
+    /// The display name returned to the caller, which may carry a province
+    /// code. Fuzzy matching scores against this same string, so a query that
+    /// spells out the province suffix can match it.
+    name: String,
+    municipality: bool,
+    alias: bool,
+    /// Index of the locality, or of the locality an alias names.
+    locality: Option<u16>,
+    /// The municipality of a locality.
+    parent: Option<String>,
+    province: Option<String>,
+}
+
+impl PlaceCandidate {
+    fn new(name: String, municipality: bool, alias: bool) -> Self {
+        Self {
+            normalized: normalize_query(&name),
+            name,
+            municipality,
+            alias,
+            locality: None,
+            parent: None,
+            province: None,
+        }
+    }
+
+    /// Mark the place as the locality at `index`, or one of its aliases.
+    fn locality(mut self, index: u16) -> Self {
+        self.locality = Some(index);
+        self
+    }
+
+    /// Set the municipality and province the place lies in.
+    fn within(mut self, parent: Option<&str>, province: &str) -> Self {
+        self.parent = parent.map(str::to_string);
+        self.province = Some(province.to_string()).filter(|province| !province.is_empty());
+        self
+    }
+
+    fn names(&self) -> (&str, &str) {
+        (&self.normalized, &self.name)
+    }
+}
+
+/// Every name [`suggest`] can offer. Aliases are independent candidates —
+/// once expanded the originating name is irrelevant.
+pub(crate) fn place_candidates(database: &DatabaseHandle) -> Vec<PlaceCandidate> {
+    let mut candidates = Vec::new();
+
+    for loc in database.locality_details() {
+        let parent = Some(loc.municipality).filter(|name| !name.is_empty());
+        if let Some(alias) = lookup_alias(loc.name) {
+            candidates.push(
+                PlaceCandidate::new(alias.to_string(), false, true)
+                    .locality(loc.index)
+                    .within(parent, loc.province),
+            );
+        }
+        let name = display_name(loc.name, loc.province, loc.had_suffix);
+        candidates.push(
+            PlaceCandidate::new(name, false, false)
+                .locality(loc.index)
+                .within(parent, loc.province),
+        );
     }
 
     for &wp in CN_LOCALITIES {
-        candidates.push(wp.to_string());
+        candidates.push(PlaceCandidate::new(wp.to_string(), false, false));
     }
 
-    if include_municipalities {
-        for muni in database.municipality_details() {
-            if include_aliases && let Some(alias) = lookup_alias(muni.name) {
-                candidates.push(alias.to_string());
-            }
+    for muni in database.municipality_details() {
+        if let Some(alias) = lookup_alias(muni.name) {
+            candidates.push(
+                PlaceCandidate::new(alias.to_string(), true, true).within(None, muni.province),
+            );
+        }
+        let name = display_name(muni.name, muni.province, muni.had_suffix);
+        candidates.push(PlaceCandidate::new(name, true, false).within(None, muni.province));
+    }
+
+    for &gm in CN_MUNICIPALITIES {
+        candidates.push(PlaceCandidate::new(gm.to_string(), true, false));
+    }
+
+    candidates
+}
+
+/// Suggest public space (street) names matching `query`.
+///
+/// Uses the same scoring, threshold and limit semantics as [`suggest`].
+///
+/// Prefer calling [`DatabaseHandle::suggest_public_spaces`] — this free
+/// function backs it.
+pub(crate) fn suggest_public_spaces(
+    database: &DatabaseHandle,
+    query: &str,
+    threshold: f32,
+    limit: usize,
+    scoring: &ScoringConfig,
+) -> Vec<Suggestion> {
+    let normalized = normalize_query(query);
+    if normalized.is_empty() {
+        return Vec::new();
+    }
+
+    match database.suggest_index() {
+        Some(index) => index.suggest(&normalized, threshold, limit, scoring),
+        None => rank(
+            &normalized,
+            database.public_spaces(),
+            threshold,
+            limit,
+            scoring,
+        ),
+    }
+}
+
+/// Names normalized once, so suggestions need not normalize every name per
+/// request. Public spaces are also sorted so prefix queries can binary-search.
+#[derive(Clone)]
+pub(crate) struct SuggestIndex {
+    /// `(normalized name, name)`, sorted and without duplicate names.
+    public_spaces: Vec<(String, String)>,
+    /// Locality and municipality names, see [`place_candidates`].
+    places: Vec<PlaceCandidate>,
+}
+
+impl SuggestIndex {
+    /// Index the given public space and place names.
+    pub(crate) fn build<'a>(
+        public_spaces: impl Iterator<Item = &'a str>,
+        places: Vec<PlaceCandidate>,
+    ) -> Self {
+        let mut public_spaces: Vec<(String, String)> = public_spaces
+            .map(|name| (normalize_query(name), name.to_string()))
+            .collect();
+        public_spaces.sort_unstable();
+        public_spaces.dedup();
 
-            candidates.push(display_name(muni.name, muni.province, muni.had_suffix));
+        Self {
+            public_spaces,
+            places,
         }
+    }
+
+    /// Same results as [`rank`] over all public space names.
+    ///
+    /// With the default scoring, names starting with the query score at
+    /// least `1.5`, while any other name scores at most `1 + n / (n + 1)` for
+    /// a query of `n` characters (a substring of a longer name; fuzzy matches
+    /// stay below `1.2`). When the prefix range alone holds `limit` names
+    /// scoring above that bound, no other name can make the cut and the full
+    /// scan is skipped.
+    fn suggest(
+        &self,
+        normalized: &str,
+        threshold: f32,
+        limit: usize,
+        scoring: &ScoringConfig,
+    ) -> Vec<Suggestion> {
+        let start = self
+            .public_spaces
+            .partition_point(|(name, _)| name.as_str() < normalized);
+        let len =
+            self.public_spaces[start..].partition_point(|(name, _)| name.starts_with(normalized));
+        let prefixed = &self.public_spaces[start..start + len];
 
-        for &gm in CN_MUNICIPALITIES {
-            candidates.push(gm.to_string());
+        let chars = normalized.chars().count() as f32;
+        let fuzzy_bound = match scoring.algorithm {
+            SuggestAlgorithm::Dice => {
+                scoring.subsequence_weight + scoring.dice_weight + MAX_PREFIX_BONUS
+            }
+            SuggestAlgorithm::Edit => 1.0,
+        };
+        let bound = (1.0 + chars / (chars + 1.0)).max(fuzzy_bound);
+        let ranked = rank_normalized(
+            normalized,
+            prefixed.iter().map(pair_refs),
+            threshold,
+            limit,
+            scoring,
+        );
+        // Without separators names no longer sort like the index, so names
+        // outside the prefix range can match the start of the query too.
+        if !scoring.ignore_separators
+            && ranked.len() == limit
+            && ranked.iter().all(|suggestion| suggestion.score > bound)
+        {
+            return ranked;
         }
+
+        rank_normalized(
+            normalized,
+            self.public_spaces.iter().map(pair_refs),
+            threshold,
+            limit,
+            scoring,
+        )
     }
+}
+
+/// Borrow both names of an index entry.
+fn pair_refs((normalized, name): &(String, String)) -> (&str, &str) {
+    (normalized, name)
+}
 
-    let mut scored: Vec<(f32, String)> = candidates
+/// Score `candidates` against the normalized query and return at most `limit`
+/// distinct names scoring at least `threshold`, best first.
+fn rank<S: AsRef<str> + Ord>(
+    normalized: &str,
+    candidates: impl IntoIterator<Item = S>,
+    threshold: f32,
+    limit: usize,
+    scoring: &ScoringConfig,
+) -> Vec<Suggestion> {
+    let candidates = candidates
         .into_iter()
-        .filter_map(|display| {
-            let score = fuzzy_score(&normalized, &normalize_query(&display));
+        .map(|display| (normalize_query(display.as_ref()), display));
+    rank_normalized(normalized, candidates, threshold, limit, scoring)
+}
+
+/// [`rank`] for candidates paired with their already normalized form.
+fn rank_normalized<N: AsRef<str>, S: AsRef<str> + Ord>(
+    normalized: &str,
+    candidates: impl IntoIterator<Item = (N, S)>,
+    threshold: f32,
+    limit: usize,
+    scoring: &ScoringConfig,
+) -> Vec<Suggestion> {
+    let query = if scoring.ignore_separators {
+        remove_separators(normalized)
+    } else {
+        Cow::Borrowed(normalized)
+    };
+    let normalized = query.as_ref();
+    let mut scored: Vec<(f32, S)> = candidates
+        .into_iter()
+        .filter_map(|(candidate, display)| {
+            let candidate = if scoring.ignore_separators {
+                remove_separators(candidate.as_ref())
+            } else {
+                Cow::Borrowed(candidate.as_ref())
+            };
+            let candidate = candidate.as_ref();
+            // Skip the full comparison for names that cannot reach the
+            // threshold even with a perfect dice coefficient or alignment.
+            if !candidate.contains(normalized)
+                && fuzzy_score_upper_bound(normalized, candidate, scoring) < threshold
+            {
+                return None;
+            }
+            let score = fuzzy_score(normalized, candidate, scoring);
             (score >= threshold).then_some((score, display))
         })
         .collect();
 
     // Highest score first; ties broken alphabetically so identical display
-    // names from the locality and municipality pools end up adjacent for
-    // deduplication.
+    // names (e.g. from the locality and municipality pools) end up adjacent
+    // for deduplication.
     scored.sort_by(|(a_score, a_name), (b_score, b_name)| {
         b_score
             .partial_cmp(a_score)
@@ -102,7 +444,13 @@ pub(crate) fn suggest(
     scored
         .into_iter()
         .take(limit)
-        .map(|(_, display)| display)
+        .map(|(score, display)| Suggestion {
+            name: display.as_ref().to_string(),
+            score,
+            highlight: match_range(normalized, display.as_ref()),
+            municipality: None,
+            province: None,
+        })
         .collect()
 }
 
@@ -117,16 +465,66 @@ fn display_name(name: &str, province: &str, had_suffix: bool) -> String {
     }
 }
 
-/// Normalize user input and candidates for case-insensitive matching.
+/// Normalize user input and candidates for case- and diacritic-insensitive
+/// matching.
+///
+/// The value is decomposed (NFD) and combining marks are dropped, so `é`
+/// folds to `e` and `â` to `a`.
 pub(crate) fn normalize_query(value: &str) -> String {
-    value.trim().to_lowercase()
+    value.trim().chars().flat_map(fold_char).collect()
+}
+
+/// Drop the spaces, hyphens and apostrophes that compound place names are
+/// written with in varying ways, e.g. `'s-Gravenhage` or `Den Haag`.
+fn remove_separators(value: &str) -> Cow<'_, str> {
+    let separator = |c: char| matches!(c, ' ' | '-' | '\'' | '\u{2019}');
+    if value.contains(separator) {
+        Cow::Owned(value.replace(separator, ""))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Fold a single character the way [`normalize_query`] does.
+fn fold_char(c: char) -> impl Iterator<Item = char> {
+    c.to_lowercase()
+        .nfd()
+        .filter(|folded| !is_combining_mark(*folded))
+}
+
+/// Byte range within `haystack` of the first contiguous match of the
+/// normalized `needle`, the same match [`fuzzy_score`] boosts.
+///
+/// The range refers to the original, unnormalized `haystack`, so it can be
+/// used to highlight the match in a display name. Returns `None` when
+/// `needle` only matches as a subsequence or by bigram similarity.
+fn match_range(needle: &str, haystack: &str) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+
+    // For every byte of the folded haystack, the byte range of the original
+    // character it was folded from.
+    let mut folded = String::new();
+    let mut sources = Vec::new();
+    let leading = haystack.len() - haystack.trim_start().len();
+    for (offset, c) in haystack.trim().char_indices() {
+        let source = (leading + offset, leading + offset + c.len_utf8());
+        for folded_char in fold_char(c) {
+            folded.push(folded_char);
+            sources.extend(std::iter::repeat_n(source, folded_char.len_utf8()));
+        }
+    }
+
+    let start = folded.find(needle)?;
+    Some((sources[start].0, sources[start + needle.len() - 1].1))
 }
 
 /// Compute a fuzzy score between the search `needle` and a candidate `haystack`.
 ///
-/// Algorithm details:
+/// Algorithm details, with the [`ScoringConfig::default`] values:
 /// - Substring boost: if `haystack` contains `needle`, return `1.0 + len(needle)/len(haystack)`,
-///   with an extra `+0.5` when the match is anchored at the start of `haystack`.
+///   with an extra `+0.5` (`start_boost`) when the match is anchored at the start of `haystack`.
 ///   This prioritizes contiguous matches while keeping longer exacts slightly below shorter perfects.
 /// - Otherwise compute:
 ///   - `subsequence_ratio`: fraction of `needle` characters found in order within `haystack`.
@@ -134,22 +532,63 @@ pub(crate) fn normalize_query(value: &str) -> String {
 /// - Final score: `0.6 * subsequence_ratio + 0.4 * dice_coefficient`, plus a prefix bonus
 ///   of up to `+0.2` proportional to the length of the common prefix between `needle` and `haystack`.
 ///   Subsequence helps partial-word matching; dice helps tolerate small typos.
-pub(crate) fn fuzzy_score(needle: &str, haystack: &str) -> f32 {
+///   When either string is a single character, the dice coefficient is taken over
+///   characters instead of bigrams.
+///
+/// With [`SuggestAlgorithm::Edit`] the score of a `haystack` without the
+/// `needle` is its [`edit_similarity`] instead, also between 0 and 1, so the
+/// same thresholds apply.
+pub(crate) fn fuzzy_score(needle: &str, haystack: &str, scoring: &ScoringConfig) -> f32 {
     if needle.is_empty() || haystack.is_empty() {
         return 0.0;
     }
 
-    if let Some(pos) = haystack.find(needle) {
+    if scoring.substring_boost
+        && let Some(pos) = haystack.find(needle)
+    {
         let ratio = needle.chars().count() as f32 / haystack.chars().count() as f32;
-        let start_boost = if pos == 0 { 0.5 } else { 0.0 };
+        let start_boost = if pos == 0 { scoring.start_boost } else { 0.0 };
         return 1.0 + ratio.min(1.0) + start_boost;
     }
 
+    if scoring.algorithm == SuggestAlgorithm::Edit {
+        return edit_similarity(needle, haystack);
+    }
+
     let subsequence = subsequence_ratio(needle, haystack);
-    let dice = dice_coefficient(needle, haystack);
-    (subsequence * 0.6) + (dice * 0.4) + prefix_bonus(needle, haystack)
+    // A single character has no bigrams; compare character counts instead.
+    let dice = if needle.chars().nth(1).is_none() || haystack.chars().nth(1).is_none() {
+        char_dice_coefficient(needle, haystack)
+    } else {
+        dice_coefficient(needle, haystack)
+    };
+    (subsequence * scoring.subsequence_weight)
+        + (dice * scoring.dice_weight)
+        + prefix_bonus(needle, haystack)
 }
 
+/// Upper bound of [`fuzzy_score`] for a `haystack` that does not contain
+/// `needle`, assuming the best possible dice coefficient or, for
+/// [`SuggestAlgorithm::Edit`], no edits besides the length difference.
+fn fuzzy_score_upper_bound(needle: &str, haystack: &str, scoring: &ScoringConfig) -> f32 {
+    // A little slack absorbs rounding differences with the exact score.
+    let bound = match scoring.algorithm {
+        SuggestAlgorithm::Dice => {
+            (subsequence_ratio(needle, haystack) * scoring.subsequence_weight)
+                + scoring.dice_weight
+                + prefix_bonus(needle, haystack)
+        }
+        SuggestAlgorithm::Edit => {
+            let (a, b) = (needle.chars().count(), haystack.chars().count());
+            1.0 - a.abs_diff(b) as f32 / a.max(b) as f32
+        }
+    };
+    bound + 1e-4
+}
+
+/// Largest [`prefix_bonus`].
+const MAX_PREFIX_BONUS: f32 = 0.2;
+
 /// Bonus up to 0.2 scaling with the fraction of `needle` that matches `haystack` from the start.
 fn prefix_bonus(needle: &str, haystack: &str) -> f32 {
     let matched = needle
@@ -161,7 +600,7 @@ fn prefix_bonus(needle: &str, haystack: &str) -> f32 {
         return 0.0;
     }
     let needle_len = needle.chars().count();
-    (matched as f32 / needle_len as f32) * 0.2
+    (matched as f32 / needle_len as f32) * MAX_PREFIX_BONUS
 }
 
 /// Ratio of `needle` characters appearing in order inside `haystack`.
@@ -188,6 +627,69 @@ fn subsequence_ratio(needle: &str, haystack: &str) -> f32 {
     matched as f32 / needle.chars().count() as f32
 }
 
+/// One minus the optimal string alignment distance between `a` and `b`
+/// (insertions, deletions, substitutions and transpositions of adjacent
+/// characters), divided by the length of the longer string.
+fn edit_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    // Three rows of the distance matrix: the current one and the two before.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+/// Dice coefficient over single characters: `2 * shared / (len_a + len_b)`,
+/// counting duplicates. Used where a string is too short for bigrams; like
+/// the bigram version it favors names close in length to the query.
+fn char_dice_coefficient(a: &str, b: &str) -> f32 {
+    let mut b_counts: HashMap<char, usize> = HashMap::new();
+    let mut total_b = 0usize;
+    for ch in b.chars() {
+        total_b += 1;
+        *b_counts.entry(ch).or_insert(0) += 1;
+    }
+
+    let mut intersection = 0usize;
+    let mut total_a = 0usize;
+    for ch in a.chars() {
+        total_a += 1;
+        if let Some(count) = b_counts.get_mut(&ch)
+            && *count > 0
+        {
+            *count -= 1;
+            intersection += 1;
+        }
+    }
+
+    let total = total_a + total_b;
+    if total == 0 {
+        return 0.0;
+    }
+    (2 * intersection) as f32 / total as f32
+}
+
 /// Dice coefficient using character bigrams.
 ///
 /// This measures similarity based on overlapping adjacent character pairs.
@@ -241,9 +743,54 @@ fn dice_coefficient(a: &str, b: &str) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::{
-        DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD, dice_coefficient, fuzzy_score,
-        normalize_query, subsequence_ratio, suggest,
+        DEFAULT_SUGGEST_LIMIT, DEFAULT_SUGGEST_THRESHOLD, ScoringConfig, SuggestAlgorithm,
+        SuggestIndex, dice_coefficient, edit_similarity, fuzzy_score, match_range, normalize_query,
+        rank, subsequence_ratio,
     };
+    use std::time::Instant;
+
+    /// Deterministic, Dutch-looking street names, with some duplicates.
+    fn synthetic_street_names(count: usize) -> Vec<String> {
+        const SYLLABLES: &[&str] = &[
+            "sta", "tions", "kerk", "dorps", "mo", "len", "van", "der", "werf", "bé", "ek", "hof",
+            "sint", "jans", "eg", "mond", "zee", "wijk", "ooster", "brug",
+        ];
+        const SUFFIXES: &[&str] = &["straat", "weg", "laan", "plein", "kade", "dijk", "pad"];
+
+        let mut state = 0x2545_f491_u64;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) as usize % bound
+        };
+        (0..count)
+            .map(|_| {
+                let mut name = String::new();
+                for _ in 0..1 + next(3) {
+                    name.push_str(SYLLABLES[next(SYLLABLES.len())]);
+                }
+                name.push_str(SUFFIXES[next(SUFFIXES.len())]);
+                let mut chars = name.chars();
+                let first = chars.next().unwrap().to_uppercase();
+                first.chain(chars).collect()
+            })
+            .collect()
+    }
+
+    const QUERIES: &[&str] = &[
+        "sta",
+        "Stationsstr",
+        "kerkstraat",
+        "molnweg",
+        "bé",
+        "be",
+        "s",
+        "dijk",
+        "Sint Jans",
+        "zeewijkpad",
+        "xyz",
+    ];
 
     #[test]
     fn suggest_appends_province_code_for_suffixed_names() {
@@ -270,10 +817,17 @@ mod tests {
             municipality_province: vec![0],
             locality_had_suffix: vec![true],
             municipality_had_suffix: vec![false],
+            house_number_suffixes: Vec::new(),
+            range_points: Vec::new(),
+            checksum: None,
+            suggest_index: Default::default(),
+            reverse_index: Default::default(),
+            locality_postal_codes: Default::default(),
+            street_index: Default::default(),
+            address_count: Default::default(),
         });
 
-        let results = suggest(
-            &database,
+        let results = database.suggest(
             "Bergen",
             DEFAULT_SUGGEST_THRESHOLD,
             DEFAULT_SUGGEST_LIMIT,
@@ -284,6 +838,119 @@ mod tests {
         // The suffixed locality is disambiguated; the municipality is not.
         assert!(results.contains(&"Bergen (LI)".to_string()));
         assert!(results.contains(&"Bergen".to_string()));
+
+        // Localities come with their municipality, municipalities without.
+        let scored = database.suggest_scored("Bergen", 0.0, 10, true, false);
+        let context = |name: &str| {
+            let suggestion = scored.iter().find(|s| s.name == name).unwrap();
+            (
+                suggestion.municipality.as_deref(),
+                suggestion.province.as_deref(),
+            )
+        };
+        assert_eq!(context("Bergen (LI)"), (Some("Bergen"), Some("LI")));
+        assert_eq!(context("Bergen"), (None, Some("LI")));
+
+        // The precomputed names give the same suggestions.
+        let suggest_all = |database: &DatabaseHandle| {
+            [(true, false), (false, true), (true, true)].map(|(municipalities, aliases)| {
+                database.suggest_scored("berg", 0.0, 10, municipalities, aliases)
+            })
+        };
+        let linear = suggest_all(&database);
+        database.build_suggest_index();
+        assert!(database.suggest_index().is_some());
+        assert_eq!(suggest_all(&database), linear);
+    }
+
+    #[test]
+    fn normalize_query_folds_diacritics() {
+        assert_eq!(normalize_query(" Súdwest-Fryslân "), "sudwest-fryslan");
+        assert_eq!(normalize_query("Curaçao"), "curacao");
+        assert_eq!(normalize_query("Doesbürg"), "doesburg");
+        assert_eq!(normalize_query("Sappemeer"), "sappemeer");
+    }
+
+    #[test]
+    fn match_range_maps_to_original_offsets() {
+        assert_eq!(match_range("dam", "Amsterdam"), Some((6, 9)));
+        assert_eq!(match_range("ams", "Amsterdam"), Some((0, 3)));
+        // "â" is two bytes in the original but folds to a single "a".
+        assert_eq!(match_range("fryslan", "Súdwest-Fryslân"), Some((9, 17)));
+        assert_eq!(match_range("amstrdam", "Amsterdam"), None);
+        assert_eq!(match_range("", "Amsterdam"), None);
+    }
+
+    #[test]
+    fn suggest_index_matches_linear_scan() {
+        let names = synthetic_street_names(2_000);
+        let index = SuggestIndex::build(names.iter().map(String::as_str), Vec::new());
+
+        let edit = ScoringConfig {
+            algorithm: SuggestAlgorithm::Edit,
+            ..ScoringConfig::default()
+        };
+        let ignoring = ScoringConfig {
+            ignore_separators: true,
+            ..ScoringConfig::default()
+        };
+        for scoring in [ScoringConfig::default(), edit, ignoring] {
+            for query in QUERIES {
+                let normalized = normalize_query(query);
+                for threshold in [0.0, 0.7, 1.6] {
+                    for limit in [0, 1, 10] {
+                        let linear = rank(
+                            &normalized,
+                            names.iter().map(String::as_str),
+                            threshold,
+                            limit,
+                            &scoring,
+                        );
+                        let indexed = index.suggest(&normalized, threshold, limit, &scoring);
+                        assert_eq!(indexed, linear, "{query} {threshold} {limit}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compare the linear scan with the index on a national-sized set of
+    /// street names. Run with
+    /// `cargo test --release suggest_index_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn suggest_index_benchmark() {
+        let names = synthetic_street_names(500_000);
+
+        let started = Instant::now();
+        let index = SuggestIndex::build(names.iter().map(String::as_str), Vec::new());
+        eprintln!("index built in {:?}", started.elapsed());
+
+        for query in QUERIES {
+            let normalized = normalize_query(query);
+
+            let started = Instant::now();
+            let linear = rank(
+                &normalized,
+                names.iter().map(String::as_str),
+                DEFAULT_SUGGEST_THRESHOLD,
+                DEFAULT_SUGGEST_LIMIT,
+                &ScoringConfig::default(),
+            );
+            let linear_time = started.elapsed();
+
+            let started = Instant::now();
+            let indexed = index.suggest(
+                &normalized,
+                DEFAULT_SUGGEST_THRESHOLD,
+                DEFAULT_SUGGEST_LIMIT,
+                &ScoringConfig::default(),
+            );
+            let indexed_time = started.elapsed();
+
+            assert_eq!(indexed, linear);
+            eprintln!("{query:>12}: linear {linear_time:>12?}, indexed {indexed_time:>12?}");
+        }
     }
 
     #[test]
@@ -291,13 +958,142 @@ mod tests {
         let needle = normalize_query("dam");
         let exact = normalize_query("amsterdam");
         let fuzzy = normalize_query("dandandimam");
-        let exact_score = fuzzy_score(&needle, &exact);
-        let fuzzy_score_value = fuzzy_score(&needle, &fuzzy);
+        let exact_score = fuzzy_score(&needle, &exact, &ScoringConfig::default());
+        let fuzzy_score_value = fuzzy_score(&needle, &fuzzy, &ScoringConfig::default());
 
         assert!(exact_score > 1.0);
         assert!(exact_score > fuzzy_score_value);
     }
 
+    #[test]
+    fn scoring_weights_change_the_ranking() {
+        // The first name has all query characters in order but few of its
+        // bigrams; the second shares most bigrams but only the first "r" in
+        // order.
+        let names = ["Rotxtxexrxdxaxm", "Xotterdax"];
+        let ranking = |scoring: &ScoringConfig| {
+            rank("rotterdam", names, 0.0, 2, scoring)
+                .into_iter()
+                .map(|suggestion| suggestion.name)
+                .collect::<Vec<_>>()
+        };
+
+        let subsequence = ScoringConfig {
+            subsequence_weight: 0.9,
+            dice_weight: 0.1,
+            ..ScoringConfig::default()
+        };
+        let dice = ScoringConfig {
+            subsequence_weight: 0.1,
+            dice_weight: 0.9,
+            ..ScoringConfig::default()
+        };
+        assert_eq!(ranking(&subsequence), ["Rotxtxexrxdxaxm", "Xotterdax"]);
+        assert_eq!(ranking(&dice), ["Xotterdax", "Rotxtxexrxdxaxm"]);
+    }
+
+    #[test]
+    fn both_algorithms_accept_a_typo() {
+        let edit = ScoringConfig {
+            algorithm: SuggestAlgorithm::Edit,
+            ..ScoringConfig::default()
+        };
+        let dice_score = fuzzy_score("rotterdm", "rotterdam", &ScoringConfig::default());
+        let edit_score = fuzzy_score("rotterdm", "rotterdam", &edit);
+
+        // One deletion in nine characters.
+        assert!((edit_score - 8.0 / 9.0).abs() < 1e-6);
+        for score in [dice_score, edit_score] {
+            assert!(score >= DEFAULT_SUGGEST_THRESHOLD, "{score}");
+        }
+
+        // A transposition is a single edit, but breaks three bigrams.
+        let edit_score = fuzzy_score("rottedram", "rotterdam", &edit);
+        let dice_score = fuzzy_score("rottedram", "rotterdam", &ScoringConfig::default());
+        assert!(edit_score > dice_score);
+        assert_eq!(
+            rank("rotterdm", ["Rotterdam", "Rotterdamseweg"], 0.0, 1, &edit)[0].name,
+            "Rotterdam"
+        );
+    }
+
+    #[test]
+    fn edit_similarity_counts_transpositions_once() {
+        assert_eq!(edit_similarity("abcd", "abcd"), 1.0);
+        assert_eq!(edit_similarity("abcd", "abdc"), 0.75);
+        assert_eq!(edit_similarity("abcd", ""), 0.0);
+        assert_eq!(edit_similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+    }
+
+    #[test]
+    fn one_character_queries_favor_short_names() {
+        let unboosted = ScoringConfig {
+            substring_boost: false,
+            ..ScoringConfig::default()
+        };
+        let score = |haystack| fuzzy_score("a", haystack, &unboosted);
+
+        // 0.6 for the match, 0.4 * 2 / 3 for the shared character and the
+        // full prefix bonus.
+        assert!((score("ab") - (0.6 + 0.4 * 2.0 / 3.0 + 0.2)).abs() < 1e-6);
+        assert!(score("ab") > score("amsterdam"));
+        assert!(score("amsterdam") > score("dam"));
+        assert_eq!(score("b"), 0.0);
+        assert_eq!(fuzzy_score("a", "b", &ScoringConfig::default()), 0.0);
+        // Contained one-character queries still get the substring boost.
+        assert!(fuzzy_score("a", "amsterdam", &ScoringConfig::default()) > 1.5);
+    }
+
+    #[test]
+    fn two_character_queries_use_bigrams() {
+        // "rt" is in order in "rotterdam" but shares no bigram with it.
+        let score = fuzzy_score("rt", "rotterdam", &ScoringConfig::default());
+        assert!((score - (0.6 + 0.1)).abs() < 1e-6);
+        assert!(fuzzy_score("ro", "rotterdam", &ScoringConfig::default()) > 1.5);
+        assert_eq!(dice_coefficient("ro", "ro"), 1.0);
+        assert_eq!(
+            fuzzy_score("xy", "rotterdam", &ScoringConfig::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn separators_can_be_ignored() {
+        let names = ["Den Haag", "'s-Gravenhage", "Den Ham", "Gravenzande"];
+        let ignoring = ScoringConfig {
+            ignore_separators: true,
+            ..ScoringConfig::default()
+        };
+        let best = |query: &str, scoring: &ScoringConfig| {
+            rank(&normalize_query(query), names, 0.0, 1, scoring)[0]
+                .name
+                .clone()
+        };
+
+        assert_eq!(best("denhaag", &ignoring), "Den Haag");
+        assert_eq!(best("s gravenhage", &ignoring), "'s-Gravenhage");
+        assert_eq!(best("Den-Haag", &ignoring), "Den Haag");
+        // By default the separators have to match.
+        let denhaag = rank("denhaag", names, 0.0, 1, &ScoringConfig::default());
+        assert!(denhaag[0].score < 1.0);
+    }
+
+    #[test]
+    fn substring_boost_can_be_disabled() {
+        let boosted = fuzzy_score("dam", "amsterdam", &ScoringConfig::default());
+        let unboosted = fuzzy_score(
+            "dam",
+            "amsterdam",
+            &ScoringConfig {
+                substring_boost: false,
+                ..ScoringConfig::default()
+            },
+        );
+
+        assert!(boosted > 1.0);
+        assert!(unboosted <= 1.0);
+    }
+
     #[test]
     fn subsequence_ratio_respects_order() {
         let needle = normalize_query("ams");
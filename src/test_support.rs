@@ -0,0 +1,68 @@
+//! Fixtures and scratch files shared by the unit tests.
+
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "compressed_database")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "compressed_database")]
+use crate::Database;
+
+/// The test fixture `test/bag.bin`, decoded once.
+///
+/// Clone it for a database to change or to hand to a
+/// [`DatabaseHandle`](crate::DatabaseHandle).
+#[cfg(feature = "compressed_database")]
+pub(crate) fn fixture_db() -> &'static Database {
+    static DATABASE: OnceLock<Database> = OnceLock::new();
+    DATABASE.get_or_init(|| {
+        let compressed = std::fs::read("test/bag.bin").unwrap();
+        let mut decoder = zstd::Decoder::new(&compressed[..]).unwrap();
+        Database::from_reader(&mut decoder).unwrap()
+    })
+}
+
+/// The bytes of the uncompressed fixture `test/bag_uncompressed.bin`, read
+/// once, for views that outlive the test.
+#[cfg(feature = "compressed_database")]
+pub(crate) fn fixture_bytes() -> &'static [u8] {
+    static BYTES: OnceLock<Vec<u8>> = OnceLock::new();
+    BYTES.get_or_init(|| std::fs::read("test/bag_uncompressed.bin").unwrap())
+}
+
+/// A path named `name` in a fresh temporary directory, which is removed with
+/// everything in it when the guard is dropped, also when a test fails.
+pub(crate) struct TempPath {
+    path: PathBuf,
+    _dir: tempfile::TempDir,
+}
+
+impl TempPath {
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = tempfile::Builder::new()
+            .prefix("bag-address-lookup-")
+            .tempdir()
+            .unwrap();
+        Self {
+            path: dir.path().join(name),
+            _dir: dir,
+        }
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
@@ -1,7 +1,11 @@
-use std::{collections::HashMap, error::Error};
+use std::{cmp::Ordering, collections::HashMap, error::Error};
+
+use rayon::prelude::*;
 
 use crate::{
-    Address, Locality, NumberRange, PublicSpace, encode_pc,
+    Address, AddressPoint, HouseNumberSuffix, Locality, NumberRange, PublicSpace, RdPoint,
+    database::encode_house_number_suffix,
+    encode_pc,
     parsing::{MunicipalityRelation, municipalities::Municipality},
 };
 
@@ -19,12 +23,24 @@ pub struct LocalityMap {
 /// Entries are deduplicated on the BAG id (not the name), so that two
 /// Woonplaatsen sharing a name but differing in identificatiecode remain
 /// distinct. Sorting is lexicographic on (name, id) for stable output.
-pub fn index_localities(localities: Vec<Locality>) -> Result<LocalityMap, Box<dyn Error>> {
+///
+/// With `normalize_names`, names are cleaned up by [`normalize_name`] and
+/// names differing only in case get one spelling: the variant that sorts
+/// first, which prefers capitals (`Den Haag` over `den haag`). Localities
+/// keep their own entries, but share that spelling.
+pub fn index_localities(
+    localities: Vec<Locality>,
+    normalize_names: bool,
+) -> Result<LocalityMap, Box<dyn Error>> {
+    let names = display_names(
+        localities.iter().map(|locality| locality.name.as_str()),
+        normalize_names,
+    );
     let mut unique: HashMap<u16, (String, bool)> = HashMap::with_capacity(localities.len());
-    for locality in localities {
+    for (locality, name) in localities.into_iter().zip(names) {
         unique
             .entry(locality.id)
-            .or_insert((locality.name, locality.had_suffix));
+            .or_insert((name, locality.had_suffix));
     }
 
     let mut entries: Vec<(String, u16, bool)> = unique
@@ -161,16 +177,31 @@ pub fn index_municipalities(
     })
 }
 
+pub struct PublicSpaceMap {
+    /// Distinct public space names, sorted.
+    pub public_space_names: Vec<String>,
+    /// BAG openbare ruimte id -> (public space name index, locality index).
+    pub public_spaces_map: HashMap<u64, (u32, u16)>,
+}
+
 /// Build public space name indexes and map ids to locality indexes.
 ///
 /// Public spaces referencing a locality that isn't in `locality_map` are
 /// dropped — this happens when BAG keeps an issued street pointing to a
 /// Woonplaats whose active lifecycle has ended (e.g. after a municipality
 /// merger). We log the count so skew stays visible.
+///
+/// With `normalize_names`, names are cleaned up by [`normalize_name`] and
+/// names differing only in case share one entry. Its display form is the
+/// variant that sorts first, which prefers capitals (`Kerkstraat` over
+/// `kerkstraat`).
+///
+/// Fails when there are more distinct names than fit a `u32` index.
 pub fn index_public_spaces(
     public_spaces: Vec<PublicSpace>,
     locality_map: HashMap<u16, u16>,
-) -> (Vec<String>, HashMap<u64, (u32, u16)>) {
+    normalize_names: bool,
+) -> Result<PublicSpaceMap, Box<dyn Error>> {
     let mut kept: Vec<PublicSpace> = Vec::with_capacity(public_spaces.len());
     let mut orphaned = 0usize;
     for public_space in public_spaces {
@@ -184,65 +215,165 @@ pub fn index_public_spaces(
         eprintln!("Warning: Dropped {orphaned} public space(s) referencing an unknown locality");
     }
 
-    let mut public_space_names: Vec<String> = kept
-        .iter()
-        .map(|public_space| public_space.name.clone())
-        .collect();
+    let names = display_names(
+        kept.iter().map(|public_space| public_space.name.as_str()),
+        normalize_names,
+    );
+    let mut public_space_names = names.clone();
     public_space_names.sort();
     public_space_names.dedup();
 
+    if public_space_names.len() > u32::MAX as usize {
+        return Err("too many public space names for u32 index".into());
+    }
+
     let mut name_index = HashMap::with_capacity(public_space_names.len());
     for (index, name) in public_space_names.iter().enumerate() {
-        name_index.insert(name.clone(), index as u32);
+        name_index.insert(name.as_str(), index as u32);
     }
 
     let mut public_spaces_map = HashMap::with_capacity(kept.len());
-    for public_space in kept {
-        let public_space_index = *name_index
-            .get(&public_space.name)
-            .expect("Public space name not found in name index");
-        let locality_index = *locality_map
-            .get(&public_space.locality_id)
-            .expect("Locality ID not found in locality map");
+    for (public_space, name) in kept.iter().zip(&names) {
+        // Both lookups succeed: names come from `kept` and unknown
+        // localities were filtered out above.
+        let (Some(&public_space_index), Some(&locality_index)) = (
+            name_index.get(name.as_str()),
+            locality_map.get(&public_space.locality_id),
+        ) else {
+            return Err(format!("public space {} could not be indexed", public_space.id).into());
+        };
 
         public_spaces_map.insert(public_space.id, (public_space_index, locality_index));
     }
 
-    (public_space_names, public_spaces_map)
+    Ok(PublicSpaceMap {
+        public_space_names,
+        public_spaces_map,
+    })
+}
+
+/// Trim a name and collapse inner runs of whitespace into one space, so
+/// source rows differing only in spacing get the same name.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The display form of each of `names`: the name as is, or with
+/// `normalize_names` the first in sort order of the names equal to it after
+/// [`normalize_name`] but for case.
+fn display_names<'a>(names: impl Iterator<Item = &'a str>, normalize_names: bool) -> Vec<String> {
+    if !normalize_names {
+        return names.map(str::to_string).collect();
+    }
+
+    let normalized: Vec<String> = names.map(normalize_name).collect();
+    let mut canonical: HashMap<String, &str> = HashMap::new();
+    for name in &normalized {
+        let spelling = canonical.entry(name.to_lowercase()).or_insert(name);
+        if name.as_str() < *spelling {
+            *spelling = name;
+        }
+    }
+    normalized
+        .iter()
+        .map(|name| canonical[&name.to_lowercase()].to_string())
+        .collect()
 }
 
 /// Encode addresses into sorted, contiguous number ranges.
 pub fn encode_addresses(
-    addresses: Vec<Address>,
+    addresses: &[Address],
     public_spaces_map: &HashMap<u64, (u32, u16)>,
 ) -> Vec<NumberRange> {
-    let mut entries = Vec::with_capacity(addresses.len());
+    let mut entries = encoded_entries(addresses, public_spaces_map);
 
-    for address in addresses {
-        let Some((public_space_index, locality_index)) =
-            public_spaces_map.get(&address.public_space_id)
-        else {
-            continue;
-        };
+    // The national dataset has millions of entries, so sort on all cores.
+    // The sort is stable and entries that compare equal are identical, so
+    // the result is the same as that of a sequential sort.
+    entries.par_sort_by(entry_order);
 
-        let pc_encoded = encode_pc(address.postal_code.as_bytes());
+    ranges_from_sorted(entries)
+}
 
-        entries.push(EncodedEntry {
-            postal_code: pc_encoded,
-            house_number: address.house_number,
-            public_space_index: *public_space_index,
-            locality_index: *locality_index,
-        });
+/// The addresses on a known public space, with their indexes.
+fn encoded_entries(
+    addresses: &[Address],
+    public_spaces_map: &HashMap<u64, (u32, u16)>,
+) -> Vec<EncodedEntry> {
+    addresses
+        .iter()
+        .filter_map(|address| encoded_entry(address, public_spaces_map))
+        .collect()
+}
+
+/// The entry of an address on a known public space.
+fn encoded_entry(
+    address: &Address,
+    public_spaces_map: &HashMap<u64, (u32, u16)>,
+) -> Option<EncodedEntry> {
+    let &(public_space_index, locality_index) = public_spaces_map.get(&address.public_space_id)?;
+
+    Some(EncodedEntry {
+        postal_code: encode_pc(address.postal_code.as_bytes()),
+        house_number: address.house_number,
+        public_space_index,
+        locality_index,
+    })
+}
+
+/// Encode addresses into number ranges and their positions, like
+/// [`encode_addresses`] followed by [`encode_range_points`].
+///
+/// Takes the addresses and points by value and frees each record as soon as
+/// it is reduced to the few fields the ranges are built from, so the full
+/// records are never held together with the sorted entries and the ranges.
+pub(crate) fn encode_address_ranges(
+    addresses: Vec<Address>,
+    public_spaces_map: &HashMap<u64, (u32, u16)>,
+    points: Vec<AddressPoint>,
+) -> (Vec<NumberRange>, Vec<RdPoint>) {
+    let has_points = !points.is_empty();
+    let positions: HashMap<u64, (f64, f64)> = points
+        .into_iter()
+        .map(|point| (point.address_id, (point.x, point.y)))
+        .collect();
+
+    let mut entries = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if let Some(entry) = encoded_entry(&address, public_spaces_map) {
+            entries.push((entry, positions.get(&address.id).copied()));
+        }
     }
+    drop(positions);
+
+    entries.par_sort_by(|a, b| entry_order(&a.0, &b.0));
+    let ranges = ranges_from_sorted(entries.iter().map(|(entry, _)| *entry));
+
+    let range_points = if has_points {
+        sum_range_points(
+            &ranges,
+            entries
+                .into_iter()
+                .filter_map(|(entry, position)| Some((entry, position?))),
+        )
+    } else {
+        Vec::new()
+    };
+
+    (ranges, range_points)
+}
 
-    entries.sort_by(|a, b| {
-        a.postal_code
-            .cmp(&b.postal_code)
-            .then_with(|| a.public_space_index.cmp(&b.public_space_index))
-            .then_with(|| a.locality_index.cmp(&b.locality_index))
-            .then_with(|| a.house_number.cmp(&b.house_number))
-    });
+/// Order of the entries the ranges are built from.
+fn entry_order(a: &EncodedEntry, b: &EncodedEntry) -> Ordering {
+    a.postal_code
+        .cmp(&b.postal_code)
+        .then_with(|| a.public_space_index.cmp(&b.public_space_index))
+        .then_with(|| a.locality_index.cmp(&b.locality_index))
+        .then_with(|| a.house_number.cmp(&b.house_number))
+}
 
+/// Group entries sorted by [`entry_order`] into number ranges.
+fn ranges_from_sorted(entries: impl IntoIterator<Item = EncodedEntry>) -> Vec<NumberRange> {
     let mut ranges = Vec::new();
     let mut current: Option<NumberRange> = None;
 
@@ -259,7 +390,14 @@ pub fn encode_addresses(
                     && range.public_space_index == public_space_index
                     && range.locality_index == locality_index =>
             {
-                let range_end = range.start + range.length as u32 * range.step as u32;
+                // The end is the last number added, so it fits. Should it
+                // not, skip the number rather than wrap around.
+                let Some(range_end) = range
+                    .start
+                    .checked_add(u32::from(range.length) * u32::from(range.step))
+                else {
+                    continue;
+                };
                 if house_number <= range_end {
                     // Duplicate or already covered by the range
                     continue;
@@ -311,6 +449,7 @@ pub fn encode_addresses(
     ranges
 }
 
+#[derive(Clone, Copy)]
 struct EncodedEntry {
     postal_code: u32,
     house_number: u32,
@@ -318,10 +457,139 @@ struct EncodedEntry {
     locality_index: u16,
 }
 
+/// Collect the house letters and additions of all addresses on a known public
+/// space, sorted by (postal code, house number, letter, addition).
+///
+/// Addresses without a letter or addition are skipped, as are values that do
+/// not fit the BAG format (one ASCII letter, up to four ASCII alphanumerics).
+pub fn encode_house_number_suffixes(
+    addresses: &[Address],
+    public_spaces_map: &HashMap<u64, (u32, u16)>,
+) -> Vec<HouseNumberSuffix> {
+    let mut suffixes: Vec<HouseNumberSuffix> = addresses
+        .iter()
+        .filter(|address| public_spaces_map.contains_key(&address.public_space_id))
+        .filter_map(|address| {
+            let (letter, addition) = encode_house_number_suffix(
+                address.house_letter.as_deref(),
+                address.house_number_addition.as_deref(),
+            )?;
+            if letter == 0 && addition == [0; 4] {
+                return None;
+            }
+            Some(HouseNumberSuffix {
+                postal_code: encode_pc(address.postal_code.as_bytes()),
+                house_number: address.house_number,
+                letter,
+                addition,
+            })
+        })
+        .collect();
+
+    suffixes.sort_by_key(|s| (s.postal_code, s.house_number, s.letter, s.addition));
+    suffixes.dedup();
+    suffixes
+}
+
+/// Average the Verblijfsobject positions of the addresses in each range.
+///
+/// Returns one point per range, parallel to `ranges`, with
+/// [`RdPoint::MISSING`] for ranges none of whose addresses has a position.
+/// Returns an empty list when there are no positions at all, so databases
+/// built without Verblijfsobject data carry no coordinate table.
+pub fn encode_range_points(
+    ranges: &[NumberRange],
+    addresses: &[Address],
+    public_spaces_map: &HashMap<u64, (u32, u16)>,
+    points: &[AddressPoint],
+) -> Vec<RdPoint> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let positions: HashMap<u64, (f64, f64)> = points
+        .iter()
+        .map(|point| (point.address_id, (point.x, point.y)))
+        .collect();
+
+    sum_range_points(
+        ranges,
+        addresses.iter().filter_map(|address| {
+            let &position = positions.get(&address.id)?;
+            Some((encoded_entry(address, public_spaces_map)?, position))
+        }),
+    )
+}
+
+/// Average the positions of the entries in each range.
+fn sum_range_points(
+    ranges: &[NumberRange],
+    located: impl Iterator<Item = (EncodedEntry, (f64, f64))>,
+) -> Vec<RdPoint> {
+    // (sum x, sum y, count) per range
+    let mut sums = vec![(0.0f64, 0.0f64, 0u32); ranges.len()];
+
+    for (entry, (x, y)) in located {
+        let key = (
+            entry.postal_code,
+            entry.public_space_index,
+            entry.locality_index,
+        );
+
+        // Ranges are sorted by (postal code, public space, locality, start)
+        // and do not overlap, so the candidate is the last one starting at or
+        // before the house number.
+        let index = ranges.partition_point(|range| {
+            (
+                range.postal_code,
+                range.public_space_index,
+                range.locality_index,
+                range.start,
+            ) <= (key.0, key.1, key.2, entry.house_number)
+        });
+        let Some(index) = index.checked_sub(1) else {
+            continue;
+        };
+        let range = &ranges[index];
+        if (
+            range.postal_code,
+            range.public_space_index,
+            range.locality_index,
+        ) != key
+        {
+            continue;
+        }
+        let offset = entry.house_number - range.start;
+        let step = u32::from(range.step.max(1));
+        if !offset.is_multiple_of(step) || offset / step > u32::from(range.length) {
+            continue;
+        }
+
+        let sum = &mut sums[index];
+        sum.0 += x;
+        sum.1 += y;
+        sum.2 += 1;
+    }
+
+    sums.into_iter()
+        .map(|(x, y, count)| {
+            if count == 0 {
+                RdPoint::MISSING
+            } else {
+                RdPoint::from_metres(x / f64::from(count), y / f64::from(count))
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{LocalityMap, encode_addresses, index_localities, index_public_spaces};
-    use crate::{Address, Locality, NumberRange, PublicSpace, encode_pc};
+    use super::{
+        LocalityMap, PublicSpaceMap, encode_address_ranges, encode_addresses,
+        encode_house_number_suffixes, encode_range_points, encoded_entries, entry_order,
+        index_localities, index_public_spaces, normalize_name, ranges_from_sorted,
+    };
+    use crate::{Address, AddressPoint, Locality, NumberRange, PublicSpace, RdPoint, encode_pc};
 
     fn locality_map_fixture() -> LocalityMap {
         let localities = vec![
@@ -342,7 +610,7 @@ mod tests {
             },
         ];
 
-        index_localities(localities).expect("locality map fixture")
+        index_localities(localities, false).expect("locality map fixture")
     }
 
     #[test]
@@ -367,7 +635,7 @@ mod tests {
             })
             .collect();
 
-        let result = index_localities(localities);
+        let result = index_localities(localities, false);
 
         assert!(result.is_err());
     }
@@ -393,7 +661,10 @@ mod tests {
             },
         ];
 
-        let (names, map) = index_public_spaces(public_spaces, locality_map);
+        let PublicSpaceMap {
+            public_space_names: names,
+            public_spaces_map: map,
+        } = index_public_spaces(public_spaces, locality_map, false).unwrap();
 
         assert_eq!(names, vec!["Hoofdweg", "Spoorstraat"]);
         assert_eq!(map.get(&1), Some(&(0, 0)));
@@ -401,6 +672,115 @@ mod tests {
         assert_eq!(map.get(&3), Some(&(1, 1)));
     }
 
+    #[test]
+    fn normalized_localities_collapse_whitespace_variants() {
+        let localities = || {
+            vec![
+                Locality {
+                    id: 20,
+                    name: " den  Haag".to_string(),
+                    had_suffix: false,
+                },
+                Locality {
+                    id: 20,
+                    name: "Den Haag ".to_string(),
+                    had_suffix: false,
+                },
+                Locality {
+                    id: 21,
+                    name: "den haag".to_string(),
+                    had_suffix: false,
+                },
+            ]
+        };
+
+        // The variants of locality 20 collapse, and locality 21 keeps its
+        // own entry but gets the same spelling.
+        let result = index_localities(localities(), true).unwrap();
+        assert_eq!(result.locality_names, vec!["Den Haag", "Den Haag"]);
+        assert_eq!(result.locality_codes, vec![20, 21]);
+
+        // Raw mode keeps the first name of each locality as is.
+        let result = index_localities(localities(), false).unwrap();
+        assert_eq!(result.locality_names, vec![" den  Haag", "den haag"]);
+        assert_eq!(result.locality_codes, vec![20, 21]);
+    }
+
+    #[test]
+    fn normalized_public_spaces_collapse_variants() {
+        let public_spaces = || {
+            vec![
+                PublicSpace {
+                    id: 1,
+                    name: "kerkstraat".to_string(),
+                    locality_id: 10,
+                },
+                PublicSpace {
+                    id: 2,
+                    name: " Kerkstraat".to_string(),
+                    locality_id: 11,
+                },
+                PublicSpace {
+                    id: 3,
+                    name: "Lange  Haven".to_string(),
+                    locality_id: 10,
+                },
+            ]
+        };
+
+        let LocalityMap { locality_map, .. } = locality_map_fixture();
+        let PublicSpaceMap {
+            public_space_names: names,
+            public_spaces_map: map,
+        } = index_public_spaces(public_spaces(), locality_map, true).unwrap();
+        assert_eq!(names, vec!["Kerkstraat", "Lange Haven"]);
+        assert_eq!(map.get(&1), Some(&(0, 2)));
+        assert_eq!(map.get(&2), Some(&(0, 0)));
+        assert_eq!(map.get(&3), Some(&(1, 2)));
+
+        // Raw mode keeps every variant as-is.
+        let LocalityMap { locality_map, .. } = locality_map_fixture();
+        let PublicSpaceMap {
+            public_space_names: names,
+            ..
+        } = index_public_spaces(public_spaces(), locality_map, false).unwrap();
+        assert_eq!(names, vec![" Kerkstraat", "Lange  Haven", "kerkstraat"]);
+    }
+
+    #[test]
+    fn normalize_name_trims_and_collapses_whitespace() {
+        assert_eq!(normalize_name("  Den \t Haag \n"), "Den Haag");
+        assert_eq!(normalize_name("Kerkstraat"), "Kerkstraat");
+    }
+
+    #[test]
+    fn index_public_spaces_drops_unknown_localities() {
+        let LocalityMap { locality_map, .. } = locality_map_fixture();
+        let public_spaces = vec![
+            PublicSpace {
+                id: 1,
+                name: "Hoofdweg".to_string(),
+                locality_id: 11,
+            },
+            // Locality 99 expired and was filtered out.
+            PublicSpace {
+                id: 2,
+                name: "Verdwenen Laan".to_string(),
+                locality_id: 99,
+            },
+        ];
+
+        let PublicSpaceMap {
+            public_space_names: names,
+            public_spaces_map: map,
+        } = index_public_spaces(public_spaces, locality_map, false).unwrap();
+
+        assert_eq!(names, vec!["Hoofdweg"]);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&1), Some(&(0, 0)));
+        assert!(!map.contains_key(&2));
+    }
+
     #[test]
     fn encode_addresses_groups_and_sorts_ranges() {
         let mut public_spaces_map = std::collections::HashMap::new();
@@ -409,50 +789,64 @@ mod tests {
 
         let addresses = vec![
             Address {
+                id: 1,
                 house_number: 2,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 1,
             },
             Address {
+                id: 2,
                 house_number: 1,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 1,
             },
             Address {
+                id: 3,
                 house_number: 2,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 1,
             },
             Address {
+                id: 4,
                 house_number: 4,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 1,
             },
             Address {
+                id: 5,
                 house_number: 1,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 2,
             },
             Address {
+                id: 6,
                 house_number: 3,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AC".to_string(),
                 public_space_id: 1,
             },
             Address {
+                id: 7,
                 house_number: 9,
-
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "1234AB".to_string(),
                 public_space_id: 999,
             },
         ];
 
-        let ranges = encode_addresses(addresses, &public_spaces_map);
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
 
         let pc_ab = encode_pc(b"1234AB");
         let pc_ac = encode_pc(b"1234AC");
@@ -491,15 +885,7 @@ mod tests {
             },
         ];
 
-        assert_eq!(ranges.len(), expected.len());
-        for (actual, expected) in ranges.iter().zip(expected.iter()) {
-            assert_eq!(actual.postal_code, expected.postal_code);
-            assert_eq!(actual.start, expected.start);
-            assert_eq!(actual.length, expected.length);
-            assert_eq!(actual.step, expected.step);
-            assert_eq!(actual.public_space_index, expected.public_space_index);
-            assert_eq!(actual.locality_index, expected.locality_index);
-        }
+        assert_eq!(ranges, expected);
     }
 
     #[test]
@@ -511,13 +897,16 @@ mod tests {
         let addresses: Vec<Address> = [1, 3, 5, 7, 2, 4, 6]
             .into_iter()
             .map(|n| Address {
+                id: u64::from(n),
                 house_number: n,
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "5678CD".to_string(),
                 public_space_id: 1,
             })
             .collect();
 
-        let ranges = encode_addresses(addresses, &public_spaces_map);
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
 
         let pc = encode_pc(b"5678CD");
 
@@ -539,13 +928,16 @@ mod tests {
         let addresses: Vec<Address> = [1, 3, 5, 7, 9]
             .into_iter()
             .map(|n| Address {
+                id: u64::from(n),
                 house_number: n,
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "5678CD".to_string(),
                 public_space_id: 1,
             })
             .collect();
 
-        let ranges = encode_addresses(addresses, &public_spaces_map);
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
 
         assert_eq!(ranges.len(), 1);
         assert_eq!(ranges[0].start, 1);
@@ -562,22 +954,245 @@ mod tests {
         let addresses: Vec<Address> = [2, 4, 6, 9]
             .into_iter()
             .map(|n| Address {
+                id: u64::from(n),
+                house_number: n,
+                house_letter: None,
+                house_number_addition: None,
+                postal_code: "5678CD".to_string(),
+                public_space_id: 1,
+            })
+            .collect();
+
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
+
+        let pc = encode_pc(b"5678CD");
+        let range = |start, length, step| NumberRange {
+            postal_code: pc,
+            start,
+            length,
+            public_space_index: 0,
+            locality_index: 0,
+            step,
+        };
+        // 2,4,6 with step=2, then 9 alone
+        assert_eq!(ranges, [range(2, 2, 2), range(9, 0, 1)]);
+    }
+
+    #[test]
+    fn encode_addresses_handles_house_numbers_near_u32_max() {
+        let mut public_spaces_map = std::collections::HashMap::new();
+        public_spaces_map.insert(1u64, (0, 0));
+
+        let addresses: Vec<Address> = [u32::MAX - 4, u32::MAX - 2, u32::MAX, 7]
+            .into_iter()
+            .map(|n| Address {
+                id: u64::from(n),
                 house_number: n,
+                house_letter: None,
+                house_number_addition: None,
                 postal_code: "5678CD".to_string(),
                 public_space_id: 1,
             })
             .collect();
 
-        let ranges = encode_addresses(addresses, &public_spaces_map);
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
 
         assert_eq!(ranges.len(), 2);
-        // First range: 2,4,6 with step=2
-        assert_eq!(ranges[0].start, 2);
-        assert_eq!(ranges[0].length, 2);
-        assert_eq!(ranges[0].step, 2);
-        // Second range: 9 alone
-        assert_eq!(ranges[1].start, 9);
-        assert_eq!(ranges[1].length, 0);
-        assert_eq!(ranges[1].step, 1);
+        assert_eq!(ranges[1].start, u32::MAX - 4);
+        assert_eq!(ranges[1].length, 2);
+        assert_eq!(ranges[1].step, 2);
+
+        let db = crate::DatabaseHandle::Decoded(
+            crate::Database::try_new(
+                vec!["Utrecht".to_string()],
+                vec!["Dorpsweg".to_string()],
+                ranges,
+            )
+            .unwrap(),
+        );
+        for house_number in [7, u32::MAX - 4, u32::MAX - 2, u32::MAX] {
+            assert_eq!(
+                db.lookup("5678CD", house_number),
+                Some(("Dorpsweg", "Utrecht"))
+            );
+        }
+        assert!(db.lookup("5678CD", u32::MAX - 1).is_none());
+        let (_, range) = db.ranges().nth(1).unwrap();
+        assert_eq!(range.house_numbers().last(), Some(u32::MAX));
+    }
+
+    #[test]
+    fn parallel_encode_matches_sequential_encode() {
+        let data = crate::ParsedData::from_bag_zip(
+            std::path::Path::new("test/bag.zip"),
+            std::time::Instant::now(),
+        )
+        .unwrap();
+        let LocalityMap { locality_map, .. } = index_localities(data.localities, true).unwrap();
+        let PublicSpaceMap {
+            public_spaces_map, ..
+        } = index_public_spaces(data.public_spaces, locality_map, true).unwrap();
+
+        let mut entries = encoded_entries(&data.addresses, &public_spaces_map);
+        entries.sort_by(entry_order);
+        let expected = ranges_from_sorted(entries);
+        assert!(!expected.is_empty());
+
+        for threads in [1, 4] {
+            let ranges = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| encode_addresses(&data.addresses, &public_spaces_map));
+            assert_eq!(ranges, expected, "{threads} threads");
+        }
+    }
+
+    #[test]
+    fn owned_encode_matches_borrowed_encode() {
+        let data = crate::ParsedData::from_bag_zip(
+            std::path::Path::new("test/bag.zip"),
+            std::time::Instant::now(),
+        )
+        .unwrap();
+        // The fixture has no Verblijfsobjecten, so give every address a position.
+        let address_points: Vec<AddressPoint> = data
+            .addresses
+            .iter()
+            .map(|address| AddressPoint {
+                address_id: address.id,
+                x: 155_000.0 + f64::from(address.house_number) * 1.37,
+                y: 463_000.0 + (address.id % 1000) as f64 * 0.71,
+            })
+            .collect();
+        let LocalityMap { locality_map, .. } = index_localities(data.localities, true).unwrap();
+        let PublicSpaceMap {
+            public_spaces_map, ..
+        } = index_public_spaces(data.public_spaces, locality_map, true).unwrap();
+
+        let expected_ranges = encode_addresses(&data.addresses, &public_spaces_map);
+        let expected_points = encode_range_points(
+            &expected_ranges,
+            &data.addresses,
+            &public_spaces_map,
+            &address_points,
+        );
+
+        let (ranges, points) =
+            encode_address_ranges(data.addresses, &public_spaces_map, address_points);
+        assert_eq!(ranges, expected_ranges);
+        assert!(points.iter().any(|point| *point != RdPoint::MISSING));
+        assert_eq!(points, expected_points);
+    }
+
+    #[test]
+    fn lookups_respect_house_number_parity() {
+        // 1234AB has both sides of the street, 5678CD only the odd side.
+        let csv = "1234AB,1,Kerkstraat,Utrecht\n\
+                   1234AB,2,Kerkstraat,Utrecht\n\
+                   1234AB,3,Kerkstraat,Utrecht\n\
+                   1234AB,4,Kerkstraat,Utrecht\n\
+                   5678CD,1,Dorpsweg,Utrecht\n\
+                   5678CD,3,Dorpsweg,Utrecht\n\
+                   5678CD,5,Dorpsweg,Utrecht\n";
+        let db = crate::DatabaseHandle::Decoded(crate::Database::from_csv(csv.as_bytes()).unwrap());
+
+        for n in 1..=4 {
+            assert!(db.lookup("1234AB", n).is_some(), "1234AB {n}");
+        }
+        assert!(db.lookup("1234AB", 5).is_none());
+
+        for n in [1, 3, 5] {
+            assert!(db.lookup("5678CD", n).is_some(), "5678CD {n}");
+        }
+        for n in [0, 2, 4, 6, 7] {
+            assert!(db.lookup("5678CD", n).is_none(), "5678CD {n}");
+        }
+    }
+
+    #[test]
+    fn encode_range_points_averages_per_range() {
+        let mut public_spaces_map = std::collections::HashMap::new();
+        public_spaces_map.insert(1u64, (0, 0));
+
+        // 2,4,6 and 9 form two ranges; 9 has no position
+        let addresses: Vec<Address> = [2, 4, 6, 9]
+            .into_iter()
+            .map(|n| Address {
+                id: u64::from(n),
+                house_number: n,
+                house_letter: None,
+                house_number_addition: None,
+                postal_code: "5678CD".to_string(),
+                public_space_id: 1,
+            })
+            .collect();
+        let ranges = encode_addresses(&addresses, &public_spaces_map);
+
+        let points = [
+            AddressPoint {
+                address_id: 2,
+                x: 100.0,
+                y: 200.0,
+            },
+            AddressPoint {
+                address_id: 6,
+                x: 102.0,
+                y: 201.0,
+            },
+            // Not an address in the set
+            AddressPoint {
+                address_id: 42,
+                x: 0.0,
+                y: 0.0,
+            },
+        ];
+
+        let range_points = encode_range_points(&ranges, &addresses, &public_spaces_map, &points);
+
+        assert_eq!(
+            range_points,
+            vec![RdPoint { x: 1010, y: 2005 }, RdPoint::MISSING]
+        );
+        assert!(encode_range_points(&ranges, &addresses, &public_spaces_map, &[]).is_empty());
+    }
+
+    #[test]
+    fn encode_house_number_suffixes_keeps_only_suffixed_addresses() {
+        let mut public_spaces_map = std::collections::HashMap::new();
+        public_spaces_map.insert(1u64, (0, 0));
+
+        let address = |house_number, letter: Option<&str>, addition: Option<&str>, ps_id| Address {
+            id: 0,
+            house_number,
+            house_letter: letter.map(str::to_string),
+            house_number_addition: addition.map(str::to_string),
+            postal_code: "1234AB".to_string(),
+            public_space_id: ps_id,
+        };
+        let addresses = vec![
+            address(12, Some("B"), None, 1),
+            address(12, None, None, 1),
+            address(12, Some("A"), Some("1"), 1),
+            address(3, None, Some("bis"), 1),
+            address(12, Some("B"), None, 1),
+            address(5, Some("A"), None, 999),
+            address(6, Some("AB"), None, 1),
+        ];
+
+        let suffixes = encode_house_number_suffixes(&addresses, &public_spaces_map);
+
+        let found: Vec<_> = suffixes
+            .iter()
+            .map(|s| (s.house_number, s.letter(), s.addition()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, None, Some("bis")),
+                (12, Some('A'), Some("1")),
+                (12, Some('B'), None),
+            ]
+        );
     }
 }
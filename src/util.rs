@@ -0,0 +1,42 @@
+//! Helpers shared by the parsers and the database.
+
+/// Minimal CSV line parser: double-quoted fields, `""` escapes a literal
+/// quote, comma separator outside quotes. RVIG Tabel 33 and the address CSV
+/// of `Database::from_csv` follow this shape.
+pub(crate) fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                current.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) => in_quotes = true,
+            (',', false) => out.push(std::mem::take(&mut current)),
+            (ch, _) => current.push(ch),
+        }
+    }
+    out.push(current);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_csv_line;
+
+    #[test]
+    fn parse_csv_line_handles_quoted_fields() {
+        let fields = parse_csv_line(r#""0014","Groningen","","",""#);
+        assert_eq!(fields, vec!["0014", "Groningen", "", "", ""]);
+    }
+
+    #[test]
+    fn parse_csv_line_unescapes_doubled_quotes() {
+        let fields = parse_csv_line(r#""a","b""c","d""#);
+        assert_eq!(fields, vec!["a", r#"b"c"#, "d"]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Peak heap use of building a database from parsed BAG data.
+//!
+//! A test binary of its own, as it replaces the global allocator to count
+//! the bytes in use.
+
+#![cfg(feature = "create")]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use bag_address_lookup::{
+    Address, AddressPoint, Database, LocalityMap, ParsedData, PublicSpaceMap, encode_addresses,
+    encode_house_number_suffixes, encode_range_points, index_localities, index_municipalities,
+    index_public_spaces,
+};
+
+/// Times the fixture addresses are repeated.
+const COPIES: u64 = 5_000;
+
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, keeping track of the bytes in use and their peak.
+struct Tracking;
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        IN_USE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            IN_USE.fetch_sub(layout.size(), Ordering::SeqCst);
+            grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn grow(size: usize) {
+    let in_use = IN_USE.fetch_add(size, Ordering::SeqCst) + size;
+    PEAK.fetch_max(in_use, Ordering::SeqCst);
+}
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+/// Run `f`, returning its result and the most bytes in use meanwhile.
+fn peak_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    PEAK.store(IN_USE.load(Ordering::SeqCst), Ordering::SeqCst);
+    let result = f();
+    (result, PEAK.load(Ordering::SeqCst))
+}
+
+/// The fixture, with its addresses repeated at higher house numbers so they
+/// take most of the memory, and a position for every address as the national
+/// extract has.
+fn parsed_fixture() -> ParsedData {
+    let mut data = ParsedData::from_bag_zip(Path::new("test/bag.zip"), Instant::now()).unwrap();
+    let fixture = std::mem::take(&mut data.addresses);
+    data.addresses = (0..COPIES)
+        .flat_map(|copy| {
+            fixture.iter().map(move |address| Address {
+                id: address.id + copy * 1_000_000,
+                house_number: address.house_number + copy as u32 * 1_000,
+                house_letter: address.house_letter.clone(),
+                house_number_addition: address.house_number_addition.clone(),
+                postal_code: address.postal_code.clone(),
+                public_space_id: address.public_space_id,
+            })
+        })
+        .collect();
+    data.address_points = data
+        .addresses
+        .iter()
+        .map(|address| AddressPoint {
+            address_id: address.id,
+            x: 155_000.0,
+            y: 463_000.0,
+        })
+        .collect();
+    data
+}
+
+/// The steps of [`Database::from_parsed_data`] on borrowed addresses, which
+/// keeps them alive until all ranges and positions are built.
+fn build_from_borrowed_addresses(data: ParsedData) -> usize {
+    let ParsedData {
+        addresses,
+        public_spaces,
+        localities,
+        municipality_relations,
+        address_points,
+        report: _,
+    } = data;
+    let LocalityMap {
+        locality_names,
+        locality_map,
+        ..
+    } = index_localities(localities, true).unwrap();
+    let _municipalities = index_municipalities(
+        municipality_relations,
+        &[],
+        &locality_map,
+        locality_names.len(),
+    )
+    .unwrap();
+    let PublicSpaceMap {
+        public_spaces_map, ..
+    } = index_public_spaces(public_spaces, locality_map, true).unwrap();
+    let _suffixes = encode_house_number_suffixes(&addresses, &public_spaces_map);
+    let ranges = encode_addresses(&addresses, &public_spaces_map);
+    let _points = encode_range_points(&ranges, &addresses, &public_spaces_map, &address_points);
+    ranges.len()
+}
+
+#[test]
+fn building_frees_addresses_before_the_peak() {
+    // Sort on this thread only, so no worker allocates meanwhile.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let data = parsed_fixture();
+    let (borrowed_ranges, borrowed_peak) =
+        pool.install(|| peak_during(|| build_from_borrowed_addresses(data)));
+
+    let data = parsed_fixture();
+    let (database, peak) =
+        pool.install(|| peak_during(|| Database::from_parsed_data(data, &[], true).unwrap()));
+
+    assert_eq!(database.ranges.len(), borrowed_ranges);
+    assert_eq!(database.range_points.len(), borrowed_ranges);
+    assert!(
+        peak < borrowed_peak,
+        "peak of {peak} bytes, {borrowed_peak} with borrowed addresses"
+    );
+}